@@ -1,3 +1,4 @@
-INSERT INTO employee (employee_id, firstname, lastname, password, pw_salt, email, weekly_time, address_id) VALUES 
-(1, 'bob', 'baumeister', 'secret', 'no', 'test@test.de', 'PT38H', 1),
-(2, 'leck', 'eier', 'catgirls123', null, 'gargar@mehl.vn', 'PT18H', 1);
+INSERT INTO employee (employee_id, firstname, lastname, password, pw_salt, email, weekly_time, address_id, role) VALUES 
+(1, 'bob', 'baumeister', 'secret', 'no', 'test@test.de', 'PT38H', 1, 'employee'),
+(2, 'leck', 'eier', 'catgirls123', null, 'gargar@mehl.vn', 'PT18H', 1, 'employee'),
+(3, 'mia', 'meister', 'boss', null, 'chef@test.de', 'PT40H', 1, 'manager');
@@ -1 +1 @@
-TRUNCATE address, employee, task, worktime;
+TRUNCATE address, employee, task, worktime, notification, team, issued_report, report_delivery, archived_report, customer, project, task_assignment, task_budget_alert, billing_rate, absence;
@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS notification;
+
+ALTER TABLE worktime
+DROP COLUMN IF EXISTS needs_review;
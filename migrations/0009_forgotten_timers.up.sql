@@ -0,0 +1,10 @@
+ALTER TABLE worktime
+ADD COLUMN needs_review BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE IF NOT EXISTS notification (
+    notification_id SERIAL PRIMARY KEY,
+    employee_id INTEGER NOT NULL REFERENCES employee (employee_id) ON DELETE CASCADE,
+    message TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    read_at TIMESTAMPTZ
+);
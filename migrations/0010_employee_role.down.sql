@@ -0,0 +1,4 @@
+ALTER TABLE employee
+DROP COLUMN IF EXISTS role;
+
+DROP TYPE IF EXISTS EMPLOYEE_ROLE;
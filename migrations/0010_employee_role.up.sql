@@ -0,0 +1,6 @@
+CREATE TYPE EMPLOYEE_ROLE AS ENUM ('employee', 'manager', 'admin');
+
+ALTER TABLE employee
+ADD COLUMN role EMPLOYEE_ROLE NOT NULL DEFAULT 'employee';
+
+UPDATE employee SET role = 'admin' WHERE email = 'mace.windu@deepcore.com';
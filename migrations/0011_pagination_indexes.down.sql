@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_worktime_employee_start;
+DROP INDEX IF EXISTS idx_worktime_employee_task;
+DROP INDEX IF EXISTS idx_task_description;
@@ -0,0 +1,3 @@
+CREATE INDEX IF NOT EXISTS idx_worktime_employee_start ON worktime (employee_id, start_time, worktime_id);
+CREATE INDEX IF NOT EXISTS idx_worktime_employee_task ON worktime (employee_id, task_id);
+CREATE INDEX IF NOT EXISTS idx_task_description ON task ((COALESCE(task_description, '')), task_id);
@@ -0,0 +1,3 @@
+-- IANA name like 'Europe/Berlin', NULL falls back to the company timezone
+ALTER TABLE employee
+ADD COLUMN timezone VARCHAR(64);
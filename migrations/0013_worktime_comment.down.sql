@@ -0,0 +1,2 @@
+ALTER TABLE worktime
+DROP COLUMN comment;
@@ -0,0 +1,2 @@
+ALTER TABLE worktime
+ADD COLUMN comment TEXT;
@@ -0,0 +1,2 @@
+ALTER TABLE employee
+ADD COLUMN personnel_number VARCHAR(16) UNIQUE;
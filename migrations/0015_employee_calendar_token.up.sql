@@ -0,0 +1,2 @@
+ALTER TABLE employee
+ADD COLUMN calendar_token VARCHAR(64) UNIQUE;
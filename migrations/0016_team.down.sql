@@ -0,0 +1,4 @@
+ALTER TABLE employee
+DROP COLUMN IF EXISTS team_id;
+
+DROP TABLE IF EXISTS team;
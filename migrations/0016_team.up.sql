@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS team (
+    team_id SERIAL PRIMARY KEY,
+    name VARCHAR(64) NOT NULL,
+    manager_id INTEGER REFERENCES employee (employee_id) ON DELETE SET NULL
+);
+
+ALTER TABLE employee
+ADD COLUMN team_id INTEGER REFERENCES team (team_id) ON DELETE SET NULL;
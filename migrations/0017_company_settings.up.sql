@@ -0,0 +1,11 @@
+-- there is only one company, the check keeps the table at a single row
+CREATE TABLE IF NOT EXISTS company_settings (
+    settings_id INTEGER PRIMARY KEY DEFAULT 1 CHECK (settings_id = 1),
+    name VARCHAR(64) NOT NULL,
+    address TEXT,
+    logo BYTEA,
+    header_color CHAR(7),
+    accent_color CHAR(7)
+);
+
+INSERT INTO company_settings (name) VALUES ('Schmidt''s Handwerksbetrieb');
@@ -0,0 +1,7 @@
+-- checksums of the generated timesheets, the QR code on a report points to its checksum
+CREATE TABLE IF NOT EXISTS issued_report (
+    hash CHAR(64) PRIMARY KEY,
+    employee_id INTEGER NOT NULL REFERENCES employee (employee_id) ON DELETE CASCADE,
+    period VARCHAR(32) NOT NULL,
+    issued_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
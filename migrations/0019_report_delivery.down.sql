@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS report_delivery;
+DROP TYPE IF EXISTS DELIVERY_STATUS;
@@ -0,0 +1,15 @@
+CREATE TYPE DELIVERY_STATUS AS ENUM ('sent', 'failed');
+
+-- every attempt of the scheduler to mail a monthly report, a report is sent again until one
+-- attempt for the employee and period succeeded
+CREATE TABLE IF NOT EXISTS report_delivery (
+    delivery_id SERIAL PRIMARY KEY,
+    employee_id INTEGER NOT NULL REFERENCES employee (employee_id) ON DELETE CASCADE,
+    period VARCHAR(32) NOT NULL,
+    recipient VARCHAR NOT NULL,
+    status DELIVERY_STATUS NOT NULL,
+    error TEXT,
+    attempted_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS report_delivery_period_idx ON report_delivery (period, employee_id);
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS archived_report;
+DROP FUNCTION IF EXISTS reject_archived_report_update;
@@ -0,0 +1,21 @@
+-- every generated timesheet exactly as it was handed out, the files are never changed
+CREATE TABLE IF NOT EXISTS archived_report (
+    report_id SERIAL PRIMARY KEY,
+    employee_id INTEGER NOT NULL REFERENCES employee (employee_id) ON DELETE CASCADE,
+    period VARCHAR(32) NOT NULL,
+    hash CHAR(64) NOT NULL REFERENCES issued_report (hash) ON DELETE CASCADE,
+    content BYTEA NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS archived_report_employee_idx ON archived_report (employee_id, created_at);
+
+CREATE OR REPLACE FUNCTION reject_archived_report_update() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'archived reports can not be changed';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER archived_report_immutable
+    BEFORE UPDATE ON archived_report
+    FOR EACH ROW EXECUTE FUNCTION reject_archived_report_update();
@@ -0,0 +1,4 @@
+ALTER TABLE task DROP COLUMN IF EXISTS project_id;
+DROP TABLE IF EXISTS project;
+DROP TABLE IF EXISTS customer;
+DROP TYPE IF EXISTS ARCHIVE_STATUS;
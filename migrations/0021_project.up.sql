@@ -0,0 +1,24 @@
+CREATE TYPE ARCHIVE_STATUS AS ENUM ('active', 'archived');
+
+CREATE TABLE IF NOT EXISTS customer (
+    customer_id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    status ARCHIVE_STATUS NOT NULL DEFAULT 'active'
+);
+
+-- the customer is billed per project, the budgets are optional
+CREATE TABLE IF NOT EXISTS project (
+    project_id SERIAL PRIMARY KEY,
+    customer_id INTEGER NOT NULL REFERENCES customer (customer_id),
+    name TEXT NOT NULL,
+    status ARCHIVE_STATUS NOT NULL DEFAULT 'active',
+    budget_hours DOUBLE PRECISION CHECK (budget_hours >= 0),
+    budget_cents BIGINT CHECK (budget_cents >= 0)
+);
+
+CREATE INDEX IF NOT EXISTS project_customer_idx ON project (customer_id);
+
+-- tasks which were created before projects existed do not belong to one
+ALTER TABLE task ADD COLUMN IF NOT EXISTS project_id INTEGER REFERENCES project (project_id);
+
+CREATE INDEX IF NOT EXISTS task_project_idx ON task (project_id);
@@ -0,0 +1,2 @@
+-- tasks with booked worktimes can not be deleted, they are archived instead
+ALTER TABLE task ADD COLUMN IF NOT EXISTS status ARCHIVE_STATUS NOT NULL DEFAULT 'active';
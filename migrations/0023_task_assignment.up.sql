@@ -0,0 +1,12 @@
+-- a task is assigned either to an employee or to a whole team, tasks without any assignment can
+-- be booked by everyone
+CREATE TABLE IF NOT EXISTS task_assignment (
+    assignment_id SERIAL PRIMARY KEY,
+    task_id INTEGER NOT NULL REFERENCES task (task_id) ON DELETE CASCADE,
+    employee_id INTEGER REFERENCES employee (employee_id) ON DELETE CASCADE,
+    team_id INTEGER REFERENCES team (team_id) ON DELETE CASCADE,
+    CHECK ((employee_id IS NULL) <> (team_id IS NULL))
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS task_assignment_employee_idx ON task_assignment (task_id, employee_id) WHERE employee_id IS NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS task_assignment_team_idx ON task_assignment (task_id, team_id) WHERE team_id IS NOT NULL;
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS task_budget_alert;
+ALTER TABLE task DROP COLUMN IF EXISTS budget_cents;
+ALTER TABLE task DROP COLUMN IF EXISTS estimated_hours;
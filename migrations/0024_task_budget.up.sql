@@ -0,0 +1,10 @@
+ALTER TABLE task ADD COLUMN IF NOT EXISTS estimated_hours DOUBLE PRECISION CHECK (estimated_hours >= 0);
+ALTER TABLE task ADD COLUMN IF NOT EXISTS budget_cents BIGINT CHECK (budget_cents >= 0);
+
+-- every threshold like 80 or 100 percent of the estimate is only reported once per task
+CREATE TABLE IF NOT EXISTS task_budget_alert (
+    task_id INTEGER NOT NULL REFERENCES task (task_id) ON DELETE CASCADE,
+    threshold INTEGER NOT NULL,
+    crossed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (task_id, threshold)
+);
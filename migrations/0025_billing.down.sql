@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS billing_rate;
+ALTER TABLE worktime DROP COLUMN IF EXISTS billable;
@@ -0,0 +1,14 @@
+-- breaks are never billed, the flag only matters for work and ride times
+ALTER TABLE worktime ADD COLUMN IF NOT EXISTS billable BOOLEAN NOT NULL DEFAULT TRUE;
+
+-- an hourly rate applies to every worktime matching all of its set columns, the most specific one
+-- wins: a task rate before an employee rate before a work type rate
+CREATE TABLE IF NOT EXISTS billing_rate (
+    rate_id SERIAL PRIMARY KEY,
+    employee_id INTEGER REFERENCES employee (employee_id) ON DELETE CASCADE,
+    task_id INTEGER REFERENCES task (task_id) ON DELETE CASCADE,
+    work_type WORKTIME_TYPE,
+    rate_cents BIGINT NOT NULL CHECK (rate_cents >= 0)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS billing_rate_scope_idx ON billing_rate (employee_id, task_id, work_type) NULLS NOT DISTINCT;
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS absence;
+DROP TYPE IF EXISTS ABSENCE_STATUS;
+DROP TYPE IF EXISTS ABSENCE_TYPE;
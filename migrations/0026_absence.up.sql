@@ -0,0 +1,17 @@
+CREATE TYPE ABSENCE_TYPE AS ENUM ('vacation', 'sickness', 'other');
+CREATE TYPE ABSENCE_STATUS AS ENUM ('requested', 'approved', 'rejected');
+
+-- whole days off from start_date to end_date (both inclusive), only approved ones count in the
+-- reports and exports
+CREATE TABLE IF NOT EXISTS absence (
+    absence_id SERIAL PRIMARY KEY,
+    employee_id INTEGER NOT NULL REFERENCES employee (employee_id) ON DELETE CASCADE,
+    absence_type ABSENCE_TYPE NOT NULL,
+    start_date DATE NOT NULL,
+    end_date DATE NOT NULL,
+    status ABSENCE_STATUS NOT NULL DEFAULT 'requested',
+    comment TEXT,
+    CHECK (end_date >= start_date)
+);
+
+CREATE INDEX IF NOT EXISTS absence_employee_idx ON absence (employee_id, start_date);
@@ -0,0 +1,12 @@
+ALTER TABLE archived_report DROP CONSTRAINT IF EXISTS archived_report_checksum_key;
+
+ALTER TABLE archived_report
+    DROP CONSTRAINT archived_report_employee_id_fkey,
+    ADD CONSTRAINT archived_report_employee_id_fkey
+        FOREIGN KEY (employee_id) REFERENCES employee (employee_id) ON DELETE CASCADE,
+    DROP CONSTRAINT archived_report_hash_fkey,
+    ADD CONSTRAINT archived_report_hash_fkey
+        FOREIGN KEY (hash) REFERENCES issued_report (hash) ON DELETE CASCADE;
+
+DROP TRIGGER IF EXISTS archived_report_undeletable ON archived_report;
+DROP FUNCTION IF EXISTS reject_archived_report_delete;
@@ -0,0 +1,29 @@
+-- a checksum is archived once per employee and period, the first file is kept
+DELETE FROM archived_report duplicate USING archived_report first
+WHERE duplicate.employee_id = first.employee_id
+    AND duplicate.period = first.period
+    AND duplicate.hash = first.hash
+    AND duplicate.report_id > first.report_id;
+
+ALTER TABLE archived_report
+    ADD CONSTRAINT archived_report_checksum_key UNIQUE (employee_id, period, hash);
+
+-- archived reports are never deleted, neither directly nor together with their employee or
+-- checksum
+CREATE OR REPLACE FUNCTION reject_archived_report_delete() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'archived reports can not be deleted';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER archived_report_undeletable
+    BEFORE DELETE ON archived_report
+    FOR EACH ROW EXECUTE FUNCTION reject_archived_report_delete();
+
+ALTER TABLE archived_report
+    DROP CONSTRAINT archived_report_employee_id_fkey,
+    ADD CONSTRAINT archived_report_employee_id_fkey
+        FOREIGN KEY (employee_id) REFERENCES employee (employee_id) ON DELETE RESTRICT,
+    DROP CONSTRAINT archived_report_hash_fkey,
+    ADD CONSTRAINT archived_report_hash_fkey
+        FOREIGN KEY (hash) REFERENCES issued_report (hash) ON DELETE RESTRICT;
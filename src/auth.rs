@@ -51,6 +51,18 @@ struct Claims {
     exp: i64,
 }
 
+// audience of download tokens, access tokens have none so neither is accepted as the other
+const DOWNLOAD_AUDIENCE: &str = "download";
+
+#[derive(Serialize, Deserialize, Debug)]
+struct DownloadClaims {
+    sub: String,
+    exp: i64,
+    aud: String,
+    // path of the only resource the token grants access to
+    res: String,
+}
+
 #[derive(Debug)]
 pub enum LoginError {
     InvalidCredentials,
@@ -188,13 +200,9 @@ pub async fn refresh(
     Ok(Json(logres))
 }
 
-pub async fn auth(mut request: Request, next: Next) -> Result<Response, LoginError> {
-    let TypedHeader(Authorization(bearer)) = request
-        .extract_parts::<TypedHeader<Authorization<Bearer>>>()
-        .await
-        .map_err(|_| LoginError::InvalidToken)?;
-    let claims = jsonwebtoken::decode::<Claims>(
-        bearer.token(),
+fn decode_access_token(token: &str) -> Result<Claims, LoginError> {
+    jsonwebtoken::decode::<Claims>(
+        token,
         &DecodingKey::from_secret(
             dotenvy::var("JWT_SECRET")
                 .expect("No secret was provided.")
@@ -202,7 +210,131 @@ pub async fn auth(mut request: Request, next: Next) -> Result<Response, LoginErr
         ),
         &Validation::default(),
     )
-    .map_err(|_| LoginError::InvalidCredentials)?;
-    request.extensions_mut().insert(claims.claims.sub);
+    .map(|token_data| token_data.claims)
+    .map_err(|_| LoginError::InvalidCredentials)
+}
+
+pub(crate) fn employee_id_from_access_token(token: &str) -> Result<i32, LoginError> {
+    decode_access_token(token)?
+        .sub
+        .parse::<i32>()
+        .map_err(|_| LoginError::InvalidToken)
+}
+
+/// Creates a short-lived token that allows downloading the resource at `path` without an
+/// authorization header, e.g. by opening a link in the browser.
+pub(crate) fn create_download_token(
+    employee_id: &i32,
+    path: &str,
+    valid_for: Duration,
+) -> Result<String, LoginError> {
+    let claims = DownloadClaims {
+        sub: employee_id.to_string(),
+        exp: (Utc::now() + valid_for).timestamp(),
+        aud: DOWNLOAD_AUDIENCE.to_string(),
+        res: path.to_string(),
+    };
+
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(
+            dotenvy::var("JWT_SECRET")
+                .expect("No secret was provided.")
+                .as_ref(),
+        ),
+    )
+    .map_err(|_| LoginError::TokenCreation)
+}
+
+// returns the employee the download token was issued for if it is valid for the path
+pub(crate) fn verify_download_token(token: &str, path: &str) -> Result<i32, LoginError> {
+    let mut validation = Validation::default();
+    validation.set_audience(&[DOWNLOAD_AUDIENCE]);
+
+    let claims = jsonwebtoken::decode::<DownloadClaims>(
+        token,
+        &DecodingKey::from_secret(
+            dotenvy::var("JWT_SECRET")
+                .expect("No secret was provided.")
+                .as_ref(),
+        ),
+        &validation,
+    )
+    .map_err(|_| LoginError::InvalidToken)?
+    .claims;
+
+    if claims.res != path {
+        return Err(LoginError::InvalidToken);
+    }
+
+    claims
+        .sub
+        .parse::<i32>()
+        .map_err(|_| LoginError::InvalidToken)
+}
+
+pub async fn auth(mut request: Request, next: Next) -> Result<Response, LoginError> {
+    let TypedHeader(Authorization(bearer)) = request
+        .extract_parts::<TypedHeader<Authorization<Bearer>>>()
+        .await
+        .map_err(|_| LoginError::InvalidToken)?;
+    let claims = decode_access_token(bearer.token())?;
+    request.extensions_mut().insert(claims.sub);
     Ok(next.run(request).await)
 }
+
+// websockets can not carry an authorization header, so subscriptions send the access token
+// in the payload of the connection init message: { "authorization": "Bearer <token>" }
+pub async fn ws_connection_init(
+    payload: serde_json::Value,
+) -> async_graphql::Result<async_graphql::Data> {
+    let token = payload
+        .get("authorization")
+        .or_else(|| payload.get("Authorization"))
+        .and_then(serde_json::Value::as_str)
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or_else(|| async_graphql::Error::new("Missing access token"))?;
+
+    let employee_id = decode_access_token(token)
+        .ok()
+        .and_then(|claims| claims.sub.parse::<i32>().ok())
+        .ok_or_else(|| async_graphql::Error::new("Invalid Token"))?;
+
+    let mut data = async_graphql::Data::default();
+    data.insert(employee_id);
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_download_token() {
+        // given is a download token for a report
+        let token =
+            create_download_token(&1, "/reports/2024-01.pdf", Duration::minutes(5)).unwrap();
+
+        // then it is only valid for that report and not as access token
+        assert_eq!(
+            verify_download_token(&token, "/reports/2024-01.pdf").unwrap(),
+            1
+        );
+        assert!(verify_download_token(&token, "/reports/2024-02.pdf").is_err());
+        assert!(employee_id_from_access_token(&token).is_err());
+    }
+
+    #[test]
+    fn test_access_token_is_no_download_token() {
+        let login_response = create_login_response(1).unwrap();
+
+        assert_eq!(
+            employee_id_from_access_token(&login_response.access_token).unwrap(),
+            1
+        );
+        assert!(
+            verify_download_token(&login_response.access_token, "/reports/2024-01.pdf").is_err()
+        );
+    }
+}
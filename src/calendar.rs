@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderValue},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+
+use crate::download::DownloadError;
+use crate::models::{Absence, AbsenceStatus, Worktime};
+use crate::service::{absence, employee, task, worktime};
+
+// iCalendar lines should not be longer than 75 octets
+const MAX_LINE_LENGTH: usize = 75;
+
+// how many days of worktimes the feed contains, approved absences are also listed as far ahead
+fn feed_days() -> i64 {
+    dotenvy::var("CALENDAR_FEED_DAYS")
+        .ok()
+        .and_then(|days| days.parse::<i64>().ok())
+        .unwrap_or(90)
+}
+
+// escapes text values like described in RFC 5545 section 3.3.11
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+// folds a content line after 75 octets without splitting a character
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut line_length = 0;
+
+    for character in line.chars() {
+        if line_length + character.len_utf8() > MAX_LINE_LENGTH {
+            folded.push_str("\r\n ");
+            line_length = 1;
+        }
+        folded.push(character);
+        line_length += character.len_utf8();
+    }
+    folded.push_str("\r\n");
+
+    folded
+}
+
+fn format_timestamp(timestamp: DateTime<Utc>) -> String {
+    timestamp.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Renders the stopped worktimes as VEVENTs with the task description as summary and the work
+/// type as category, the absences as all-day VEVENTs with the absence type as category.
+pub fn render_calendar(
+    worktimes: &[Worktime],
+    absences: &[Absence],
+    task_descriptions: &HashMap<i32, Option<String>>,
+    now: DateTime<Utc>,
+) -> String {
+    let mut lines = vec![
+        String::from("BEGIN:VCALENDAR"),
+        String::from("VERSION:2.0"),
+        String::from("PRODID:-//Squad Mandalore//TTBackend//DE"),
+        String::from("CALSCALE:GREGORIAN"),
+        String::from("METHOD:PUBLISH"),
+        String::from("X-WR-CALNAME:Arbeitszeiten"),
+    ];
+
+    // running timers have no end yet and show up once they are stopped
+    for worktime in worktimes.iter().filter(|w| w.end_time.is_some()) {
+        let summary = task_descriptions
+            .get(&worktime.task_id)
+            .cloned()
+            .flatten()
+            .unwrap_or_else(|| String::from("No description available"));
+
+        lines.push(String::from("BEGIN:VEVENT"));
+        lines.push(format!("UID:worktime-{}@ttbackend", worktime.worktime_id));
+        lines.push(format!("DTSTAMP:{}", format_timestamp(now)));
+        lines.push(format!("DTSTART:{}", format_timestamp(worktime.start_time)));
+        if let Some(end_time) = worktime.end_time {
+            lines.push(format!("DTEND:{}", format_timestamp(end_time)));
+        }
+        lines.push(format!("SUMMARY:{}", escape_text(&summary)));
+        lines.push(format!(
+            "CATEGORIES:{}",
+            format!("{:?}", worktime.work_type).to_uppercase()
+        ));
+        if let Some(comment) = &worktime.comment {
+            lines.push(format!("DESCRIPTION:{}", escape_text(comment)));
+        }
+        lines.push(String::from("END:VEVENT"));
+    }
+    for absence in absences {
+        let absence_type = format!("{:?}", absence.absence_type);
+
+        lines.push(String::from("BEGIN:VEVENT"));
+        lines.push(format!("UID:absence-{}@ttbackend", absence.absence_id));
+        lines.push(format!("DTSTAMP:{}", format_timestamp(now)));
+        lines.push(format!(
+            "DTSTART;VALUE=DATE:{}",
+            absence.start_date.format("%Y%m%d")
+        ));
+        // the end of all-day events is exclusive
+        lines.push(format!(
+            "DTEND;VALUE=DATE:{}",
+            (absence.end_date + Duration::days(1)).format("%Y%m%d")
+        ));
+        lines.push(format!("SUMMARY:{}", absence_type));
+        lines.push(format!("CATEGORIES:{}", absence_type.to_uppercase()));
+        lines.push(String::from("TRANSP:TRANSPARENT"));
+        if let Some(comment) = &absence.comment {
+            lines.push(format!("DESCRIPTION:{}", escape_text(comment)));
+        }
+        lines.push(String::from("END:VEVENT"));
+    }
+    lines.push(String::from("END:VCALENDAR"));
+
+    lines.iter().map(|line| fold_line(line)).collect()
+}
+
+pub async fn get_calendar(employee_id: &i32, pool: &PgPool) -> anyhow::Result<String> {
+    let now = Utc::now();
+    let feed_days = feed_days();
+    let worktimes = worktime::get_timers_in_boundary(
+        employee_id,
+        (now - Duration::days(feed_days)).fixed_offset(),
+        (now + Duration::days(1)).fixed_offset(),
+        pool,
+    )
+    .await?;
+
+    let mut task_ids: Vec<i32> = worktimes.iter().map(|w| w.task_id).collect();
+    task_ids.sort_unstable();
+    task_ids.dedup();
+    let task_descriptions: HashMap<i32, Option<String>> = task::get_tasks_by_ids(&task_ids, pool)
+        .await?
+        .into_iter()
+        .map(|task| (task.task_id, task.task_description))
+        .collect();
+
+    let absences = absence::get_absences(
+        &[*employee_id],
+        (now - Duration::days(feed_days)).date_naive(),
+        (now + Duration::days(feed_days)).date_naive(),
+        Some(AbsenceStatus::Approved),
+        pool,
+    )
+    .await?;
+
+    Ok(render_calendar(
+        &worktimes,
+        &absences,
+        &task_descriptions,
+        now,
+    ))
+}
+
+// GET /calendar/{token}.ics, calendar apps can not send an authorization header so the
+// secret token of the employee is part of the path
+pub async fn calendar_feed(
+    State(pool): State<PgPool>,
+    Path(file): Path<String>,
+) -> Result<Response, DownloadError> {
+    let token = file.strip_suffix(".ics").ok_or(DownloadError::NotFound)?;
+
+    let employee_id = employee::get_employee_id_by_calendar_token(token, &pool)
+        .await
+        .map_err(|error| {
+            tracing::error!("Failed to look up the calendar token: {:?}", error);
+            DownloadError::Generation
+        })?
+        .ok_or(DownloadError::NotFound)?;
+
+    let calendar = get_calendar(&employee_id, &pool).await.map_err(|error| {
+        tracing::error!("Failed to create the calendar: {:?}", error);
+        DownloadError::Generation
+    })?;
+
+    let mut response = calendar.into_response();
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("text/calendar; charset=utf-8"),
+    );
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::AbsenceType;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_escape_text() {
+        assert_eq!(
+            escape_text("Kunde; Meier, Hans\nTür\\Tor"),
+            "Kunde\\; Meier\\, Hans\\nTür\\\\Tor"
+        );
+    }
+
+    #[test]
+    fn test_fold_line() {
+        let line = format!("SUMMARY:{}", "ä".repeat(40));
+        let folded = fold_line(&line);
+
+        assert!(folded
+            .split("\r\n")
+            .all(|part| part.len() <= MAX_LINE_LENGTH));
+        assert_eq!(folded.replace("\r\n ", ""), format!("{}\r\n", line));
+    }
+
+    #[sqlx::test(fixtures(
+        "../fixtures/truncate.sql",
+        "../fixtures/task.sql",
+        "../fixtures/address.sql",
+        "../fixtures/employee.sql",
+        "../fixtures/worktime.sql"
+    ))]
+    async fn test_render_calendar(pool: PgPool) -> anyhow::Result<()> {
+        // given are the worktimes of a day
+        let worktimes = worktime::get_timers_in_boundary(
+            &1,
+            DateTime::parse_from_rfc3339("2024-01-03T00:00:00+00:00")?,
+            DateTime::parse_from_rfc3339("2024-01-04T00:00:00+00:00")?,
+            &pool,
+        )
+        .await?;
+        let task_descriptions = HashMap::from([
+            (2, Some(String::from("second task"))),
+            (3, Some(String::from("Repair Millennium Falcon"))),
+        ]);
+        let now = DateTime::parse_from_rfc3339("2024-02-01T12:00:00+00:00")?.to_utc();
+
+        // and an approved vacation
+        let absences = [Absence {
+            absence_id: 7,
+            employee_id: 1,
+            absence_type: AbsenceType::Vacation,
+            start_date: NaiveDate::from_ymd_opt(2024, 1, 8).unwrap(),
+            end_date: NaiveDate::from_ymd_opt(2024, 1, 9).unwrap(),
+            status: AbsenceStatus::Approved,
+            comment: Some(String::from("Ski, Alps")),
+        }];
+
+        // when the calendar is rendered
+        let calendar = render_calendar(&worktimes, &absences, &task_descriptions, now);
+
+        // then every worktime and absence is an event
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(calendar.matches("BEGIN:VEVENT").count(), 3);
+        assert!(calendar.contains(
+            "UID:absence-7@ttbackend\r\n\
+            DTSTAMP:20240201T120000Z\r\n\
+            DTSTART;VALUE=DATE:20240108\r\n\
+            DTEND;VALUE=DATE:20240110\r\n\
+            SUMMARY:Vacation\r\n\
+            CATEGORIES:VACATION\r\n\
+            TRANSP:TRANSPARENT\r\n\
+            DESCRIPTION:Ski\\, Alps\r\n"
+        ));
+        assert!(calendar.contains(
+            "UID:worktime-4@ttbackend\r\n\
+            DTSTAMP:20240201T120000Z\r\n\
+            DTSTART:20240103T123000Z\r\n\
+            DTEND:20240103T143000Z\r\n\
+            SUMMARY:second task\r\n\
+            CATEGORIES:RIDE\r\n"
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,754 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::{
+    auth::{self, LoginError},
+    export::{
+        datev::{export_datev, DatevConfig},
+        export_worktimes, may_export, write_month_report_csv, write_task_report_csv, ExportFormat,
+        ExportStyle, ReportFormat,
+    },
+    models::{EmployeeRole, ReportVerification},
+    pdf::{
+        billing::render_billing_pdf, branding::Branding, render_pdf, task_report::render_task_pdf,
+        team::render_team_pdf, validate_month, verification, HeaderColor, Locale, ReportPeriod,
+    },
+    service::{archived_report, billing, employee, report, team},
+};
+
+// download links are meant to be opened right away
+const DOWNLOAD_TOKEN_LIFETIME_MINUTES: i64 = 5;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportQuery {
+    token: Option<String>,
+    #[serde(default)]
+    header_color: HeaderColor,
+    #[serde(default)]
+    locale: Locale,
+    #[serde(default)]
+    style: ExportStyle,
+}
+
+#[derive(Deserialize)]
+pub struct TokenQuery {
+    token: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportQuery {
+    token: Option<String>,
+    from: NaiveDate,
+    to: NaiveDate,
+    #[serde(default)]
+    style: ExportStyle,
+    // comma separated, the authenticated employee if missing
+    employee_ids: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct PeriodQuery {
+    token: Option<String>,
+    from: NaiveDate,
+    to: NaiveDate,
+    #[serde(default)]
+    locale: Locale,
+}
+
+#[derive(Deserialize)]
+pub struct BillingQuery {
+    token: Option<String>,
+    from: NaiveDate,
+    to: NaiveDate,
+    #[serde(default)]
+    locale: Locale,
+}
+
+#[derive(Deserialize)]
+pub struct TaskReportQuery {
+    token: Option<String>,
+    from: NaiveDate,
+    to: NaiveDate,
+    #[serde(default)]
+    style: ExportStyle,
+    #[serde(default)]
+    locale: Locale,
+}
+
+#[derive(Debug)]
+pub enum DownloadError {
+    Unauthorized(LoginError),
+    Forbidden,
+    NotFound,
+    BadRequest,
+    Generation,
+}
+
+impl From<LoginError> for DownloadError {
+    fn from(error: LoginError) -> Self {
+        DownloadError::Unauthorized(error)
+    }
+}
+
+impl IntoResponse for DownloadError {
+    fn into_response(self) -> Response {
+        match self {
+            DownloadError::Unauthorized(error) => error.into_response(),
+            DownloadError::Forbidden => {
+                (StatusCode::FORBIDDEN, "Access to the file is not allowed").into_response()
+            }
+            DownloadError::NotFound => (StatusCode::NOT_FOUND, "File not found").into_response(),
+            DownloadError::BadRequest => {
+                (StatusCode::BAD_REQUEST, "Invalid query parameters").into_response()
+            }
+            DownloadError::Generation => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "An unexpected error occurred while creating the file",
+            )
+                .into_response(),
+        }
+    }
+}
+
+pub fn report_path(period: &ReportPeriod, format: ReportFormat) -> String {
+    format!("/reports/{}.{}", period, format.extension())
+}
+
+pub fn export_path(format: ExportFormat) -> String {
+    format!("/exports/worktimes.{}", format.extension())
+}
+
+// the report of a team or 'company' for every employee
+pub fn team_report_path(team_id: Option<i32>) -> String {
+    match team_id {
+        Some(team_id) => format!("/reports/team/{}.pdf", team_id),
+        None => String::from("/reports/team/company.pdf"),
+    }
+}
+
+// the billing summary of a customer or 'all' for every task
+pub fn billing_report_path(customer_id: Option<i32>) -> String {
+    match customer_id {
+        Some(customer_id) => format!("/reports/billing/{}.pdf", customer_id),
+        None => String::from("/reports/billing/all.pdf"),
+    }
+}
+
+pub fn task_report_path(task_id: i32, format: ReportFormat) -> String {
+    format!("/reports/task/{}.{}", task_id, format.extension())
+}
+
+pub fn archived_report_path(report_id: i32) -> String {
+    format!("/reports/archive/{}.pdf", report_id)
+}
+
+// the QR code on a report points here, the checksum is the only thing needed for the check
+pub fn verification_path(hash: &str) -> String {
+    format!("/reports/verify/{}", hash)
+}
+
+pub fn datev_path(month: &str) -> String {
+    format!("/exports/datev/{}.csv", month)
+}
+
+// absolute url of the path if PUBLIC_URL is set, otherwise the path itself
+pub fn public_url(path: &str) -> String {
+    let public_url = dotenvy::var("PUBLIC_URL").unwrap_or_default();
+    format!("{}{}", public_url.trim_end_matches('/'), path)
+}
+
+// the name of an enum variant like it is expected in the query
+fn query_value(value: impl Serialize) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|value| value.as_str().map(String::from))
+        .unwrap_or_default()
+}
+
+fn download_url(path: &str, employee_id: &i32, query: &str) -> Result<String, LoginError> {
+    let token = auth::create_download_token(
+        employee_id,
+        path,
+        Duration::minutes(DOWNLOAD_TOKEN_LIFETIME_MINUTES),
+    )?;
+    let mut url = format!("{}?token={}", public_url(path), token);
+    if !query.is_empty() {
+        url.push('&');
+        url.push_str(query);
+    }
+
+    Ok(url)
+}
+
+// a download token only grants access to the path it was created for
+fn authenticate(
+    token: Option<String>,
+    bearer: Option<TypedHeader<Authorization<Bearer>>>,
+    path: &str,
+) -> Result<i32, DownloadError> {
+    let employee_id = match (token, bearer) {
+        (Some(token), _) => auth::verify_download_token(&token, path)?,
+        (None, Some(TypedHeader(Authorization(bearer)))) => {
+            auth::employee_id_from_access_token(bearer.token())?
+        }
+        (None, None) => return Err(DownloadError::Unauthorized(LoginError::InvalidToken)),
+    };
+
+    Ok(employee_id)
+}
+
+// The pdf and xlsx writers only hand out the finished file, so every download is built in memory
+// and sent with its length. Long ranges and company-wide team reports take as much memory as the
+// file is large, which is why report periods are limited to 366 days.
+fn attachment(
+    body: Vec<u8>,
+    content_type: &'static str,
+    filename: &str,
+) -> Result<Response, DownloadError> {
+    let content_disposition = format!("attachment; filename=\"{}\"", filename);
+    let mut response = body.into_response();
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    response.headers_mut().insert(
+        header::CONTENT_DISPOSITION,
+        HeaderValue::from_str(&content_disposition).map_err(|_| DownloadError::NotFound)?,
+    );
+
+    Ok(response)
+}
+
+/// Creates a link to the pdf of the given period which can be opened without an authorization
+/// header for a few minutes.
+pub fn create_report_url(
+    employee_id: &i32,
+    period: &ReportPeriod,
+    header_color: HeaderColor,
+    locale: Locale,
+) -> Result<String, LoginError> {
+    download_url(
+        &report_path(period, ReportFormat::Pdf),
+        employee_id,
+        &format!(
+            "headerColor={}&locale={}",
+            query_value(header_color),
+            query_value(locale)
+        ),
+    )
+}
+
+/// Creates a link to the csv of the given period which can be opened without an authorization
+/// header for a few minutes.
+pub fn create_report_csv_url(
+    employee_id: &i32,
+    period: &ReportPeriod,
+    style: ExportStyle,
+) -> Result<String, LoginError> {
+    download_url(
+        &report_path(period, ReportFormat::Csv),
+        employee_id,
+        &format!("style={}", query_value(style)),
+    )
+}
+
+/// Creates a link to an archived report which can be opened without an authorization header for
+/// a few minutes.
+pub fn create_archived_report_url(employee_id: &i32, report_id: i32) -> Result<String, LoginError> {
+    download_url(&archived_report_path(report_id), employee_id, "")
+}
+
+/// Creates a link to the worktime export which can be opened without an authorization header
+/// for a few minutes.
+pub fn create_export_url(
+    employee_id: &i32,
+    employee_ids: Option<&[i32]>,
+    from: NaiveDate,
+    to: NaiveDate,
+    format: ExportFormat,
+    style: ExportStyle,
+) -> Result<String, LoginError> {
+    let mut query = format!("from={}&to={}&style={}", from, to, query_value(style));
+    if let Some(employee_ids) = employee_ids {
+        let employee_ids: Vec<String> = employee_ids.iter().map(i32::to_string).collect();
+        query.push_str(&format!("&employeeIds={}", employee_ids.join(",")));
+    }
+
+    download_url(&export_path(format), employee_id, &query)
+}
+
+/// Creates a link to the pdf of the team report which can be opened without an authorization
+/// header for a few minutes.
+pub fn create_team_report_url(
+    employee_id: &i32,
+    team_id: Option<i32>,
+    from: NaiveDate,
+    to: NaiveDate,
+    locale: Locale,
+) -> Result<String, LoginError> {
+    download_url(
+        &team_report_path(team_id),
+        employee_id,
+        &format!("from={}&to={}&locale={}", from, to, query_value(locale)),
+    )
+}
+
+/// Creates a link to the pdf of the billing summary which can be opened without an authorization
+/// header for a few minutes.
+pub fn create_billing_report_url(
+    employee_id: &i32,
+    customer_id: Option<i32>,
+    from: NaiveDate,
+    to: NaiveDate,
+    locale: Locale,
+) -> Result<String, LoginError> {
+    download_url(
+        &billing_report_path(customer_id),
+        employee_id,
+        &format!("from={}&to={}&locale={}", from, to, query_value(locale)),
+    )
+}
+
+/// Creates a link to the task report which can be opened without an authorization header for a
+/// few minutes.
+pub fn create_task_report_url(
+    employee_id: &i32,
+    task_id: i32,
+    from: NaiveDate,
+    to: NaiveDate,
+    format: ReportFormat,
+    style: ExportStyle,
+    locale: Locale,
+) -> Result<String, LoginError> {
+    download_url(
+        &task_report_path(task_id, format),
+        employee_id,
+        &format!(
+            "from={}&to={}&style={}&locale={}",
+            from,
+            to,
+            query_value(style),
+            query_value(locale)
+        ),
+    )
+}
+
+/// Creates a link to the DATEV payroll import file of the month which can be opened without an
+/// authorization header for a few minutes.
+pub fn create_datev_url(employee_id: &i32, month: &str) -> Result<String, LoginError> {
+    download_url(&datev_path(month), employee_id, "")
+}
+
+// GET /reports/{period}.{pdf,csv}, authenticated either by bearer token or by a download token
+pub async fn period_report(
+    State(pool): State<PgPool>,
+    Path(file): Path<String>,
+    Query(query): Query<ReportQuery>,
+    bearer: Option<TypedHeader<Authorization<Bearer>>>,
+) -> Result<Response, DownloadError> {
+    let (period, format): (ReportPeriod, ReportFormat) = file
+        .rsplit_once('.')
+        .and_then(|(period, extension)| {
+            Some((
+                period.parse().ok()?,
+                ReportFormat::from_extension(extension)?,
+            ))
+        })
+        .ok_or(DownloadError::NotFound)?;
+
+    let employee_id = authenticate(query.token, bearer, &report_path(&period, format))?;
+
+    if format == ReportFormat::Csv {
+        let (from, to) = period.bounds().map_err(|_| DownloadError::NotFound)?;
+        let csv = report::get_month_report(employee_id, from, to, &pool)
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|report| write_month_report_csv(&report, query.style))
+            .map_err(|error| {
+                tracing::error!("Failed to create report {}: {:?}", period, error);
+                DownloadError::Generation
+            })?;
+
+        return attachment(
+            csv,
+            format.content_type(),
+            &format!("report-{}.csv", period),
+        );
+    }
+
+    let pdf = render_pdf(
+        period,
+        query.header_color,
+        query.locale,
+        &pool,
+        &employee_id,
+    )
+    .await
+    .map_err(|error| {
+        tracing::error!("Failed to create report {}: {:?}", period, error);
+        DownloadError::Generation
+    })?;
+
+    attachment(pdf, "application/pdf", &format!("report-{}.pdf", period))
+}
+
+// GET /reports/archive/{report_id}.pdf, the stored file of a report generated earlier, managers
+// may download the reports of other employees
+pub async fn archived_report_pdf(
+    State(pool): State<PgPool>,
+    Path(file): Path<String>,
+    Query(query): Query<TokenQuery>,
+    bearer: Option<TypedHeader<Authorization<Bearer>>>,
+) -> Result<Response, DownloadError> {
+    let report_id: i32 = file
+        .strip_suffix(".pdf")
+        .and_then(|report_id| report_id.parse().ok())
+        .ok_or(DownloadError::NotFound)?;
+
+    let employee_id = authenticate(query.token, bearer, &archived_report_path(report_id))?;
+
+    let internal_error = |error: sqlx::Error| {
+        tracing::error!(
+            "Failed to load the archived report {}: {:?}",
+            report_id,
+            error
+        );
+        DownloadError::Generation
+    };
+    let report = archived_report::get_archived_report_file(report_id, &pool)
+        .await
+        .map_err(internal_error)?
+        .ok_or(DownloadError::NotFound)?;
+    if !may_export(&employee_id, &[report.employee_id], &pool)
+        .await
+        .map_err(internal_error)?
+    {
+        return Err(DownloadError::Forbidden);
+    }
+
+    attachment(
+        report.content,
+        "application/pdf",
+        &format!("report-{}-{}.pdf", report.period, report_id),
+    )
+}
+
+// GET /exports/worktimes.{csv,xlsx}, authenticated either by bearer token or by a download token
+pub async fn worktime_export(
+    State(pool): State<PgPool>,
+    Path(file): Path<String>,
+    Query(query): Query<ExportQuery>,
+    bearer: Option<TypedHeader<Authorization<Bearer>>>,
+) -> Result<Response, DownloadError> {
+    let format = file
+        .strip_prefix("worktimes.")
+        .and_then(ExportFormat::from_extension)
+        .ok_or(DownloadError::NotFound)?;
+
+    let employee_id = authenticate(query.token, bearer, &export_path(format))?;
+
+    let employee_ids = match query.employee_ids {
+        None => vec![employee_id],
+        Some(employee_ids) => employee_ids
+            .split(',')
+            .map(|id| id.trim().parse::<i32>())
+            .collect::<Result<Vec<i32>, _>>()
+            .map_err(|_| DownloadError::BadRequest)?,
+    };
+    if query.to < query.from {
+        return Err(DownloadError::BadRequest);
+    }
+
+    let internal_error = |error: sqlx::Error| {
+        tracing::error!("Failed to check the export permission: {:?}", error);
+        DownloadError::Generation
+    };
+    if !may_export(&employee_id, &employee_ids, &pool)
+        .await
+        .map_err(internal_error)?
+    {
+        return Err(DownloadError::Forbidden);
+    }
+    let timezone = employee::get_timezone(&employee_id, &pool)
+        .await
+        .map_err(internal_error)?;
+
+    let export = export_worktimes(
+        &employee_ids,
+        query.from,
+        query.to,
+        format,
+        query.style,
+        &timezone,
+        &pool,
+    )
+    .await
+    .map_err(|error| {
+        tracing::error!("Failed to export worktimes: {:?}", error);
+        DownloadError::Generation
+    })?;
+
+    attachment(
+        export,
+        format.content_type(),
+        &format!(
+            "worktimes-{}-{}.{}",
+            query.from,
+            query.to,
+            format.extension()
+        ),
+    )
+}
+
+// GET /exports/datev/{month}.csv, only for managers since it contains every employee
+pub async fn datev_export(
+    State(pool): State<PgPool>,
+    Path(file): Path<String>,
+    Query(query): Query<TokenQuery>,
+    bearer: Option<TypedHeader<Authorization<Bearer>>>,
+) -> Result<Response, DownloadError> {
+    let month = file.strip_suffix(".csv").ok_or(DownloadError::NotFound)?;
+    validate_month(month).map_err(|_| DownloadError::NotFound)?;
+
+    let employee_id = authenticate(query.token, bearer, &datev_path(month))?;
+
+    let role = employee::get_role(&employee_id, &pool)
+        .await
+        .map_err(|error| {
+            tracing::error!("Failed to check the export permission: {:?}", error);
+            DownloadError::Generation
+        })?;
+    if role < EmployeeRole::Manager {
+        return Err(DownloadError::Forbidden);
+    }
+
+    let export = export_datev(month, &DatevConfig::from_env(), &pool)
+        .await
+        .map_err(|error| {
+            tracing::error!("Failed to create the DATEV export {}: {:?}", month, error);
+            DownloadError::Generation
+        })?;
+
+    attachment(
+        export,
+        "text/csv; charset=windows-1252",
+        &format!("datev-{}.csv", month),
+    )
+}
+
+// GET /reports/team/{teamId or 'company'}.pdf, only for the manager of the team and admins
+pub async fn team_report_pdf(
+    State(pool): State<PgPool>,
+    Path(file): Path<String>,
+    Query(query): Query<PeriodQuery>,
+    bearer: Option<TypedHeader<Authorization<Bearer>>>,
+) -> Result<Response, DownloadError> {
+    let team_id = match file.strip_suffix(".pdf").ok_or(DownloadError::NotFound)? {
+        "company" => None,
+        team_id => Some(
+            team_id
+                .parse::<i32>()
+                .map_err(|_| DownloadError::NotFound)?,
+        ),
+    };
+    if query.to < query.from {
+        return Err(DownloadError::BadRequest);
+    }
+
+    let employee_id = authenticate(query.token, bearer, &team_report_path(team_id))?;
+
+    let internal_error = |error: sqlx::Error| {
+        tracing::error!("Failed to create the team report: {:?}", error);
+        DownloadError::Generation
+    };
+    let team = match team_id {
+        Some(team_id) => match team::get_team(&team_id, &pool).await {
+            Ok(team) => Some(team),
+            Err(sqlx::Error::RowNotFound) => return Err(DownloadError::NotFound),
+            Err(error) => return Err(internal_error(error)),
+        },
+        None => None,
+    };
+    if !team::may_see_report(&employee_id, team.as_ref(), &pool)
+        .await
+        .map_err(internal_error)?
+    {
+        return Err(DownloadError::Forbidden);
+    }
+
+    let employee_ids = team::get_member_ids(team_id, &pool)
+        .await
+        .map_err(internal_error)?;
+    let report = report::get_team_report(&employee_ids, query.from, query.to, &pool)
+        .await
+        .map_err(internal_error)?;
+
+    let branding = Branding::load(&pool).await.map_err(|error| {
+        tracing::error!("Failed to load the company settings: {:?}", error);
+        DownloadError::Generation
+    })?;
+
+    let team_name = team.map(|team| team.name);
+    let pdf = render_team_pdf(&report, team_name.as_deref(), query.locale, &branding).map_err(
+        |error| {
+            tracing::error!("Failed to render the team report: {:?}", error);
+            DownloadError::Generation
+        },
+    )?;
+
+    attachment(
+        pdf,
+        "application/pdf",
+        &format!("team-report-{}-{}.pdf", query.from, query.to),
+    )
+}
+
+// GET /reports/billing/{customerId|all}.pdf, only for managers
+pub async fn billing_report_pdf(
+    State(pool): State<PgPool>,
+    Path(file): Path<String>,
+    Query(query): Query<BillingQuery>,
+    bearer: Option<TypedHeader<Authorization<Bearer>>>,
+) -> Result<Response, DownloadError> {
+    let customer_id = match file.strip_suffix(".pdf").ok_or(DownloadError::NotFound)? {
+        "all" => None,
+        customer_id => Some(
+            customer_id
+                .parse::<i32>()
+                .map_err(|_| DownloadError::NotFound)?,
+        ),
+    };
+    if query.to < query.from {
+        return Err(DownloadError::BadRequest);
+    }
+
+    let employee_id = authenticate(query.token, bearer, &billing_report_path(customer_id))?;
+
+    let internal_error = |error: sqlx::Error| {
+        tracing::error!("Failed to create the billing summary: {:?}", error);
+        DownloadError::Generation
+    };
+    if employee::get_role(&employee_id, &pool)
+        .await
+        .map_err(internal_error)?
+        < EmployeeRole::Manager
+    {
+        return Err(DownloadError::Forbidden);
+    }
+
+    let summary = match billing::get_billing_summary(customer_id, query.from, query.to, &pool).await
+    {
+        Ok(summary) => summary,
+        Err(sqlx::Error::RowNotFound) => return Err(DownloadError::NotFound),
+        Err(error) => return Err(internal_error(error)),
+    };
+
+    let branding = Branding::load(&pool).await.map_err(|error| {
+        tracing::error!("Failed to load the company settings: {:?}", error);
+        DownloadError::Generation
+    })?;
+
+    let pdf = render_billing_pdf(&summary, query.locale, &branding).map_err(|error| {
+        tracing::error!("Failed to render the billing summary: {:?}", error);
+        DownloadError::Generation
+    })?;
+
+    attachment(
+        pdf,
+        "application/pdf",
+        &format!("billing-{}-{}.pdf", query.from, query.to),
+    )
+}
+
+// GET /reports/task/{taskId}.{pdf,csv}, only for managers since it contains every employee
+pub async fn task_report(
+    State(pool): State<PgPool>,
+    Path(file): Path<String>,
+    Query(query): Query<TaskReportQuery>,
+    bearer: Option<TypedHeader<Authorization<Bearer>>>,
+) -> Result<Response, DownloadError> {
+    let (task_id, format) = file
+        .split_once('.')
+        .and_then(|(task_id, extension)| {
+            Some((
+                task_id.parse::<i32>().ok()?,
+                ReportFormat::from_extension(extension)?,
+            ))
+        })
+        .ok_or(DownloadError::NotFound)?;
+    if query.to < query.from {
+        return Err(DownloadError::BadRequest);
+    }
+
+    let employee_id = authenticate(query.token, bearer, &task_report_path(task_id, format))?;
+
+    let internal_error = |error: sqlx::Error| {
+        tracing::error!("Failed to create the task report: {:?}", error);
+        DownloadError::Generation
+    };
+    if employee::get_role(&employee_id, &pool)
+        .await
+        .map_err(internal_error)?
+        < EmployeeRole::Manager
+    {
+        return Err(DownloadError::Forbidden);
+    }
+
+    let report = match report::get_task_report(task_id, query.from, query.to, &pool).await {
+        Ok(report) => report,
+        Err(sqlx::Error::RowNotFound) => return Err(DownloadError::NotFound),
+        Err(error) => return Err(internal_error(error)),
+    };
+
+    let file = match format {
+        ReportFormat::Pdf => match Branding::load(&pool).await {
+            Ok(branding) => render_task_pdf(&report, query.locale, &branding),
+            Err(error) => Err(error),
+        },
+        ReportFormat::Csv => write_task_report_csv(&report, query.style),
+    }
+    .map_err(|error| {
+        tracing::error!("Failed to render the task report: {:?}", error);
+        DownloadError::Generation
+    })?;
+
+    attachment(
+        file,
+        format.content_type(),
+        &format!(
+            "task-report-{}-{}-{}.{}",
+            task_id,
+            query.from,
+            query.to,
+            format.extension()
+        ),
+    )
+}
+
+// GET /reports/verify/{hash}, without authentication so that customers and auditors can check a
+// printed report with its QR code
+pub async fn verify_report(
+    State(pool): State<PgPool>,
+    Path(hash): Path<String>,
+) -> Result<Json<ReportVerification>, DownloadError> {
+    match verification::verify_report(&hash, &pool).await {
+        Ok(Some(verification)) => Ok(Json(verification)),
+        Ok(None) => Err(DownloadError::NotFound),
+        Err(error) => {
+            tracing::error!("Failed to verify the report {}: {:?}", hash, error);
+            Err(DownloadError::Generation)
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use tokio::sync::broadcast;
+
+use crate::models::{TaskBudgetAlert, TimerEvent, TimerEventKind, Worktime};
+
+// events are only buffered for subscribers that lag behind, nothing is persisted
+const CHANNEL_CAPACITY: usize = 256;
+
+/// Broadcasts timer changes to every open subscription.
+#[derive(Clone)]
+pub struct TimerEvents {
+    sender: broadcast::Sender<TimerEvent>,
+}
+
+impl TimerEvents {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { sender }
+    }
+
+    pub fn publish(&self, kind: TimerEventKind, worktime: Worktime) {
+        // sending only fails if nobody is subscribed, which is fine
+        let _ = self.sender.send(TimerEvent { kind, worktime });
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<TimerEvent> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for TimerEvents {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Broadcasts the tasks whose booked time passed a threshold of their estimate.
+#[derive(Clone)]
+pub struct TaskBudgetAlerts {
+    sender: broadcast::Sender<TaskBudgetAlert>,
+}
+
+impl TaskBudgetAlerts {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { sender }
+    }
+
+    pub fn publish(&self, alert: TaskBudgetAlert) {
+        let _ = self.sender.send(alert);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<TaskBudgetAlert> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for TaskBudgetAlerts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,636 @@
+use crate::models::{EmployeeRole, MonthReport, TaskReport, WorktimeType};
+use crate::service::{employee, team};
+use crate::time_utils;
+use anyhow::anyhow;
+use async_graphql::Enum;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use rust_xlsxwriter::{Format, Workbook};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+pub mod datev;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Xlsx,
+}
+
+impl ExportFormat {
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "csv" => Some(ExportFormat::Csv),
+            "xlsx" => Some(ExportFormat::Xlsx),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Xlsx => "xlsx",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Xlsx => {
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+            }
+        }
+    }
+}
+
+/// File format of reports which are either printed or processed further.
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReportFormat {
+    #[default]
+    Pdf,
+    Csv,
+}
+
+impl ReportFormat {
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "pdf" => Some(ReportFormat::Pdf),
+            "csv" => Some(ReportFormat::Csv),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ReportFormat::Pdf => "pdf",
+            ReportFormat::Csv => "csv",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ReportFormat::Pdf => "application/pdf",
+            ReportFormat::Csv => ExportFormat::Csv.content_type(),
+        }
+    }
+}
+
+/// Formatting of dates, numbers and csv separators in the export.
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ExportStyle {
+    /// 31.01.2024, 8,50 and semicolon separated like a German Excel expects
+    #[default]
+    German,
+    /// 2024-01-31, 8.50 and comma separated
+    Iso,
+}
+
+impl ExportStyle {
+    fn headers(self) -> [&'static str; 8] {
+        match self {
+            ExportStyle::German => [
+                "Mitarbeiter",
+                "Datum",
+                "Aufgabe",
+                "Art",
+                "Beginn",
+                "Ende",
+                "Dauer (h)",
+                "Kommentar",
+            ],
+            ExportStyle::Iso => [
+                "Employee",
+                "Date",
+                "Task",
+                "Type",
+                "Start",
+                "End",
+                "Duration (h)",
+                "Comment",
+            ],
+        }
+    }
+
+    fn task_report_headers(self) -> [&'static str; 5] {
+        match self {
+            ExportStyle::German => ["Aufgabe", "Mitarbeiter", "Art", "Dauer (h)", "Zeitraum"],
+            ExportStyle::Iso => ["Task", "Employee", "Type", "Duration (h)", "Period"],
+        }
+    }
+
+    fn month_report_headers(self) -> [&'static str; 6] {
+        match self {
+            ExportStyle::German => [
+                "Datum",
+                "Arbeit (h)",
+                "Fahrt (h)",
+                "Pause (h)",
+                "Gesamt (h)",
+                "Netto (h)",
+            ],
+            ExportStyle::Iso => [
+                "Date",
+                "Work (h)",
+                "Ride (h)",
+                "Break (h)",
+                "Total (h)",
+                "Net (h)",
+            ],
+        }
+    }
+
+    fn date_format(self) -> &'static str {
+        match self {
+            ExportStyle::German => "%d.%m.%Y",
+            ExportStyle::Iso => "%Y-%m-%d",
+        }
+    }
+
+    fn excel_date_format(self) -> &'static str {
+        match self {
+            ExportStyle::German => "dd.mm.yyyy",
+            ExportStyle::Iso => "yyyy-mm-dd",
+        }
+    }
+
+    fn delimiter(self) -> u8 {
+        match self {
+            ExportStyle::German => b';',
+            ExportStyle::Iso => b',',
+        }
+    }
+
+    fn work_type(self, work_type: WorktimeType) -> &'static str {
+        match (self, work_type) {
+            (ExportStyle::German, WorktimeType::Work) => "Arbeit",
+            (ExportStyle::German, WorktimeType::Break) => "Pause",
+            (ExportStyle::German, WorktimeType::Ride) => "Fahrt",
+            (ExportStyle::Iso, WorktimeType::Work) => "work",
+            (ExportStyle::Iso, WorktimeType::Break) => "break",
+            (ExportStyle::Iso, WorktimeType::Ride) => "ride",
+        }
+    }
+
+    // decimal hours with two places, payroll systems do not understand 8:30
+    fn format_hours(self, hours: f64) -> String {
+        let formatted = format!("{:.2}", hours);
+        match self {
+            ExportStyle::German => formatted.replace('.', ","),
+            ExportStyle::Iso => formatted,
+        }
+    }
+}
+
+struct ExportRow {
+    employee: String,
+    timezone: Option<String>,
+    task: Option<String>,
+    work_type: WorktimeType,
+    start_time: DateTime<Utc>,
+    end_time: Option<DateTime<Utc>>,
+    comment: Option<String>,
+}
+
+impl ExportRow {
+    // start and end in the timezone of the employee who worked
+    fn local_times(&self) -> (NaiveDateTime, Option<NaiveDateTime>) {
+        let timezone = time_utils::resolve_timezone(self.timezone.as_deref());
+        (
+            self.start_time.with_timezone(&timezone).naive_local(),
+            self.end_time
+                .map(|end_time| end_time.with_timezone(&timezone).naive_local()),
+        )
+    }
+
+    // running timers have no duration yet
+    fn hours(&self) -> Option<f64> {
+        self.end_time
+            .map(|end_time| (end_time - self.start_time).num_minutes() as f64 / 60.0)
+    }
+}
+
+// admins may export the worktimes of everyone, managers the ones of the members of their
+// teams and employees only their own
+pub async fn may_export(
+    employee_id: &i32,
+    employee_ids: &[i32],
+    pool: &PgPool,
+) -> sqlx::Result<bool> {
+    if employee_ids.iter().all(|id| id == employee_id) {
+        return Ok(true);
+    }
+
+    Ok(match employee::get_role(employee_id, pool).await? {
+        EmployeeRole::Admin => true,
+        EmployeeRole::Manager => {
+            let managed_ids = team::get_managed_ids(employee_id, pool).await?;
+            employee_ids
+                .iter()
+                .all(|id| id == employee_id || managed_ids.contains(id))
+        }
+        EmployeeRole::Employee => false,
+    })
+}
+
+/// Exports the worktimes of the employees which started between `from` and `to` (both
+/// inclusive), the days are taken in the given timezone.
+pub async fn export_worktimes(
+    employee_ids: &[i32],
+    from: NaiveDate,
+    to: NaiveDate,
+    format: ExportFormat,
+    style: ExportStyle,
+    timezone: &Tz,
+    pool: &PgPool,
+) -> anyhow::Result<Vec<u8>> {
+    if to < from {
+        return Err(anyhow!("the end of the export is before its start"));
+    }
+
+    let (start, _) = time_utils::day_bounds(from, timezone);
+    let (_, end) = time_utils::day_bounds(to, timezone);
+
+    let rows = sqlx::query_as!(
+        ExportRow,
+        r#"SELECT CONCAT_WS(' ', e.firstname, e.lastname) as "employee!", e.timezone, t.task_description as "task?", w.work_type as "work_type: WorktimeType", w.start_time, w.end_time, w.comment
+        FROM worktime w
+        JOIN employee e ON e.employee_id = w.employee_id
+        LEFT JOIN task t ON t.task_id = w.task_id
+        WHERE w.employee_id = ANY($1) AND w.start_time >= $2 AND w.start_time < $3
+        ORDER BY e.lastname, e.firstname, w.employee_id, w.start_time"#,
+        employee_ids,
+        start,
+        end,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    match format {
+        ExportFormat::Csv => write_csv(&rows, style),
+        ExportFormat::Xlsx => write_xlsx(&rows, style),
+    }
+}
+
+// text typed by users is prefixed with an apostrophe in csv files if it starts like a formula, so
+// that a spreadsheet shows it instead of evaluating it
+pub(crate) fn escape_cell(value: &str) -> String {
+    if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+fn csv_writer(style: ExportStyle) -> csv::Writer<Vec<u8>> {
+    let mut buffer = Vec::new();
+    // without the byte order mark Excel reads the file as Windows-1252 and breaks umlauts
+    if style == ExportStyle::German {
+        buffer.extend_from_slice("\u{feff}".as_bytes());
+    }
+
+    csv::WriterBuilder::new()
+        .delimiter(style.delimiter())
+        .from_writer(buffer)
+}
+
+fn write_csv(rows: &[ExportRow], style: ExportStyle) -> anyhow::Result<Vec<u8>> {
+    let mut writer = csv_writer(style);
+    writer.write_record(style.headers())?;
+
+    for row in rows {
+        let (start, end) = row.local_times();
+        writer.write_record([
+            escape_cell(&row.employee),
+            start.format(style.date_format()).to_string(),
+            escape_cell(row.task.as_deref().unwrap_or_default()),
+            style.work_type(row.work_type).to_string(),
+            start.format("%H:%M").to_string(),
+            end.map(|end| end.format("%H:%M").to_string())
+                .unwrap_or_default(),
+            row.hours()
+                .map(|hours| style.format_hours(hours))
+                .unwrap_or_default(),
+            escape_cell(row.comment.as_deref().unwrap_or_default()),
+        ])?;
+    }
+
+    Ok(writer.into_inner()?)
+}
+
+/// Writes one line per employee and work type of the task report followed by the total.
+pub fn write_task_report_csv(report: &TaskReport, style: ExportStyle) -> anyhow::Result<Vec<u8>> {
+    let task = escape_cell(report.task_description.as_deref().unwrap_or_default());
+    let period = format!(
+        "{} - {}",
+        report.from.format(style.date_format()),
+        report.to.format(style.date_format())
+    );
+    let mut writer = csv_writer(style);
+    writer.write_record(style.task_report_headers())?;
+
+    for entry in &report.entries {
+        let employee = [entry.firstname.as_deref(), entry.lastname.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<&str>>()
+            .join(" ");
+        writer.write_record([
+            task.clone(),
+            escape_cell(&employee),
+            style.work_type(entry.work_type).to_string(),
+            style.format_hours(entry.minutes as f64 / 60.0),
+            period.clone(),
+        ])?;
+    }
+    writer.write_record([
+        task,
+        String::new(),
+        String::new(),
+        style.format_hours(report.total_minutes as f64 / 60.0),
+        period,
+    ])?;
+
+    Ok(writer.into_inner()?)
+}
+
+/// Writes one line per day of the report like the day table of the pdf followed by the totals,
+/// the total is the work and ride time and the net time is the total minus the breaks.
+pub fn write_month_report_csv(report: &MonthReport, style: ExportStyle) -> anyhow::Result<Vec<u8>> {
+    let hours = |minutes: i64| style.format_hours(minutes as f64 / 60.0);
+    let mut writer = csv_writer(style);
+    writer.write_record(style.month_report_headers())?;
+
+    for day in &report.days {
+        writer.write_record([
+            day.date.format(style.date_format()).to_string(),
+            hours(day.work_minutes),
+            hours(day.ride_minutes),
+            hours(day.break_minutes),
+            hours(day.total()),
+            hours(day.net()),
+        ])?;
+    }
+    writer.write_record([
+        String::new(),
+        hours(report.work_minutes),
+        hours(report.ride_minutes),
+        hours(report.break_minutes),
+        hours(report.total()),
+        hours(report.net()),
+    ])?;
+
+    Ok(writer.into_inner()?)
+}
+
+// dates and numbers are written as typed cells, Excel displays them in the locale of the reader
+fn write_xlsx(rows: &[ExportRow], style: ExportStyle) -> anyhow::Result<Vec<u8>> {
+    let mut workbook = Workbook::new();
+    let header_format = Format::new().set_bold();
+    let date_format = Format::new().set_num_format(style.excel_date_format());
+    let time_format = Format::new().set_num_format("hh:mm");
+    let hours_format = Format::new().set_num_format("0.00");
+
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name(match style {
+        ExportStyle::German => "Arbeitszeiten",
+        ExportStyle::Iso => "Worktimes",
+    })?;
+
+    for (column, header) in style.headers().iter().enumerate() {
+        worksheet.write_string_with_format(0, column as u16, *header, &header_format)?;
+    }
+
+    for (index, row) in rows.iter().enumerate() {
+        let line = index as u32 + 1;
+        let (start, end) = row.local_times();
+
+        worksheet.write_string(line, 0, &row.employee)?;
+        worksheet.write_datetime_with_format(line, 1, start.date(), &date_format)?;
+        worksheet.write_string(line, 2, row.task.as_deref().unwrap_or_default())?;
+        worksheet.write_string(line, 3, style.work_type(row.work_type))?;
+        worksheet.write_datetime_with_format(line, 4, start.time(), &time_format)?;
+        if let Some(end) = end {
+            worksheet.write_datetime_with_format(line, 5, end.time(), &time_format)?;
+        }
+        if let Some(hours) = row.hours() {
+            worksheet.write_number_with_format(line, 6, hours, &hours_format)?;
+        }
+        // string cells are never evaluated, unlike the same text in a csv
+        worksheet.write_string(line, 7, row.comment.as_deref().unwrap_or_default())?;
+    }
+
+    worksheet.set_freeze_panes(1, 0)?;
+    worksheet.autofit();
+
+    Ok(workbook.save_to_buffer()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_hours() {
+        assert_eq!(ExportStyle::German.format_hours(8.5), "8,50");
+        assert_eq!(ExportStyle::Iso.format_hours(8.5), "8.50");
+        assert_eq!(ExportStyle::German.format_hours(2.0 / 3.0), "0,67");
+    }
+
+    #[sqlx::test(fixtures(
+        "../fixtures/truncate.sql",
+        "../fixtures/task.sql",
+        "../fixtures/address.sql",
+        "../fixtures/employee.sql",
+        "../fixtures/worktime.sql"
+    ))]
+    async fn test_export_csv(pool: PgPool) -> anyhow::Result<()> {
+        // given is a day with two worktimes, one comment looks like a formula
+        sqlx::query!("UPDATE worktime SET comment = 'Stau; A3' WHERE worktime_id = 4")
+            .execute(&pool)
+            .await?;
+        sqlx::query!("UPDATE worktime SET comment = '=1+1' WHERE worktime_id = 5")
+            .execute(&pool)
+            .await?;
+        let day = NaiveDate::from_ymd_opt(2024, 1, 3).unwrap();
+
+        // when it is exported in both styles
+        let german = export_worktimes(
+            &[1],
+            day,
+            day,
+            ExportFormat::Csv,
+            ExportStyle::German,
+            &chrono_tz::Europe::Berlin,
+            &pool,
+        )
+        .await?;
+        let iso = export_worktimes(
+            &[1],
+            day,
+            day,
+            ExportFormat::Csv,
+            ExportStyle::Iso,
+            &chrono_tz::Europe::Berlin,
+            &pool,
+        )
+        .await?;
+
+        // then the times are local and formatted like the style
+        assert_eq!(
+            String::from_utf8(german)?,
+            "\u{feff}Mitarbeiter;Datum;Aufgabe;Art;Beginn;Ende;Dauer (h);Kommentar\n\
+            bob baumeister;03.01.2024;second task;Fahrt;13:30;15:30;2,00;\"Stau; A3\"\n\
+            bob baumeister;03.01.2024;Repair Millennium Falcon;Arbeit;16:00;19:00;3,00;'=1+1\n"
+        );
+        assert_eq!(
+            String::from_utf8(iso)?,
+            "Employee,Date,Task,Type,Start,End,Duration (h),Comment\n\
+            bob baumeister,2024-01-03,second task,ride,13:30,15:30,2.00,Stau; A3\n\
+            bob baumeister,2024-01-03,Repair Millennium Falcon,work,16:00,19:00,3.00,'=1+1\n"
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(
+        "../fixtures/truncate.sql",
+        "../fixtures/task.sql",
+        "../fixtures/address.sql",
+        "../fixtures/employee.sql",
+        "../fixtures/worktime.sql"
+    ))]
+    async fn test_export_xlsx(pool: PgPool) -> anyhow::Result<()> {
+        let xlsx = export_worktimes(
+            &[1, 2],
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+            ExportFormat::Xlsx,
+            ExportStyle::German,
+            &chrono_tz::Europe::Berlin,
+            &pool,
+        )
+        .await?;
+
+        // xlsx files are zip archives
+        assert!(xlsx.starts_with(b"PK"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_escape_cell() {
+        assert_eq!(escape_cell("=HYPERLINK(\"x\")"), "'=HYPERLINK(\"x\")");
+        assert_eq!(escape_cell("+49 170"), "'+49 170");
+        assert_eq!(escape_cell("-1"), "'-1");
+        assert_eq!(escape_cell("@SUM(A1)"), "'@SUM(A1)");
+        assert_eq!(escape_cell("Stau; A3"), "Stau; A3");
+        assert_eq!(escape_cell(""), "");
+    }
+
+    #[test]
+    fn test_write_task_report_csv() -> anyhow::Result<()> {
+        let report = TaskReport {
+            task_id: 2,
+            task_description: Some(String::from("second task")),
+            from: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            to: NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+            entries: vec![crate::models::TaskReportEntry {
+                employee_id: 1,
+                firstname: Some(String::from("bob")),
+                lastname: Some(String::from("baumeister")),
+                work_type: WorktimeType::Ride,
+                minutes: 90,
+            }],
+            total_minutes: 90,
+        };
+
+        let csv = write_task_report_csv(&report, ExportStyle::Iso)?;
+
+        assert_eq!(
+            String::from_utf8(csv)?,
+            "Task,Employee,Type,Duration (h),Period\n\
+            second task,bob baumeister,ride,1.50,2024-01-01 - 2024-01-31\n\
+            second task,,,1.50,2024-01-01 - 2024-01-31\n"
+        );
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(
+        "../fixtures/truncate.sql",
+        "../fixtures/task.sql",
+        "../fixtures/address.sql",
+        "../fixtures/employee.sql",
+        "../fixtures/worktime.sql"
+    ))]
+    async fn test_write_month_report_csv(pool: PgPool) -> anyhow::Result<()> {
+        // given is the report of the first two days of january
+        let report = crate::service::report::get_month_report(
+            1,
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 2).unwrap(),
+            &pool,
+        )
+        .await?;
+
+        let csv = String::from_utf8(write_month_report_csv(&report, ExportStyle::German)?)?;
+
+        // then every day is a line with the same totals as the report
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(
+            lines[0],
+            "\u{feff}Datum;Arbeit (h);Fahrt (h);Pause (h);Gesamt (h);Netto (h)"
+        );
+        assert!(lines[1].starts_with("01.01.2024;"));
+        assert!(lines[2].starts_with("02.01.2024;"));
+        let hours = |minutes: i64| ExportStyle::German.format_hours(minutes as f64 / 60.0);
+        assert_eq!(
+            lines[3],
+            format!(
+                ";{};{};{};{};{}",
+                hours(report.work_minutes),
+                hours(report.ride_minutes),
+                hours(report.break_minutes),
+                hours(report.total()),
+                hours(report.net())
+            )
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(
+        "../fixtures/truncate.sql",
+        "../fixtures/address.sql",
+        "../fixtures/employee.sql"
+    ))]
+    async fn test_may_export(pool: PgPool) -> sqlx::Result<()> {
+        // employees can only export their own worktimes, managers the ones of their team
+        let team = team::create_team("Montage", Some(3), &pool).await?;
+        team::set_employee_team(&1, Some(team.team_id), &pool).await?;
+        assert!(may_export(&1, &[1], &pool).await?);
+        assert!(!may_export(&1, &[1, 2], &pool).await?);
+        assert!(may_export(&3, &[1, 3], &pool).await?);
+        assert!(!may_export(&3, &[1, 2], &pool).await?);
+
+        // the manager of another team is denied
+        let other_team = team::create_team("Vertrieb", Some(2), &pool).await?;
+        team::set_employee_team(&2, Some(other_team.team_id), &pool).await?;
+        sqlx::query!("UPDATE employee SET role = 'manager' WHERE employee_id = 2")
+            .execute(&pool)
+            .await?;
+        assert!(!may_export(&2, &[1], &pool).await?);
+
+        // admins may export everyone
+        sqlx::query!("UPDATE employee SET role = 'admin' WHERE employee_id = 2")
+            .execute(&pool)
+            .await?;
+        assert!(may_export(&2, &[1, 3], &pool).await?);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,300 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::Context;
+use chrono::{Datelike, NaiveDate, Utc};
+use sqlx::PgPool;
+
+use crate::models::{AbsenceStatus, AbsenceType, Worktime, WorktimeType};
+use crate::pdf::{get_month_worktimes, validate_month};
+use crate::service::{absence, employee, report};
+use crate::time_utils;
+
+/// Numbers of the consultant and client in DATEV and the wage types (Lohnarten) the worktime
+/// and absence types are booked on. Types without a wage type are not exported.
+pub struct DatevConfig {
+    pub consultant_number: String,
+    pub client_number: String,
+    pub work: Option<u16>,
+    pub ride: Option<u16>,
+    pub pause: Option<u16>,
+    pub vacation: Option<u16>,
+    pub sickness: Option<u16>,
+    pub other_absence: Option<u16>,
+}
+
+impl DatevConfig {
+    pub fn from_env() -> Self {
+        Self {
+            consultant_number: dotenvy::var("DATEV_CONSULTANT_NUMBER").unwrap_or_default(),
+            client_number: dotenvy::var("DATEV_CLIENT_NUMBER").unwrap_or_default(),
+            work: wage_type_from_env("DATEV_WAGE_TYPE_WORK", Some(100)),
+            ride: wage_type_from_env("DATEV_WAGE_TYPE_RIDE", Some(110)),
+            pause: wage_type_from_env("DATEV_WAGE_TYPE_BREAK", None),
+            vacation: wage_type_from_env("DATEV_WAGE_TYPE_VACATION", Some(300)),
+            sickness: wage_type_from_env("DATEV_WAGE_TYPE_SICKNESS", Some(310)),
+            other_absence: wage_type_from_env("DATEV_WAGE_TYPE_OTHER_ABSENCE", None),
+        }
+    }
+
+    fn wage_type(&self, work_type: WorktimeType) -> Option<u16> {
+        match work_type {
+            WorktimeType::Work => self.work,
+            WorktimeType::Ride => self.ride,
+            WorktimeType::Break => self.pause,
+        }
+    }
+
+    fn absence_wage_type(&self, absence_type: AbsenceType) -> Option<u16> {
+        match absence_type {
+            AbsenceType::Vacation => self.vacation,
+            AbsenceType::Sickness => self.sickness,
+            AbsenceType::Other => self.other_absence,
+        }
+    }
+}
+
+// an empty value or 'none' disables the export of the worktime type
+fn wage_type_from_env(name: &str, default: Option<u16>) -> Option<u16> {
+    match dotenvy::var(name) {
+        Err(_) => default,
+        Ok(value) => {
+            let wage_type = parse_wage_type(&value);
+            if wage_type.is_none() && !value.trim().is_empty() && value.trim() != "none" {
+                tracing::warn!("{} '{}' is not a wage type number", name, value);
+            }
+            wage_type
+        }
+    }
+}
+
+fn parse_wage_type(value: &str) -> Option<u16> {
+    value
+        .trim()
+        .parse::<u16>()
+        .ok()
+        .filter(|wage_type| *wage_type <= 9999)
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PayrollLine {
+    pub personnel_number: String,
+    pub wage_type: u16,
+    pub hours: f64,
+}
+
+/// Books the totals of the month report of every employee on the wage types, the month is taken
+/// in the timezone of each employee like in the monthly pdf and only stopped worktimes count.
+/// Approved absences are booked with the target time of their weekdays within the month.
+pub async fn payroll_lines(
+    given_month: &str,
+    config: &DatevConfig,
+    pool: &PgPool,
+) -> anyhow::Result<Vec<PayrollLine>> {
+    let first_day = validate_month(given_month)?;
+    let last_day = first_day
+        .checked_add_months(chrono::Months::new(1))
+        .and_then(|next_month| next_month.pred_opt())
+        .context("the month is out of range")?;
+    let mut lines = Vec::new();
+
+    for employee in employee::get_all_employees(pool).await? {
+        let timezone = time_utils::resolve_timezone(employee.timezone.as_deref());
+        let worktimes = get_month_worktimes(
+            first_day.year(),
+            first_day.month(),
+            &timezone,
+            pool,
+            &employee.employee_id,
+        )
+        .await?;
+
+        // running timers are booked once they are stopped, worktimes over the end of the month
+        // only count with their part within the month
+        let worktimes: Vec<Worktime> = worktimes
+            .into_iter()
+            .filter(|worktime| worktime.end_time.is_some())
+            .collect();
+        let report = report::build_month_report(
+            employee.employee_id,
+            &worktimes,
+            first_day,
+            last_day,
+            &timezone,
+            Utc::now(),
+            &HashMap::new(),
+        );
+
+        let mut minutes_per_wage_type: BTreeMap<u16, i64> = BTreeMap::new();
+        for (work_type, minutes) in [
+            (WorktimeType::Work, report.work_minutes),
+            (WorktimeType::Ride, report.ride_minutes),
+            (WorktimeType::Break, report.break_minutes),
+        ] {
+            if let Some(wage_type) = config.wage_type(work_type) {
+                if minutes > 0 {
+                    *minutes_per_wage_type.entry(wage_type).or_default() += minutes;
+                }
+            }
+        }
+        let absences = absence::get_absences(
+            &[employee.employee_id],
+            first_day,
+            last_day,
+            Some(AbsenceStatus::Approved),
+            pool,
+        )
+        .await?;
+        for absence in &absences {
+            let Some(wage_type) = config.absence_wage_type(absence.absence_type) else {
+                continue;
+            };
+            let minutes = absence::absence_minutes(
+                absence,
+                employee.weekly_time.as_ref(),
+                first_day,
+                last_day,
+            );
+            if minutes > 0 {
+                *minutes_per_wage_type.entry(wage_type).or_default() += minutes;
+            }
+        }
+
+        let personnel_number = employee
+            .personnel_number
+            .unwrap_or_else(|| employee.employee_id.to_string());
+        lines.extend(
+            minutes_per_wage_type
+                .into_iter()
+                .map(|(wage_type, minutes)| PayrollLine {
+                    personnel_number: personnel_number.clone(),
+                    wage_type,
+                    hours: minutes as f64 / 60.0,
+                }),
+        );
+    }
+
+    Ok(lines)
+}
+
+// Semicolon separated lines for the ASCII import of Lohn und Gehalt:
+// Beraternummer;Mandantennummer;Personalnummer;Abrechnungsmonat;Lohnart;Stunden
+fn write_datev(lines: &[PayrollLine], month: NaiveDate, config: &DatevConfig) -> String {
+    lines
+        .iter()
+        .map(|line| {
+            format!(
+                "{};{};{};{};{};{}\r\n",
+                config.consultant_number,
+                config.client_number,
+                line.personnel_number,
+                month.format("%m/%Y"),
+                line.wage_type,
+                format!("{:.2}", line.hours).replace('.', ",")
+            )
+        })
+        .collect()
+}
+
+pub async fn export_datev(
+    given_month: &str,
+    config: &DatevConfig,
+    pool: &PgPool,
+) -> anyhow::Result<Vec<u8>> {
+    let month = validate_month(given_month).context("given month has the wrong format")?;
+    let lines = payroll_lines(given_month, config, pool).await?;
+
+    encode_windows_1252(&write_datev(&lines, month, config))
+}
+
+// DATEV expects the ANSI codepage of Windows instead of UTF-8
+fn encode_windows_1252(text: &str) -> anyhow::Result<Vec<u8>> {
+    let (bytes, _, had_errors) = encoding_rs::WINDOWS_1252.encode(text);
+    if had_errors {
+        anyhow::bail!("the export contains characters which are not in Windows-1252");
+    }
+    Ok(bytes.into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> DatevConfig {
+        DatevConfig {
+            consultant_number: String::from("1234567"),
+            client_number: String::from("12345"),
+            work: Some(100),
+            ride: Some(110),
+            pause: None,
+            vacation: Some(300),
+            sickness: Some(310),
+            other_absence: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_wage_type() {
+        assert_eq!(parse_wage_type("100"), Some(100));
+        assert_eq!(parse_wage_type(" 2000 "), Some(2000));
+        assert_eq!(parse_wage_type("none"), None);
+        assert_eq!(parse_wage_type("10000"), None);
+    }
+
+    #[test]
+    fn test_encode_windows_1252() -> anyhow::Result<()> {
+        assert_eq!(encode_windows_1252("1;Ä;2,50\r\n")?, b"1;\xC4;2,50\r\n");
+        assert!(encode_windows_1252("€").is_ok());
+        assert!(encode_windows_1252("漢").is_err());
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/task.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+        "../../fixtures/worktime.sql"
+    ))]
+    async fn test_export_datev(pool: PgPool) -> anyhow::Result<()> {
+        // given is an employee with a personnel number
+        sqlx::query!("UPDATE employee SET personnel_number = '00042' WHERE employee_id = 2")
+            .execute(&pool)
+            .await?;
+        // and an approved vacation over the end of the month, a rejected and an unmapped absence
+        let day = |month, day| NaiveDate::from_ymd_opt(2024, month, day).unwrap();
+        let vacation = absence::request_absence(
+            &2,
+            AbsenceType::Vacation,
+            day(1, 30),
+            day(2, 2),
+            None,
+            &pool,
+        )
+        .await?;
+        absence::review_absence(vacation.absence_id, AbsenceStatus::Approved, &pool).await?;
+        let rejected =
+            absence::request_absence(&1, AbsenceType::Vacation, day(1, 8), day(1, 8), None, &pool)
+                .await?;
+        absence::review_absence(rejected.absence_id, AbsenceStatus::Rejected, &pool).await?;
+        let other =
+            absence::request_absence(&1, AbsenceType::Other, day(1, 9), day(1, 9), None, &pool)
+                .await?;
+        absence::review_absence(other.absence_id, AbsenceStatus::Approved, &pool).await?;
+
+        // when the january is exported
+        let export = export_datev("2024-01", &config(), &pool).await?;
+        let export = encoding_rs::WINDOWS_1252.decode(&export).0;
+
+        // then the hours are summed up per wage type, breaks are left out and the vacation counts
+        // with the target time of tuesday and wednesday
+        assert_eq!(
+            export,
+            "1234567;12345;1;01/2024;100;75,00\r\n\
+            1234567;12345;1;01/2024;110;58,00\r\n\
+            1234567;12345;00042;01/2024;100;8,00\r\n\
+            1234567;12345;00042;01/2024;300;7,20\r\n"
+        );
+
+        Ok(())
+    }
+}
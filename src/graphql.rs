@@ -1,9 +1,29 @@
-use async_graphql::{extensions::Logger, EmptySubscription, MergedObject, Schema};
-use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
-use axum::Extension;
+use async_graphql::{
+    dataloader::DataLoader, extensions::Logger, http::ALL_WEBSOCKET_PROTOCOLS, MergedObject,
+    MergedSubscription, Schema,
+};
+use async_graphql_axum::{GraphQLProtocol, GraphQLRequest, GraphQLResponse, GraphQLWebSocket};
+use axum::{
+    extract::WebSocketUpgrade,
+    response::{IntoResponse, Response},
+    Extension,
+};
 
+use crate::{
+    auth::ws_connection_init,
+    events::{TaskBudgetAlerts, TimerEvents},
+};
+
+mod absence;
+mod billing;
+mod company;
 mod employee;
+mod export;
+mod guard;
+pub(crate) mod loader;
+mod notification;
 mod pdf;
+mod report;
 mod task;
 mod timer;
 
@@ -13,6 +33,12 @@ pub struct Query(
     task::TaskQuery,
     pdf::PDFQuery,
     employee::EmployeeQuery,
+    notification::NotificationQuery,
+    export::ExportQuery,
+    report::ReportQuery,
+    company::CompanyQuery,
+    billing::BillingQuery,
+    absence::AbsenceQuery,
 );
 
 #[derive(MergedObject, Default)]
@@ -20,15 +46,53 @@ pub struct Mutation(
     timer::TimerMutation,
     task::TaskMutation,
     employee::EmployeeMutation,
+    notification::NotificationMutation,
+    report::TeamMutation,
+    company::CompanyMutation,
+    billing::BillingMutation,
+    absence::AbsenceMutation,
 );
 
-pub type SchemaType = Schema<Query, Mutation, EmptySubscription>;
+#[derive(MergedSubscription, Default)]
+pub struct Subscription(timer::TimerSubscription, task::TaskSubscription);
+
+pub type SchemaType = Schema<Query, Mutation, Subscription>;
 
-pub fn create_schema(database_pool: sqlx::PgPool) -> SchemaType {
-    Schema::build(Query::default(), Mutation::default(), EmptySubscription)
-        .extension(Logger)
-        .data(database_pool)
-        .finish()
+pub fn create_schema(
+    database_pool: sqlx::PgPool,
+    timer_events: TimerEvents,
+    budget_alerts: TaskBudgetAlerts,
+) -> SchemaType {
+    Schema::build(
+        Query::default(),
+        Mutation::default(),
+        Subscription::default(),
+    )
+    .extension(Logger)
+    .data(DataLoader::new(
+        loader::TaskLoader::new(database_pool.clone()),
+        tokio::spawn,
+    ))
+    .data(DataLoader::new(
+        loader::EmployeeLoader::new(database_pool.clone()),
+        tokio::spawn,
+    ))
+    .data(DataLoader::new(
+        loader::ProjectLoader::new(database_pool.clone()),
+        tokio::spawn,
+    ))
+    .data(DataLoader::new(
+        loader::CustomerLoader::new(database_pool.clone()),
+        tokio::spawn,
+    ))
+    .data(DataLoader::new(
+        loader::TaskAssignmentLoader::new(database_pool.clone()),
+        tokio::spawn,
+    ))
+    .data(database_pool)
+    .data(timer_events)
+    .data(budget_alerts)
+    .finish()
 }
 
 pub async fn graphql_handler(
@@ -45,3 +109,18 @@ pub async fn graphql_handler(
         .await
         .into()
 }
+
+pub async fn graphql_ws_handler(
+    Extension(schema): Extension<SchemaType>,
+    protocol: GraphQLProtocol,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    upgrade
+        .protocols(ALL_WEBSOCKET_PROTOCOLS)
+        .on_upgrade(move |stream| {
+            GraphQLWebSocket::new(stream, schema, protocol)
+                .on_connection_init(ws_connection_init)
+                .serve()
+        })
+        .into_response()
+}
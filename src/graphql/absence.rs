@@ -0,0 +1,129 @@
+use chrono::NaiveDate;
+
+use crate::{
+    export::may_export,
+    graphql::guard::RoleGuard,
+    models::{self, AbsenceStatus, AbsenceType, EmployeeRole},
+    service::absence,
+};
+
+// like the longest report period
+const MAX_ABSENCE_DAYS: i64 = 366;
+
+#[derive(Default)]
+pub struct AbsenceQuery;
+
+#[async_graphql::Object]
+impl AbsenceQuery {
+    /// Absences overlapping the days between from and to (both inclusive), managers may query
+    /// other employees.
+    async fn absences(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        from: NaiveDate,
+        to: NaiveDate,
+        employee_id: Option<i32>,
+        status: Option<AbsenceStatus>,
+    ) -> async_graphql::Result<Vec<models::Absence>> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+        let own_id = ctx.data::<i32>()?;
+        let employee_id = employee_id.unwrap_or(*own_id);
+
+        if !may_export(own_id, &[employee_id], pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)?
+        {
+            return Err(async_graphql::Error::new(
+                "Only managers can see the absences of other employees",
+            ));
+        }
+
+        absence::get_absences(&[employee_id], from, to, status, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+}
+
+#[derive(Default)]
+pub struct AbsenceMutation;
+
+#[async_graphql::Object]
+impl AbsenceMutation {
+    /// Requests whole days off from startDate to endDate (both inclusive), they count once a
+    /// manager approved them.
+    async fn request_absence(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        absence_type: AbsenceType,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        comment: Option<String>,
+    ) -> async_graphql::Result<models::Absence> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+        let employee_id = ctx.data::<i32>()?;
+
+        if end_date < start_date {
+            return Err(async_graphql::Error::new(
+                "The end of the absence is before its start",
+            ));
+        }
+        if (end_date - start_date).num_days() >= MAX_ABSENCE_DAYS {
+            return Err(async_graphql::Error::new(format!(
+                "An absence can be at most {} days long",
+                MAX_ABSENCE_DAYS
+            )));
+        }
+        if absence::has_overlapping_absence(employee_id, start_date, end_date, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)?
+        {
+            return Err(async_graphql::Error::new(
+                "There is already an absence on these days",
+            ));
+        }
+
+        absence::request_absence(
+            employee_id,
+            absence_type,
+            start_date,
+            end_date,
+            comment.as_deref(),
+            pool,
+        )
+        .await
+        .map_err(async_graphql::Error::new_with_source)
+    }
+
+    /// Withdraws an own absence which is not reviewed yet.
+    async fn cancel_absence(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        absence_id: i32,
+    ) -> async_graphql::Result<Option<models::Absence>> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+        let employee_id = ctx.data::<i32>()?;
+
+        absence::cancel_absence(absence_id, employee_id, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Manager)")]
+    async fn review_absence(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        absence_id: i32,
+        approved: bool,
+    ) -> async_graphql::Result<Option<models::Absence>> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+        let status = if approved {
+            AbsenceStatus::Approved
+        } else {
+            AbsenceStatus::Rejected
+        };
+
+        absence::review_absence(absence_id, status, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+}
@@ -0,0 +1,114 @@
+use chrono::NaiveDate;
+
+use crate::{
+    download::create_billing_report_url,
+    graphql::guard::RoleGuard,
+    models::{self, EmployeeRole},
+    pdf::Locale,
+    service::billing,
+};
+
+#[derive(Default)]
+pub struct BillingQuery;
+
+#[async_graphql::Object]
+impl BillingQuery {
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Manager)")]
+    async fn billing_rates(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+    ) -> async_graphql::Result<Vec<models::BillingRate>> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+
+        billing::get_rates(pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+
+    /// Billable hours and amounts per task between from and to (both inclusive), only of the
+    /// projects of the customer if it is given.
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Manager)")]
+    async fn billing_summary(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        customer_id: Option<i32>,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> async_graphql::Result<models::BillingSummary> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+
+        if to < from {
+            return Err(async_graphql::Error::new(
+                "The end of the summary is before its start",
+            ));
+        }
+
+        match billing::get_billing_summary(customer_id, from, to, pool).await {
+            Err(sqlx::Error::RowNotFound) => Err(async_graphql::Error::new("Customer not found")),
+            summary => summary.map_err(async_graphql::Error::new_with_source),
+        }
+    }
+
+    /// Short-lived link to the pdf of the billing summary.
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Manager)")]
+    async fn billing_summary_pdf_url(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        customer_id: Option<i32>,
+        from: NaiveDate,
+        to: NaiveDate,
+        #[graphql(default)] locale: Locale,
+    ) -> async_graphql::Result<String> {
+        let employee_id = ctx.data::<i32>()?;
+
+        if to < from {
+            return Err(async_graphql::Error::new(
+                "The end of the summary is before its start",
+            ));
+        }
+
+        create_billing_report_url(employee_id, customer_id, from, to, locale)
+            .map_err(|_| async_graphql::Error::new("Download link could not be created"))
+    }
+}
+
+#[derive(Default)]
+pub struct BillingMutation;
+
+#[async_graphql::Object]
+impl BillingMutation {
+    /// Sets the hourly rate of the worktimes matching every given scope, without any scope it is
+    /// the default rate. A rate of the same scope is replaced.
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Manager)")]
+    async fn set_billing_rate(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        employee_id: Option<i32>,
+        task_id: Option<i32>,
+        work_type: Option<models::WorktimeType>,
+        rate_cents: i64,
+    ) -> async_graphql::Result<models::BillingRate> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+
+        if rate_cents < 0 {
+            return Err(async_graphql::Error::new("A rate must not be negative"));
+        }
+
+        billing::set_rate(employee_id, task_id, work_type, rate_cents, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Manager)")]
+    async fn delete_billing_rate(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        rate_id: i32,
+    ) -> async_graphql::Result<Option<models::BillingRate>> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+
+        billing::delete_rate(rate_id, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{
+    graphql::guard::RoleGuard,
+    models::{self, EmployeeRole},
+    pdf::branding::{decode_logo, parse_hex_color},
+    service::company,
+};
+
+// logos are printed small, there is no need for large files
+const MAX_LOGO_BYTES: usize = 1024 * 1024;
+// like the column of the company settings
+const MAX_NAME_LENGTH: usize = 64;
+
+fn validate_color(color: Option<&str>) -> async_graphql::Result<()> {
+    match color {
+        Some(color) if parse_hex_color(color).is_none() => Err(async_graphql::Error::new(format!(
+            "'{}' is not a hex color like '#a50d0d'",
+            color
+        ))),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Default)]
+pub struct CompanyQuery;
+
+#[async_graphql::Object]
+impl CompanyQuery {
+    async fn company_settings(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+    ) -> async_graphql::Result<models::CompanySettings> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+
+        company::get_company_settings(pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+}
+
+#[derive(Default)]
+pub struct CompanyMutation;
+
+#[async_graphql::Object]
+impl CompanyMutation {
+    /// Sets the name, address and brand colors printed on every report, null colors reset them
+    /// to the default.
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Admin)")]
+    async fn update_company_settings(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        name: String,
+        address: Option<String>,
+        header_color: Option<String>,
+        accent_color: Option<String>,
+    ) -> async_graphql::Result<models::CompanySettings> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+
+        if name.trim().is_empty() {
+            return Err(async_graphql::Error::new("The company name is empty"));
+        }
+        if name.chars().count() > MAX_NAME_LENGTH {
+            return Err(async_graphql::Error::new(format!(
+                "The company name is longer than {} characters",
+                MAX_NAME_LENGTH
+            )));
+        }
+        validate_color(header_color.as_deref())?;
+        validate_color(accent_color.as_deref())?;
+
+        company::update_company_settings(
+            &name,
+            address.as_deref(),
+            header_color.as_deref(),
+            accent_color.as_deref(),
+            pool,
+        )
+        .await
+        .map_err(async_graphql::Error::new_with_source)
+    }
+
+    /// Sets the base64 encoded PNG or JPEG logo of the reports, null removes it.
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Admin)")]
+    async fn update_company_logo(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        logo: Option<String>,
+    ) -> async_graphql::Result<bool> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+
+        let logo = match logo {
+            None => None,
+            Some(logo) => {
+                let logo = base64::decode(logo)
+                    .map_err(|_| async_graphql::Error::new("The logo is not base64 encoded"))?;
+                if logo.len() > MAX_LOGO_BYTES {
+                    return Err(async_graphql::Error::new("The logo is larger than 1 MB"));
+                }
+                decode_logo(&logo).map_err(|error| async_graphql::Error::new(error.to_string()))?;
+                Some(logo)
+            }
+        };
+
+        company::set_company_logo(logo.as_deref(), pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)?;
+
+        Ok(true)
+    }
+}
@@ -1,6 +1,11 @@
 use crate::{
-    models,
-    service::employee::{get_employee, update_password},
+    download::public_url,
+    graphql::guard::RoleGuard,
+    models::{self, EmployeeRole},
+    service::employee::{
+        create_calendar_token, delete_calendar_token, get_employee, update_password,
+        update_personnel_number, update_timezone,
+    },
 };
 
 #[derive(Default)]
@@ -20,6 +25,71 @@ impl EmployeeMutation {
             .await
             .map_err(async_graphql::Error::new_with_source)
     }
+
+    /// Sets the IANA timezone (e.g. 'Europe/Berlin') used for days and months, null resets it
+    /// to the company timezone.
+    async fn update_timezone(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        timezone: Option<String>,
+    ) -> async_graphql::Result<models::Employee> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+        let employee_id = ctx.data::<i32>()?;
+
+        if let Some(timezone) = &timezone {
+            timezone.parse::<chrono_tz::Tz>().map_err(|_| {
+                async_graphql::Error::new(format!("'{}' is not a known timezone.", timezone))
+            })?;
+        }
+
+        update_timezone(employee_id, timezone, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+
+    /// Creates a secret link to the calendar feed of the own worktimes for calendar apps, an
+    /// earlier link stops working.
+    async fn create_calendar_url(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+    ) -> async_graphql::Result<String> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+        let employee_id = ctx.data::<i32>()?;
+
+        let token = create_calendar_token(employee_id, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)?;
+
+        Ok(public_url(&format!("/calendar/{}.ics", token)))
+    }
+
+    /// Disables the link to the calendar feed, returns false if there was none.
+    async fn delete_calendar_url(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+    ) -> async_graphql::Result<bool> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+        let employee_id = ctx.data::<i32>()?;
+
+        delete_calendar_token(employee_id, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+
+    /// Sets the number of an employee in the payroll system which is used in the DATEV export.
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Admin)")]
+    async fn update_personnel_number(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        employee_id: i32,
+        personnel_number: Option<String>,
+    ) -> async_graphql::Result<models::Employee> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+
+        update_personnel_number(&employee_id, personnel_number, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
 }
 
 #[derive(Default)]
@@ -0,0 +1,68 @@
+use chrono::NaiveDate;
+
+use crate::download::{create_datev_url, create_export_url};
+use crate::export::{may_export, ExportFormat, ExportStyle};
+use crate::graphql::guard::RoleGuard;
+use crate::models::EmployeeRole;
+use crate::pdf::validate_month;
+
+#[derive(Default)]
+pub struct ExportQuery;
+
+#[async_graphql::Object]
+impl ExportQuery {
+    /// Short-lived link to GET /exports/worktimes.{csv,xlsx} with the worktimes which started
+    /// between from and to (both inclusive). Managers may export other employees.
+    async fn worktime_export_url(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        from: NaiveDate,
+        to: NaiveDate,
+        #[graphql(default)] format: ExportFormat,
+        #[graphql(default)] style: ExportStyle,
+        employee_ids: Option<Vec<i32>>,
+    ) -> async_graphql::Result<String> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+        let employee_id = ctx.data::<i32>()?;
+
+        if to < from {
+            return Err(async_graphql::Error::new(
+                "The end of the export is before its start",
+            ));
+        }
+        let requested = employee_ids.clone().unwrap_or_else(|| vec![*employee_id]);
+        if !may_export(employee_id, &requested, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)?
+        {
+            return Err(async_graphql::Error::new(
+                "Only managers can export the worktimes of other employees",
+            ));
+        }
+
+        create_export_url(
+            employee_id,
+            employee_ids.as_deref(),
+            from,
+            to,
+            format,
+            style,
+        )
+        .map_err(|_| async_graphql::Error::new("Download link could not be created"))
+    }
+
+    /// Short-lived link to GET /exports/datev/{month}.csv with the hours of every employee per
+    /// wage type for the payroll in DATEV Lohn und Gehalt.
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Manager)")]
+    async fn datev_export_url(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        month: String,
+    ) -> async_graphql::Result<String> {
+        let employee_id = ctx.data::<i32>()?;
+        validate_month(&month).map_err(|error| async_graphql::Error::new(error.to_string()))?;
+
+        create_datev_url(employee_id, &month)
+            .map_err(|_| async_graphql::Error::new("Download link could not be created"))
+    }
+}
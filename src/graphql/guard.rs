@@ -0,0 +1,34 @@
+use async_graphql::{Context, Guard};
+
+use crate::{models::EmployeeRole, service::employee::get_role};
+
+/// Allows a field only for employees that have at least the given role.
+pub struct RoleGuard {
+    role: EmployeeRole,
+}
+
+impl RoleGuard {
+    pub fn new(role: EmployeeRole) -> Self {
+        Self { role }
+    }
+}
+
+impl Guard for RoleGuard {
+    async fn check(&self, ctx: &Context<'_>) -> async_graphql::Result<()> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+        let employee_id = ctx.data::<i32>()?;
+
+        let role = get_role(employee_id, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)?;
+
+        if role >= self.role {
+            Ok(())
+        } else {
+            Err(async_graphql::Error::new(format!(
+                "This action requires the role {:?}.",
+                self.role
+            )))
+        }
+    }
+}
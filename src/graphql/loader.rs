@@ -0,0 +1,216 @@
+use std::{collections::HashMap, sync::Arc};
+
+use async_graphql::dataloader::Loader;
+use sqlx::PgPool;
+
+use crate::{models, service};
+
+/// Batches the task lookups of nested resolvers like `Worktime.task` into one query.
+pub struct TaskLoader {
+    pool: PgPool,
+}
+
+impl TaskLoader {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+impl Loader<i32> for TaskLoader {
+    type Value = models::Task;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(&self, keys: &[i32]) -> Result<HashMap<i32, Self::Value>, Self::Error> {
+        Ok(service::task::get_tasks_by_ids(keys, &self.pool)
+            .await?
+            .into_iter()
+            .map(|task| (task.task_id, task))
+            .collect())
+    }
+}
+
+/// Batches the employee lookups of nested resolvers like `Worktime.employee` into one query.
+pub struct EmployeeLoader {
+    pool: PgPool,
+}
+
+impl EmployeeLoader {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+impl Loader<i32> for EmployeeLoader {
+    type Value = models::Employee;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(&self, keys: &[i32]) -> Result<HashMap<i32, Self::Value>, Self::Error> {
+        Ok(service::employee::get_employees_by_ids(keys, &self.pool)
+            .await?
+            .into_iter()
+            .map(|employee| (employee.employee_id, employee))
+            .collect())
+    }
+}
+
+/// Batches the project lookups of nested resolvers like `Task.project` into one query.
+pub struct ProjectLoader {
+    pool: PgPool,
+}
+
+impl ProjectLoader {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+impl Loader<i32> for ProjectLoader {
+    type Value = models::Project;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(&self, keys: &[i32]) -> Result<HashMap<i32, Self::Value>, Self::Error> {
+        Ok(service::project::get_projects_by_ids(keys, &self.pool)
+            .await?
+            .into_iter()
+            .map(|project| (project.project_id, project))
+            .collect())
+    }
+}
+
+/// Batches the customer lookups of nested resolvers like `Project.customer` into one query.
+pub struct CustomerLoader {
+    pool: PgPool,
+}
+
+impl CustomerLoader {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+impl Loader<i32> for CustomerLoader {
+    type Value = models::Customer;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(&self, keys: &[i32]) -> Result<HashMap<i32, Self::Value>, Self::Error> {
+        Ok(service::project::get_customers_by_ids(keys, &self.pool)
+            .await?
+            .into_iter()
+            .map(|customer| (customer.customer_id, customer))
+            .collect())
+    }
+}
+
+/// Batches the assignment lookups of `Task.assignments` into one query, tasks without
+/// assignments have no entry.
+pub struct TaskAssignmentLoader {
+    pool: PgPool,
+}
+
+impl TaskAssignmentLoader {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+impl Loader<i32> for TaskAssignmentLoader {
+    type Value = Vec<models::TaskAssignment>;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(&self, keys: &[i32]) -> Result<HashMap<i32, Self::Value>, Self::Error> {
+        let mut assignments: HashMap<i32, Self::Value> = HashMap::new();
+        for assignment in
+            service::task_assignment::get_assignments_of_tasks(keys, &self.pool).await?
+        {
+            assignments
+                .entry(assignment.task_id)
+                .or_default()
+                .push(assignment);
+        }
+        Ok(assignments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_graphql::Request;
+
+    use crate::{
+        events::{TaskBudgetAlerts, TimerEvents},
+        graphql::create_schema,
+    };
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/task.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+        "../../fixtures/worktime.sql"
+    ))]
+    async fn test_nested_task_and_employee(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        let schema = create_schema(pool, TimerEvents::new(), TaskBudgetAlerts::new());
+
+        let response = schema
+            .execute(
+                Request::new(
+                    "{ timersInBoundary(lowerBound: \"2024-01-03T00:00:00Z\", upperBound: \"2024-01-04T00:00:00Z\") { task { taskId } employee { employeeId } } }",
+                )
+                .data(1),
+            )
+            .await;
+
+        assert!(response.errors.is_empty());
+        assert_eq!(
+            response.data.into_json().unwrap(),
+            serde_json::json!({ "timersInBoundary": [
+                { "task": { "taskId": 2 }, "employee": { "employeeId": 1 } },
+                { "task": { "taskId": 3 }, "employee": { "employeeId": 1 } },
+            ] })
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("../../fixtures/truncate.sql", "../../fixtures/task.sql"))]
+    async fn test_nested_project_and_customer(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        sqlx::query!("INSERT INTO customer (customer_id, name) VALUES (1, 'Cloud City')")
+            .execute(&pool)
+            .await?;
+        sqlx::query!(
+            "INSERT INTO project (project_id, customer_id, name) VALUES (1, 1, 'Repairs')"
+        )
+        .execute(&pool)
+        .await?;
+        sqlx::query!("UPDATE task SET project_id = 1 WHERE task_id IN (2, 3)")
+            .execute(&pool)
+            .await?;
+        let schema = create_schema(pool, TimerEvents::new(), TaskBudgetAlerts::new());
+
+        let response = schema
+            .execute(
+                Request::new("{ tasks { taskId project { name customer { name } } } }").data(1),
+            )
+            .await;
+
+        assert!(response.errors.is_empty());
+        let tasks = response.data.into_json().unwrap()["tasks"].clone();
+        let repairs =
+            serde_json::json!({ "name": "Repairs", "customer": { "name": "Cloud City" } });
+        assert!(tasks
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|task| task["project"].is_null() || task["project"] == repairs));
+        assert_eq!(
+            tasks
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter(|task| task["project"] == repairs)
+                .count(),
+            2
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use crate::{models, service::notification};
+
+#[derive(Default)]
+pub struct NotificationQuery;
+
+#[async_graphql::Object]
+impl NotificationQuery {
+    async fn notifications(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+    ) -> async_graphql::Result<Vec<models::Notification>> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+        let employee_id = ctx.data::<i32>()?;
+
+        notification::get_notifications(employee_id, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+}
+
+#[derive(Default)]
+pub struct NotificationMutation;
+
+#[async_graphql::Object]
+impl NotificationMutation {
+    async fn mark_notification_read(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        notification_id: i32,
+    ) -> async_graphql::Result<Option<models::Notification>> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+        let employee_id = ctx.data::<i32>()?;
+
+        notification::mark_notification_read(notification_id, employee_id, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+}
@@ -1,20 +1,114 @@
-use crate::pdf::{generate_pdf, HeaderColor};
+use crate::download::create_report_url;
+use crate::export::may_export;
+use crate::models::{ArchivedReport, ReportVerification};
+use crate::pdf::{generate_pdf, verification, HeaderColor, Locale, ReportPeriod};
+use crate::service::archived_report;
+
+// the deprecated month string or the typed period, exactly one of them has to be given
+fn resolve_period(
+    month: Option<String>,
+    period: Option<ReportPeriod>,
+) -> async_graphql::Result<ReportPeriod> {
+    let period = match (month, period) {
+        (Some(month), None) => month
+            .parse::<ReportPeriod>()
+            .ok()
+            .filter(ReportPeriod::is_month)
+            .ok_or_else(|| async_graphql::Error::new("given month has the wrong format"))?,
+        (None, Some(period)) => period,
+        _ => {
+            return Err(async_graphql::Error::new(
+                "Either month or period has to be given",
+            ))
+        }
+    };
+    period
+        .bounds()
+        .map_err(|error| async_graphql::Error::new(error.to_string()))?;
+
+    Ok(period)
+}
 
 #[derive(Default)]
 pub struct PDFQuery;
 
 #[async_graphql::Object]
 impl PDFQuery {
+    #[graphql(deprecation = "Download the pdf from the url of pdfDownloadUrl instead.")]
     async fn generate_pdf(
         &self,
         ctx: &async_graphql::Context<'_>,
         header_color: HeaderColor,
-        month: String,
+        #[graphql(desc = "Deprecated, use period instead.")] month: Option<String>,
+        period: Option<ReportPeriod>,
+        #[graphql(default)] locale: Locale,
     ) -> async_graphql::Result<String> {
         let pool = ctx.data::<sqlx::Pool<sqlx::Postgres>>()?;
         let employee_id = ctx.data::<i32>()?;
+        let period = resolve_period(month, period)?;
+
+        generate_pdf(period, header_color, locale, pool, employee_id)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+
+    /// Short-lived link to GET /reports/{period}.pdf which works without an authorization
+    /// header. The period is a month, an ISO week, a year or a range of at most 366 days.
+    async fn pdf_download_url(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        header_color: HeaderColor,
+        #[graphql(desc = "Deprecated, use period instead.")] month: Option<String>,
+        period: Option<ReportPeriod>,
+        #[graphql(default)] locale: Locale,
+    ) -> async_graphql::Result<String> {
+        let employee_id = ctx.data::<i32>()?;
+        let period = resolve_period(month, period)?;
+
+        create_report_url(employee_id, &period, header_color, locale)
+            .map_err(|_| async_graphql::Error::new("Download link could not be created"))
+    }
+
+    /// Reports generated earlier, the newest first, which can be downloaded again exactly as they
+    /// were handed out. Managers may list the reports of other employees.
+    async fn reports(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        employee_id: Option<i32>,
+        period: Option<ReportPeriod>,
+    ) -> async_graphql::Result<Vec<ArchivedReport>> {
+        let pool = ctx.data::<sqlx::Pool<sqlx::Postgres>>()?;
+        let own_id = ctx.data::<i32>()?;
+        let employee_id = employee_id.unwrap_or(*own_id);
+
+        if !may_export(own_id, &[employee_id], pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)?
+        {
+            return Err(async_graphql::Error::new(
+                "Only managers can see the reports of other employees",
+            ));
+        }
+
+        archived_report::get_archived_reports(
+            employee_id,
+            period.map(|period| period.to_string()).as_deref(),
+            pool,
+        )
+        .await
+        .map_err(async_graphql::Error::new_with_source)
+    }
+
+    /// Checks the checksum printed on a report against the worktimes as they are stored now,
+    /// null if no report with the checksum was generated.
+    async fn verify_report(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        hash: String,
+    ) -> async_graphql::Result<Option<ReportVerification>> {
+        let pool = ctx.data::<sqlx::Pool<sqlx::Postgres>>()?;
 
-        generate_pdf(month, header_color, pool, employee_id)
+        verification::verify_report(&hash, pool)
             .await
             .map_err(async_graphql::Error::new_with_source)
     }
@@ -0,0 +1,258 @@
+use chrono::NaiveDate;
+
+use crate::{
+    download::{create_report_csv_url, create_task_report_url, create_team_report_url},
+    export::{may_export, ExportStyle, ReportFormat},
+    graphql::guard::RoleGuard,
+    models::{self, EmployeeRole},
+    pdf::{period::MonthPeriod, Locale, ReportPeriod},
+    service::{report, report_delivery, team},
+};
+
+// an unknown team is an error like the 404 of the pdf download
+async fn check_team_report_access(
+    employee_id: &i32,
+    team_id: Option<i32>,
+    pool: &sqlx::PgPool,
+) -> async_graphql::Result<()> {
+    let team = match team_id {
+        Some(team_id) => match team::get_team(&team_id, pool).await {
+            Ok(team) => Some(team),
+            Err(sqlx::Error::RowNotFound) => {
+                return Err(async_graphql::Error::new("The team does not exist"))
+            }
+            Err(error) => return Err(async_graphql::Error::new_with_source(error)),
+        },
+        None => None,
+    };
+
+    if team::may_see_report(employee_id, team.as_ref(), pool)
+        .await
+        .map_err(async_graphql::Error::new_with_source)?
+    {
+        Ok(())
+    } else {
+        Err(async_graphql::Error::new(
+            "Only admins and the manager of the team can see its report",
+        ))
+    }
+}
+
+#[derive(Default)]
+pub struct ReportQuery;
+
+#[async_graphql::Object]
+impl ReportQuery {
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Manager)")]
+    async fn teams(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+    ) -> async_graphql::Result<Vec<models::Team>> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+
+        team::get_teams(pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+
+    /// Work, ride and break time per day and task of a month, an ISO week, a year or a range of
+    /// at most 366 days, which is what the pdf report shows. Managers may query other employees.
+    async fn month_report(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        period: ReportPeriod,
+        employee_id: Option<i32>,
+    ) -> async_graphql::Result<models::MonthReport> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+        let own_id = ctx.data::<i32>()?;
+        let employee_id = employee_id.unwrap_or(*own_id);
+
+        let (from, to) = period
+            .bounds()
+            .map_err(|error| async_graphql::Error::new(error.to_string()))?;
+        if !may_export(own_id, &[employee_id], pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)?
+        {
+            return Err(async_graphql::Error::new(
+                "Only managers can see the reports of other employees",
+            ));
+        }
+
+        report::get_month_report(employee_id, from, to, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+
+    /// Short-lived link to GET /reports/{period}.csv with the totals of every day of the own
+    /// report, like the pdf from pdfDownloadUrl.
+    async fn month_report_csv_url(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        period: ReportPeriod,
+        #[graphql(default)] style: ExportStyle,
+    ) -> async_graphql::Result<String> {
+        let employee_id = ctx.data::<i32>()?;
+
+        period
+            .bounds()
+            .map_err(|error| async_graphql::Error::new(error.to_string()))?;
+
+        create_report_csv_url(employee_id, &period, style)
+            .map_err(|_| async_graphql::Error::new("Download link could not be created"))
+    }
+
+    /// Every attempt of the scheduler to mail the reports of the month, failed ones are tried
+    /// again on the next run of the delivery day.
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Manager)")]
+    async fn report_deliveries(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        month: MonthPeriod,
+    ) -> async_graphql::Result<Vec<models::ReportDelivery>> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+
+        report_delivery::get_deliveries(&ReportPeriod::Month(month).to_string(), pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+
+    /// Totals of work, ride, break and absence time, overtime and hours per task of every member
+    /// of the team between from and to (both inclusive), without a team of the whole company.
+    /// Managers see the teams they lead, admins every team and the company.
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Manager)")]
+    async fn team_report(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        team_id: Option<i32>,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> async_graphql::Result<models::TeamReport> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+        let employee_id = ctx.data::<i32>()?;
+
+        if to < from {
+            return Err(async_graphql::Error::new(
+                "The end of the report is before its start",
+            ));
+        }
+        check_team_report_access(employee_id, team_id, pool).await?;
+
+        let employee_ids = team::get_member_ids(team_id, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)?;
+
+        report::get_team_report(&employee_ids, from, to, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+
+    /// Short-lived link to the pdf of the team report.
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Manager)")]
+    async fn team_report_pdf_url(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        team_id: Option<i32>,
+        from: NaiveDate,
+        to: NaiveDate,
+        #[graphql(default)] locale: Locale,
+    ) -> async_graphql::Result<String> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+        let employee_id = ctx.data::<i32>()?;
+
+        if to < from {
+            return Err(async_graphql::Error::new(
+                "The end of the report is before its start",
+            ));
+        }
+        check_team_report_access(employee_id, team_id, pool).await?;
+
+        create_team_report_url(employee_id, team_id, from, to, locale)
+            .map_err(|_| async_graphql::Error::new("Download link could not be created"))
+    }
+
+    /// Hours booked on the task per employee and work type between from and to (both
+    /// inclusive).
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Manager)")]
+    async fn task_report(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        task_id: i32,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> async_graphql::Result<models::TaskReport> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+
+        if to < from {
+            return Err(async_graphql::Error::new(
+                "The end of the report is before its start",
+            ));
+        }
+
+        report::get_task_report(task_id, from, to, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+
+    /// Short-lived link to the task report as pdf or csv.
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Manager)")]
+    #[allow(clippy::too_many_arguments)]
+    async fn task_report_url(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        task_id: i32,
+        from: NaiveDate,
+        to: NaiveDate,
+        #[graphql(default)] format: ReportFormat,
+        #[graphql(default)] style: ExportStyle,
+        #[graphql(default)] locale: Locale,
+    ) -> async_graphql::Result<String> {
+        let employee_id = ctx.data::<i32>()?;
+
+        if to < from {
+            return Err(async_graphql::Error::new(
+                "The end of the report is before its start",
+            ));
+        }
+
+        create_task_report_url(employee_id, task_id, from, to, format, style, locale)
+            .map_err(|_| async_graphql::Error::new("Download link could not be created"))
+    }
+}
+
+#[derive(Default)]
+pub struct TeamMutation;
+
+#[async_graphql::Object]
+impl TeamMutation {
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Admin)")]
+    async fn create_team(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        name: String,
+        manager_id: Option<i32>,
+    ) -> async_graphql::Result<models::Team> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+
+        team::create_team(&name, manager_id, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+
+    /// Moves the employee into the team, null removes it from its team.
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Admin)")]
+    async fn set_employee_team(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        employee_id: i32,
+        team_id: Option<i32>,
+    ) -> async_graphql::Result<bool> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+
+        team::set_employee_team(&employee_id, team_id, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)?;
+
+        Ok(true)
+    }
+}
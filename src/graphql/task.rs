@@ -1,23 +1,130 @@
-use async_graphql::Object;
+use async_graphql::{
+    connection::{query, Connection, Edge, OpaqueCursor},
+    futures_util::{future, Stream, StreamExt},
+    Object,
+};
+use tokio_stream::wrappers::BroadcastStream;
 
-use crate::{models, service};
+use crate::{
+    events::TaskBudgetAlerts,
+    graphql::guard::RoleGuard,
+    models::{self, EmployeeRole},
+    service::{
+        self,
+        pagination::{Cursor, PageRequest},
+    },
+};
+
+fn validate_name(name: &str) -> async_graphql::Result<()> {
+    if name.trim().is_empty() {
+        return Err(async_graphql::Error::new("The name must not be empty"));
+    }
+    Ok(())
+}
+
+fn validate_budget(
+    budget_hours: Option<f64>,
+    budget_cents: Option<i64>,
+) -> async_graphql::Result<()> {
+    if budget_hours.is_some_and(|hours| !hours.is_finite() || hours < 0.0)
+        || budget_cents.is_some_and(|cents| cents < 0)
+    {
+        return Err(async_graphql::Error::new("A budget must not be negative"));
+    }
+    Ok(())
+}
+
+// archived projects take no new tasks
+async fn ensure_project_active(
+    project_id: Option<i32>,
+    pool: &sqlx::PgPool,
+) -> async_graphql::Result<()> {
+    if let Some(project_id) = project_id {
+        let project = service::project::get_project(project_id, pool)
+            .await?
+            .ok_or_else(|| async_graphql::Error::new("Project not found"))?;
+        if project.status == models::ArchiveStatus::Archived {
+            return Err(async_graphql::Error::new("The project is archived"));
+        }
+    }
+    Ok(())
+}
+
+async fn ensure_customer_exists(
+    customer_id: i32,
+    pool: &sqlx::PgPool,
+) -> async_graphql::Result<()> {
+    service::project::get_customer(customer_id, pool)
+        .await?
+        .ok_or_else(|| async_graphql::Error::new("Customer not found"))?;
+    Ok(())
+}
 
 #[derive(Default)]
 pub struct TaskQuery;
 
 #[Object]
 impl TaskQuery {
+    /// Archived tasks are left out unless they are asked for.
     async fn tasks(
         &self,
         ctx: &async_graphql::Context<'_>,
+        #[graphql(default)] include_archived: bool,
     ) -> async_graphql::Result<Vec<models::Task>> {
         let pool = ctx.data::<sqlx::PgPool>()?;
 
-        service::task::get_tasks(pool)
+        service::task::get_tasks(include_archived, pool)
             .await
             .map_err(async_graphql::Error::new_with_source)
     }
 
+    #[allow(clippy::too_many_arguments)]
+    async fn tasks_connection(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+        filter: Option<models::TaskFilter>,
+        #[graphql(default)] sort_by: models::TaskSortField,
+        #[graphql(default)] direction: models::SortDirection,
+    ) -> async_graphql::Result<Connection<OpaqueCursor<Cursor>, models::Task>> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+        let filter = filter.unwrap_or_default();
+
+        query(
+            after,
+            before,
+            first,
+            last,
+            |after: Option<OpaqueCursor<Cursor>>,
+             before: Option<OpaqueCursor<Cursor>>,
+             first,
+             last| async move {
+                let page_request = PageRequest {
+                    after: after.map(|cursor| cursor.0),
+                    before: before.map(|cursor| cursor.0),
+                    first,
+                    last,
+                };
+                let page =
+                    service::task::get_tasks_page(&filter, sort_by, direction, &page_request, pool)
+                        .await
+                        .map_err(async_graphql::Error::new_with_source)?;
+
+                let mut connection = Connection::new(page.has_previous_page, page.has_next_page);
+                connection.edges.extend(
+                    page.items
+                        .into_iter()
+                        .map(|(cursor, task)| Edge::new(OpaqueCursor(cursor), task)),
+                );
+                Ok::<_, async_graphql::Error>(connection)
+            },
+        )
+        .await
+    }
+
     async fn task_by_id(
         &self,
         ctx: &async_graphql::Context<'_>,
@@ -29,6 +136,78 @@ impl TaskQuery {
             .await
             .map_err(async_graphql::Error::new_with_source)
     }
+
+    /// The active tasks the authenticated employee may book, assigned to the employee or its team
+    /// and, unless `assignedOnly` is set, the ones which are not assigned to anybody.
+    async fn my_tasks(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        #[graphql(default)] assigned_only: bool,
+    ) -> async_graphql::Result<Vec<models::Task>> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+        let employee_id = ctx.data::<i32>()?;
+
+        let assigned_only = assigned_only || !service::task_assignment::unassigned_tasks_bookable();
+
+        service::task_assignment::get_tasks_of_employee(*employee_id, assigned_only, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+
+    async fn customers(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        #[graphql(default)] include_archived: bool,
+    ) -> async_graphql::Result<Vec<models::Customer>> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+
+        service::project::get_customers(include_archived, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+
+    /// The projects of the customer or of all customers.
+    async fn projects(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        customer_id: Option<i32>,
+        #[graphql(default)] include_archived: bool,
+    ) -> async_graphql::Result<Vec<models::Project>> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+
+        service::project::get_projects(customer_id, include_archived, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+
+    async fn project(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        project_id: i32,
+    ) -> async_graphql::Result<Option<models::Project>> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+
+        service::project::get_project(project_id, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+
+    /// Booked time against the estimate of every estimated task, only of the given task or the
+    /// tasks of the given project if they are set. Only the estimated hours trigger budget
+    /// alerts, the budget in cents is just compared here.
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Manager)")]
+    async fn task_budgets(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        task_id: Option<i32>,
+        project_id: Option<i32>,
+    ) -> async_graphql::Result<Vec<models::TaskBudget>> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+
+        service::task_budget::get_task_budgets(task_id, project_id, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
 }
 
 #[derive(Default)]
@@ -36,18 +215,22 @@ pub struct TaskMutation;
 
 #[Object]
 impl TaskMutation {
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Manager)")]
     async fn create_task(
         &self,
         ctx: &async_graphql::Context<'_>,
         task_description: String,
+        project_id: Option<i32>,
     ) -> async_graphql::Result<models::Task> {
         let pool = ctx.data::<sqlx::PgPool>()?;
+        ensure_project_active(project_id, pool).await?;
 
-        service::task::create_task(&task_description, pool)
+        service::task::create_task(&task_description, project_id, pool)
             .await
             .map_err(async_graphql::Error::new_with_source)
     }
 
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Manager)")]
     async fn update_task(
         &self,
         ctx: &async_graphql::Context<'_>,
@@ -61,6 +244,39 @@ impl TaskMutation {
             .map_err(async_graphql::Error::new_with_source)
     }
 
+    /// Moves the task into the project, without a project it does not belong to one anymore.
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Manager)")]
+    async fn set_task_project(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        task_id: i32,
+        project_id: Option<i32>,
+    ) -> async_graphql::Result<Option<models::Task>> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+        ensure_project_active(project_id, pool).await?;
+
+        service::task::set_task_project(task_id, project_id, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+
+    /// Archived tasks keep their worktimes, but no timers can be started on them anymore.
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Manager)")]
+    async fn set_task_status(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        task_id: i32,
+        status: models::ArchiveStatus,
+    ) -> async_graphql::Result<Option<models::Task>> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+
+        service::task::set_task_status(task_id, status, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+
+    /// Only tasks without worktimes can be deleted, the others have to be archived.
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Manager)")]
     async fn delete_task(
         &self,
         ctx: &async_graphql::Context<'_>,
@@ -68,8 +284,250 @@ impl TaskMutation {
     ) -> async_graphql::Result<Option<models::Task>> {
         let pool = ctx.data::<sqlx::PgPool>()?;
 
+        if service::task::has_worktimes(task_id, pool).await? {
+            return Err(async_graphql::Error::new(
+                "Time was booked on the task, archive it with setTaskStatus instead",
+            ));
+        }
+
         service::task::delete_task(task_id, pool)
             .await
             .map_err(async_graphql::Error::new_with_source)
     }
+
+    /// Assigns the task to an employee or a whole team, exactly one of them has to be given. Once a
+    /// task is assigned, only its assignees can start timers on it. Returns every assignment of
+    /// the task.
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Manager)")]
+    async fn assign_task(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        task_id: i32,
+        employee_id: Option<i32>,
+        team_id: Option<i32>,
+    ) -> async_graphql::Result<Vec<models::TaskAssignment>> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+        if employee_id.is_some() == team_id.is_some() {
+            return Err(async_graphql::Error::new(
+                "Either employeeId or teamId has to be given",
+            ));
+        }
+        service::task::get_task_by_id(task_id, pool)
+            .await?
+            .ok_or_else(|| async_graphql::Error::new("Task not found"))?;
+
+        service::task_assignment::assign_task(task_id, employee_id, team_id, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+
+    /// Removes the assignment, a task without any assignment can be booked by everyone.
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Manager)")]
+    async fn unassign_task(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        assignment_id: i32,
+    ) -> async_graphql::Result<Option<models::TaskAssignment>> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+
+        service::task_assignment::unassign_task(assignment_id, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+
+    /// Sets the planned effort of the task, without an estimate no budget alerts are sent. A
+    /// changed estimate resets the alerts, they are sent again once a booking passes the
+    /// thresholds of the new estimate. The budget is never alerted.
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Manager)")]
+    async fn set_task_estimate(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        task_id: i32,
+        estimated_hours: Option<f64>,
+        budget_cents: Option<i64>,
+    ) -> async_graphql::Result<Option<models::Task>> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+        validate_budget(estimated_hours, budget_cents)?;
+
+        service::task_budget::set_task_estimate(task_id, estimated_hours, budget_cents, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Manager)")]
+    async fn create_customer(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        name: String,
+    ) -> async_graphql::Result<models::Customer> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+        validate_name(&name)?;
+
+        service::project::create_customer(name.trim(), pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+
+    /// Archived customers are hidden from `customers` unless they are asked for.
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Manager)")]
+    async fn update_customer(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        customer_id: i32,
+        name: String,
+        status: models::ArchiveStatus,
+    ) -> async_graphql::Result<Option<models::Customer>> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+        validate_name(&name)?;
+
+        service::project::update_customer(customer_id, name.trim(), status, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Manager)")]
+    async fn create_project(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        customer_id: i32,
+        name: String,
+        budget_hours: Option<f64>,
+        budget_cents: Option<i64>,
+    ) -> async_graphql::Result<models::Project> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+        validate_name(&name)?;
+        validate_budget(budget_hours, budget_cents)?;
+        ensure_customer_exists(customer_id, pool).await?;
+
+        service::project::create_project(customer_id, name.trim(), budget_hours, budget_cents, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+
+    /// Replaces every field of the project, budgets which are not given are removed.
+    #[allow(clippy::too_many_arguments)]
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Manager)")]
+    async fn update_project(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        project_id: i32,
+        customer_id: i32,
+        name: String,
+        status: models::ArchiveStatus,
+        budget_hours: Option<f64>,
+        budget_cents: Option<i64>,
+    ) -> async_graphql::Result<Option<models::Project>> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+        validate_name(&name)?;
+        validate_budget(budget_hours, budget_cents)?;
+        ensure_customer_exists(customer_id, pool).await?;
+
+        service::project::update_project(
+            &models::Project {
+                project_id,
+                customer_id,
+                name: name.trim().to_string(),
+                status,
+                budget_hours,
+                budget_cents,
+            },
+            pool,
+        )
+        .await
+        .map_err(async_graphql::Error::new_with_source)
+    }
+}
+
+#[derive(Default)]
+pub struct TaskSubscription;
+
+#[async_graphql::Subscription]
+impl TaskSubscription {
+    /// Sent once when the booked time of a task passes a threshold of its estimate.
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Manager)")]
+    async fn task_budget_alerts(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+    ) -> async_graphql::Result<impl Stream<Item = models::TaskBudgetAlert>> {
+        let receiver = ctx.data::<TaskBudgetAlerts>()?.subscribe();
+
+        // lagged receivers skip the missed alerts
+        Ok(BroadcastStream::new(receiver).filter_map(|alert| future::ready(alert.ok())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_graphql::Request;
+
+    use crate::events::{TaskBudgetAlerts, TimerEvents};
+    use crate::graphql::create_schema;
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/task.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql"
+    ))]
+    async fn test_archived_project_takes_no_tasks(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        sqlx::query!("INSERT INTO customer (customer_id, name) VALUES (1, 'Cloud City')")
+            .execute(&pool)
+            .await?;
+        sqlx::query!(
+            "INSERT INTO project (project_id, customer_id, name, status) VALUES (1, 1, 'Repairs', 'archived')"
+        )
+        .execute(&pool)
+        .await?;
+        let schema = create_schema(pool, TimerEvents::new(), TaskBudgetAlerts::new());
+
+        for mutation in [
+            "mutation { createTask(taskDescription: \"Doors\", projectId: 1) { taskId } }",
+            "mutation { setTaskProject(taskId: 1, projectId: 1) { taskId } }",
+        ] {
+            let response = schema.execute(Request::new(mutation).data(3)).await;
+            assert_eq!(response.errors[0].message, "The project is archived");
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/task.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql"
+    ))]
+    async fn test_employee_can_not_archive_tasks(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        let schema = create_schema(pool.clone(), TimerEvents::new(), TaskBudgetAlerts::new());
+
+        for mutation in [
+            "mutation { setTaskStatus(taskId: 1, status: ARCHIVED) { taskId } }",
+            "mutation { deleteTask(taskId: 1) { taskId } }",
+        ] {
+            // a plain employee is rejected
+            let response = schema.execute(Request::new(mutation).data(1)).await;
+            assert_eq!(
+                response.errors[0].message,
+                "This action requires the role Manager."
+            );
+        }
+
+        // and the task is untouched
+        let status: String =
+            sqlx::query_scalar!("SELECT status::TEXT as \"status!\" FROM task WHERE task_id = 1")
+                .fetch_one(&pool)
+                .await?;
+        assert_eq!(status, "active");
+
+        // while the manager archives it
+        let response = schema
+            .execute(
+                Request::new("mutation { setTaskStatus(taskId: 1, status: ARCHIVED) { taskId } }")
+                    .data(3),
+            )
+            .await;
+        assert!(response.errors.is_empty());
+
+        Ok(())
+    }
 }
@@ -1,4 +1,21 @@
-use crate::{models, service::worktime};
+use async_graphql::{
+    connection::{query, Connection, Edge, OpaqueCursor},
+    futures_util::{future, stream, Stream, StreamExt},
+};
+use tokio_stream::wrappers::BroadcastStream;
+
+use crate::{
+    events::TimerEvents,
+    models::{self, EmployeeRole, TimerEventKind},
+    service::{
+        employee,
+        pagination::{Cursor, PageRequest},
+        task_assignment, worktime,
+    },
+    time_utils,
+};
+
+use super::guard::RoleGuard;
 
 #[derive(Default)]
 pub struct Timer;
@@ -17,6 +34,60 @@ impl Timer {
             .map_err(async_graphql::Error::new_with_source)
     }
 
+    #[allow(clippy::too_many_arguments)]
+    async fn timers_connection(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+        filter: Option<models::WorktimeFilter>,
+        #[graphql(default)] sort_by: models::WorktimeSortField,
+        #[graphql(default)] direction: models::SortDirection,
+    ) -> async_graphql::Result<Connection<OpaqueCursor<Cursor>, models::Worktime>> {
+        let pool = ctx.data::<sqlx::Pool<sqlx::Postgres>>()?;
+        let employee_id = ctx.data::<i32>()?;
+        let filter = filter.unwrap_or_default();
+
+        query(
+            after,
+            before,
+            first,
+            last,
+            |after: Option<OpaqueCursor<Cursor>>,
+             before: Option<OpaqueCursor<Cursor>>,
+             first,
+             last| async move {
+                let page_request = PageRequest {
+                    after: after.map(|cursor| cursor.0),
+                    before: before.map(|cursor| cursor.0),
+                    first,
+                    last,
+                };
+                let page = worktime::get_timers_page(
+                    employee_id,
+                    &filter,
+                    sort_by,
+                    direction,
+                    &page_request,
+                    pool,
+                )
+                .await
+                .map_err(async_graphql::Error::new_with_source)?;
+
+                let mut connection = Connection::new(page.has_previous_page, page.has_next_page);
+                connection.edges.extend(
+                    page.items
+                        .into_iter()
+                        .map(|(cursor, worktime)| Edge::new(OpaqueCursor(cursor), worktime)),
+                );
+                Ok::<_, async_graphql::Error>(connection)
+            },
+        )
+        .await
+    }
+
     async fn timers_in_boundary(
         &self,
         ctx: &async_graphql::Context<'_>,
@@ -38,11 +109,12 @@ impl Timer {
         let pool = ctx.data::<sqlx::Pool<sqlx::Postgres>>()?;
         let employee_id = ctx.data::<i32>()?;
 
-        let now = chrono::Utc::now().fixed_offset();
-
-        let lower_bound = now.with_time(chrono::NaiveTime::MIN).single().unwrap();
+        let timezone = employee::get_timezone(employee_id, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)?;
+        let today = time_utils::local_date(chrono::Utc::now(), &timezone);
 
-        let upper_bound = lower_bound.checked_add_days(chrono::Days::new(1)).unwrap();
+        let (lower_bound, upper_bound) = time_utils::day_bounds(today, &timezone);
 
         worktime::get_timers_in_boundary(employee_id, lower_bound, upper_bound, pool)
             .await
@@ -58,13 +130,15 @@ impl Timer {
         let pool = ctx.data::<sqlx::Pool<sqlx::Postgres>>()?;
         let employee_id = ctx.data::<i32>()?;
 
-        let now = chrono::Utc::now().fixed_offset().with_day(1).unwrap();
+        let timezone = employee::get_timezone(employee_id, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)?;
+        let today = time_utils::local_date(chrono::Utc::now(), &timezone);
 
-        let lower_bound = now.with_time(chrono::NaiveTime::MIN).single().unwrap();
-
-        let upper_bound = lower_bound
-            .checked_add_months(chrono::Months::new(1))
-            .unwrap();
+        let (lower_bound, upper_bound) =
+            time_utils::month_bounds(today.year(), today.month(), &timezone).ok_or_else(|| {
+                async_graphql::Error::new("Cannot calculate the bounds of the current month.")
+            })?;
 
         worktime::get_timers_in_boundary(employee_id, lower_bound, upper_bound, pool)
             .await
@@ -85,10 +159,23 @@ impl TimerMutation {
     ) -> async_graphql::Result<models::Worktime> {
         let pool = ctx.data::<sqlx::PgPool>()?;
         let employee_id = ctx.data::<i32>()?;
+        let timer_events = ctx.data::<TimerEvents>()?;
 
-        worktime::start_timer(employee_id, task_id, worktype, pool)
-            .await
-            .map_err(async_graphql::Error::new_with_source)
+        let worktime = worktime::start_timer(
+            employee_id,
+            task_id,
+            worktype,
+            task_assignment::unassigned_tasks_bookable(),
+            pool,
+        )
+        .await
+        .map_err(async_graphql::Error::new_with_source)?
+        .ok_or_else(|| {
+            async_graphql::Error::new("The task is archived, assigned to others or does not exist")
+        })?;
+
+        timer_events.publish(TimerEventKind::Started, worktime.clone());
+        Ok(worktime)
     }
 
     async fn stop_timer(
@@ -97,12 +184,17 @@ impl TimerMutation {
         worktime_id: i32,
     ) -> async_graphql::Result<models::Worktime> {
         let pool = ctx.data::<sqlx::PgPool>()?;
+        let timer_events = ctx.data::<TimerEvents>()?;
 
-        worktime::stop_timer(worktime_id, pool)
+        let worktime = worktime::stop_timer(worktime_id, pool)
             .await
-            .map_err(async_graphql::Error::new_with_source)
+            .map_err(async_graphql::Error::new_with_source)?;
+
+        timer_events.publish(TimerEventKind::Stopped, worktime.clone());
+        Ok(worktime)
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn update_timer(
         &self,
         ctx: &async_graphql::Context<'_>,
@@ -111,11 +203,243 @@ impl TimerMutation {
         start_time: Option<chrono::DateTime<chrono::FixedOffset>>,
         end_time: Option<chrono::DateTime<chrono::FixedOffset>>,
         worktype: Option<models::WorktimeType>,
+        comment: Option<String>,
+        billable: Option<bool>,
     ) -> async_graphql::Result<models::Worktime> {
         let pool = ctx.data::<sqlx::PgPool>()?;
+        let timer_events = ctx.data::<TimerEvents>()?;
+
+        // whether the time is charged to the customer is up to the managers
+        if billable.is_some()
+            && employee::get_role(ctx.data::<i32>()?, pool).await? < EmployeeRole::Manager
+        {
+            return Err(async_graphql::Error::new(
+                "Only managers can change whether a worktime is billable",
+            ));
+        }
+
+        // moving the time to another task follows the rules of starting a timer on it
+        if let Some(task_id) = task_id {
+            let timer = worktime::get_timer(worktime_id, pool)
+                .await?
+                .ok_or_else(|| async_graphql::Error::new("Worktime not found"))?;
+            if !task_assignment::may_book_task(
+                timer.employee_id,
+                task_id,
+                task_assignment::unassigned_tasks_bookable(),
+                pool,
+            )
+            .await?
+            {
+                return Err(async_graphql::Error::new(
+                    "The task is archived, assigned to others or does not exist",
+                ));
+            }
+        }
+
+        let worktime = worktime::update_timer(
+            worktime_id,
+            task_id,
+            start_time,
+            end_time,
+            worktype,
+            comment,
+            billable,
+            pool,
+        )
+        .await
+        .map_err(async_graphql::Error::new_with_source)?;
 
-        worktime::update_timer(worktime_id, task_id, start_time, end_time, worktype, pool)
+        timer_events.publish(TimerEventKind::Updated, worktime.clone());
+        Ok(worktime)
+    }
+}
+
+#[derive(Default)]
+pub struct TimerSubscription;
+
+#[async_graphql::Subscription]
+impl TimerSubscription {
+    /// Timer started, stopped and updated events of the current employee.
+    async fn timer_events(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+    ) -> async_graphql::Result<impl Stream<Item = models::TimerEvent>> {
+        let employee_id = *ctx.data::<i32>()?;
+        let receiver = ctx.data::<TimerEvents>()?.subscribe();
+
+        // lagged receivers skip the missed events
+        Ok(BroadcastStream::new(receiver).filter_map(move |event| {
+            future::ready(event.ok().filter(|e| e.worktime.employee_id == employee_id))
+        }))
+    }
+
+    /// All currently running timers, sent once on subscription and again after every change.
+    #[graphql(guard = "RoleGuard::new(EmployeeRole::Manager)")]
+    async fn working_now(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+    ) -> async_graphql::Result<impl Stream<Item = async_graphql::Result<Vec<models::Worktime>>>>
+    {
+        let pool = ctx.data::<sqlx::PgPool>()?.clone();
+        let receiver = ctx.data::<TimerEvents>()?.subscribe();
+
+        let changes = BroadcastStream::new(receiver).map(|_| ());
+
+        Ok(stream::once(future::ready(()))
+            .chain(changes)
+            .then(move |_| {
+                let pool = pool.clone();
+                async move {
+                    worktime::get_running_timers(&pool)
+                        .await
+                        .map_err(async_graphql::Error::new_with_source)
+                }
+            }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_graphql::Request;
+
+    use crate::events::TaskBudgetAlerts;
+    use crate::graphql::create_schema;
+
+    use super::*;
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/task.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+        "../../fixtures/worktime.sql"
+    ))]
+    async fn test_timer_events_of_current_employee(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        let timer_events = TimerEvents::new();
+        let schema = create_schema(pool.clone(), timer_events.clone(), TaskBudgetAlerts::new());
+
+        let mut events = schema.execute_stream(
+            Request::new("subscription { timerEvents { kind worktime { employeeId } } }").data(1),
+        );
+
+        let foreign = worktime::get_timers(&2, &pool).await?.remove(0);
+        let own = worktime::get_timers(&1, &pool).await?.remove(0);
+
+        // the subscription only listens once it is polled, so keep publishing until it received
+        let publisher = tokio::spawn(async move {
+            loop {
+                timer_events.publish(TimerEventKind::Started, foreign.clone());
+                timer_events.publish(TimerEventKind::Stopped, own.clone());
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        });
+
+        let response = tokio::time::timeout(std::time::Duration::from_secs(5), events.next())
             .await
-            .map_err(async_graphql::Error::new_with_source)
+            .expect("no timer event received")
+            .unwrap();
+        publisher.abort();
+
+        assert!(response.errors.is_empty());
+        assert_eq!(
+            response.data.into_json().unwrap(),
+            serde_json::json!({ "timerEvents": { "kind": "STOPPED", "worktime": { "employeeId": 1 } } })
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/task.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+        "../../fixtures/worktime.sql"
+    ))]
+    async fn test_update_timer_to_task_of_others(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        // given is task 2 which is assigned to leck only
+        task_assignment::assign_task(2, Some(2), None, &pool).await?;
+        let schema = create_schema(pool, TimerEvents::new(), TaskBudgetAlerts::new());
+
+        // then bob can not move his worktime onto it, but onto an open task
+        let response = schema
+            .execute(
+                Request::new("mutation { updateTimer(worktimeId: 1, taskId: 2) { taskId } }")
+                    .data(1),
+            )
+            .await;
+        assert_eq!(
+            response.errors[0].message,
+            "The task is archived, assigned to others or does not exist"
+        );
+        let response = schema
+            .execute(
+                Request::new("mutation { updateTimer(worktimeId: 1, taskId: 3) { taskId } }")
+                    .data(1),
+            )
+            .await;
+        assert!(response.errors.is_empty());
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/task.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+        "../../fixtures/worktime.sql"
+    ))]
+    async fn test_billable_requires_manager(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        let schema = create_schema(pool, TimerEvents::new(), TaskBudgetAlerts::new());
+        let mutation = "mutation { updateTimer(worktimeId: 1, billable: false) { billable } }";
+
+        let response = schema.execute(Request::new(mutation).data(1)).await;
+        assert_eq!(
+            response.errors[0].message,
+            "Only managers can change whether a worktime is billable"
+        );
+
+        let response = schema.execute(Request::new(mutation).data(3)).await;
+        assert!(response.errors.is_empty());
+        assert_eq!(
+            response.data.into_json().unwrap(),
+            serde_json::json!({ "updateTimer": { "billable": false } })
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/task.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+        "../../fixtures/worktime.sql"
+    ))]
+    async fn test_working_now_requires_manager(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        let schema = create_schema(pool, TimerEvents::new(), TaskBudgetAlerts::new());
+        let query = "subscription { workingNow { worktimeId } }";
+
+        let response = schema
+            .execute_stream(Request::new(query).data(1))
+            .next()
+            .await
+            .unwrap();
+        assert!(!response.errors.is_empty());
+
+        let response = schema
+            .execute_stream(Request::new(query).data(3))
+            .next()
+            .await
+            .unwrap();
+        assert!(response.errors.is_empty());
+        assert_eq!(
+            response.data.into_json().unwrap(),
+            serde_json::json!({ "workingNow": [{ "worktimeId": 2 }] })
+        );
+
+        Ok(())
     }
 }
@@ -0,0 +1,21 @@
+use sqlx::PgPool;
+
+use crate::events::{TaskBudgetAlerts, TimerEvents};
+
+pub mod forgotten_timers;
+pub mod report_delivery;
+pub mod task_budget;
+
+// spawns all background jobs of the server, they run until the process exits
+pub fn spawn_jobs(
+    database_pool: PgPool,
+    timer_events: TimerEvents,
+    budget_alerts: TaskBudgetAlerts,
+) {
+    tokio::spawn(forgotten_timers::run(
+        database_pool.clone(),
+        timer_events.clone(),
+    ));
+    tokio::spawn(report_delivery::run(database_pool.clone()));
+    tokio::spawn(task_budget::run(database_pool, timer_events, budget_alerts));
+}
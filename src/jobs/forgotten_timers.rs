@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::{
+    events::TimerEvents,
+    models::TimerEventKind,
+    service::worktime::{self, StopPolicy},
+};
+
+pub struct ForgottenTimerConfig {
+    pub max_open: chrono::Duration,
+    pub policy: StopPolicy,
+    pub check_interval: Duration,
+}
+
+impl ForgottenTimerConfig {
+    // reads the configuration from the environment, unset variables fall back to the defaults:
+    // FORGOTTEN_TIMER_LIMIT_HOURS=12, FORGOTTEN_TIMER_POLICY=limit, FORGOTTEN_TIMER_INTERVAL_MINUTES=15
+    pub fn from_env() -> anyhow::Result<Self> {
+        let max_open_hours = dotenvy::var("FORGOTTEN_TIMER_LIMIT_HOURS")
+            .unwrap_or_else(|_| String::from("12"))
+            .parse::<i64>()
+            .context("FORGOTTEN_TIMER_LIMIT_HOURS is not a proper number.")?;
+        let policy = parse_policy(
+            &dotenvy::var("FORGOTTEN_TIMER_POLICY").unwrap_or_else(|_| String::from("limit")),
+        )?;
+        let interval_minutes = dotenvy::var("FORGOTTEN_TIMER_INTERVAL_MINUTES")
+            .unwrap_or_else(|_| String::from("15"))
+            .parse::<u64>()
+            .context("FORGOTTEN_TIMER_INTERVAL_MINUTES is not a proper number.")?;
+
+        Ok(Self {
+            max_open: chrono::Duration::hours(max_open_hours),
+            policy,
+            check_interval: Duration::from_secs(interval_minutes * 60),
+        })
+    }
+}
+
+// accepts 'limit', 'start' or a time of day like '17:00'
+fn parse_policy(policy: &str) -> anyhow::Result<StopPolicy> {
+    match policy {
+        "limit" => Ok(StopPolicy::AfterLimit),
+        "start" => Ok(StopPolicy::AtStart),
+        time => chrono::NaiveTime::parse_from_str(time, "%H:%M")
+            .map(StopPolicy::AtTimeOfDay)
+            .with_context(|| format!("FORGOTTEN_TIMER_POLICY '{}' is not supported.", time)),
+    }
+}
+
+pub async fn run(database_pool: PgPool, timer_events: TimerEvents) {
+    let config = match ForgottenTimerConfig::from_env() {
+        Ok(config) => config,
+        Err(err) => {
+            tracing::error!("Forgotten timer job is disabled: {:?}", err);
+            return;
+        }
+    };
+
+    let mut interval = tokio::time::interval(config.check_interval);
+    loop {
+        interval.tick().await;
+
+        match worktime::stop_forgotten_timers(config.max_open, config.policy, &database_pool).await
+        {
+            Ok(stopped) if stopped.is_empty() => {}
+            Ok(stopped) => {
+                tracing::info!("Stopped {} forgotten timers", stopped.len());
+                for worktime in stopped {
+                    timer_events.publish(TimerEventKind::Stopped, worktime);
+                }
+            }
+            Err(err) => tracing::error!("Failed to stop forgotten timers: {:?}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_policy() {
+        assert_eq!(parse_policy("limit").unwrap(), StopPolicy::AfterLimit);
+        assert_eq!(parse_policy("start").unwrap(), StopPolicy::AtStart);
+        assert_eq!(
+            parse_policy("17:30").unwrap(),
+            StopPolicy::AtTimeOfDay(chrono::NaiveTime::from_hms_opt(17, 30, 0).unwrap())
+        );
+        assert!(parse_policy("whenever").is_err());
+    }
+}
@@ -0,0 +1,362 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, Context};
+use chrono::{Datelike, NaiveDate};
+use sqlx::PgPool;
+
+use crate::{
+    mail::{Mail, MailAttachment, MailTransport, Transport},
+    models::{AbsenceStatus, Employee, ReportDelivery},
+    pdf::{period::MonthPeriod, render_pdf, HeaderColor, Locale, ReportPeriod},
+    service::{absence, employee, report_delivery, worktime},
+    time_utils,
+};
+
+// key of the advisory lock which keeps several servers from sending the same reports
+const DELIVERY_LOCK: i64 = 0x7265_706f_7274;
+
+pub struct ReportDeliveryConfig {
+    /// day of the month from which on the reports of the previous month are sent
+    pub day_of_month: u32,
+    /// gets a copy of every report, e.g. the office manager
+    pub office_address: Option<String>,
+    /// language of the reports and the mails
+    pub locale: Locale,
+    pub check_interval: Duration,
+    /// failed attempts after which a report is not sent again
+    pub max_attempts: i64,
+}
+
+impl ReportDeliveryConfig {
+    // reads the configuration from the environment, unset variables fall back to the defaults:
+    // REPORT_DELIVERY_DAY=1, REPORT_DELIVERY_INTERVAL_MINUTES=60, REPORT_DELIVERY_LOCALE=DE,
+    // REPORT_DELIVERY_MAX_ATTEMPTS=5, no REPORT_DELIVERY_OFFICE_EMAIL
+    pub fn from_env() -> anyhow::Result<Self> {
+        let day_of_month = dotenvy::var("REPORT_DELIVERY_DAY")
+            .unwrap_or_else(|_| String::from("1"))
+            .parse::<u32>()
+            .context("REPORT_DELIVERY_DAY is not a proper number.")?;
+        // every month has the day
+        if !(1..=28).contains(&day_of_month) {
+            return Err(anyhow!("REPORT_DELIVERY_DAY has to be between 1 and 28."));
+        }
+        let interval_minutes = dotenvy::var("REPORT_DELIVERY_INTERVAL_MINUTES")
+            .unwrap_or_else(|_| String::from("60"))
+            .parse::<u64>()
+            .context("REPORT_DELIVERY_INTERVAL_MINUTES is not a proper number.")?;
+        let max_attempts = dotenvy::var("REPORT_DELIVERY_MAX_ATTEMPTS")
+            .unwrap_or_else(|_| String::from("5"))
+            .parse::<i64>()
+            .context("REPORT_DELIVERY_MAX_ATTEMPTS is not a proper number.")?;
+        let locale = match dotenvy::var("REPORT_DELIVERY_LOCALE") {
+            Ok(locale) => Locale::parse(&locale).with_context(|| {
+                format!("REPORT_DELIVERY_LOCALE '{}' is not supported.", locale)
+            })?,
+            Err(_) => Locale::default(),
+        };
+
+        Ok(Self {
+            day_of_month,
+            office_address: dotenvy::var("REPORT_DELIVERY_OFFICE_EMAIL").ok(),
+            locale,
+            check_interval: Duration::from_secs(interval_minutes * 60),
+            max_attempts,
+        })
+    }
+}
+
+// the month before the one of the date
+fn previous_month(date: NaiveDate) -> MonthPeriod {
+    let last_day = date
+        .with_day(1)
+        .and_then(|first| first.pred_opt())
+        .unwrap_or(date);
+    MonthPeriod {
+        year: last_day.year(),
+        month: last_day.month(),
+    }
+}
+
+// employees with times or approved absences in the month, others may have left the company
+async fn get_recipients(
+    from: NaiveDate,
+    to: NaiveDate,
+    pool: &PgPool,
+) -> sqlx::Result<Vec<Employee>> {
+    let employees = employee::get_all_employees(pool).await?;
+    let employee_ids: Vec<i32> = employees.iter().map(|e| e.employee_id).collect();
+    let absences =
+        absence::get_absences(&employee_ids, from, to, Some(AbsenceStatus::Approved), pool).await?;
+
+    let mut recipients = Vec::new();
+    for employee in employees {
+        let timezone = time_utils::resolve_timezone(employee.timezone.as_deref());
+        let (start, _) = time_utils::day_bounds(from, &timezone);
+        let (_, end) = time_utils::day_bounds(to, &timezone);
+        if absences
+            .iter()
+            .any(|absence| absence.employee_id == employee.employee_id)
+            || !worktime::get_timers_overlapping(&employee.employee_id, start, end, pool)
+                .await?
+                .is_empty()
+        {
+            recipients.push(employee);
+        }
+    }
+
+    Ok(recipients)
+}
+
+/// Mails the report of the month to every employee with times or absences in the month whose
+/// report was not sent yet. Every attempt is recorded, failed ones are tried again on the next
+/// runs until `max_attempts` failed. Nothing is sent while another server is delivering the
+/// reports.
+pub async fn deliver_reports(
+    month: MonthPeriod,
+    config: &ReportDeliveryConfig,
+    transport: &impl MailTransport,
+    pool: &PgPool,
+) -> anyhow::Result<Vec<ReportDelivery>> {
+    let period = ReportPeriod::Month(month);
+    let period_name = period.to_string();
+    let locale = config.locale;
+    let catalog = locale.catalog();
+    let label = period.label(locale);
+    let (from, to) = period.bounds()?;
+
+    // held until the transaction ends, also if the job fails
+    let mut lock = pool.begin().await?;
+    if !sqlx::query_scalar!(
+        r#"SELECT pg_try_advisory_xact_lock($1) as "locked!""#,
+        DELIVERY_LOCK
+    )
+    .fetch_one(&mut *lock)
+    .await?
+    {
+        return Ok(Vec::new());
+    }
+
+    let mut deliveries = Vec::new();
+    for employee in get_recipients(from, to, pool).await? {
+        if report_delivery::is_delivered(employee.employee_id, &period_name, pool).await? {
+            continue;
+        }
+        if report_delivery::count_failed(employee.employee_id, &period_name, pool).await?
+            >= config.max_attempts
+        {
+            continue;
+        }
+
+        let sent = async {
+            let pdf = render_pdf(
+                period,
+                HeaderColor::default(),
+                locale,
+                pool,
+                &employee.employee_id,
+            )
+            .await?;
+            transport
+                .send(&Mail {
+                    to: employee.email.clone(),
+                    cc: config.office_address.clone(),
+                    subject: format!("{} {}", catalog.title, label),
+                    body: format!(
+                        "{} {},\n\n{} {}.\n",
+                        catalog.mail_greeting,
+                        employee.firstname.as_deref().unwrap_or_default(),
+                        catalog.mail_text,
+                        label
+                    ),
+                    attachments: vec![MailAttachment {
+                        filename: format!("{}.pdf", period_name),
+                        content_type: String::from("application/pdf"),
+                        content: pdf,
+                    }],
+                })
+                .await
+        }
+        .await;
+
+        let error = sent.err().map(|err| format!("{:#}", err));
+        if let Some(error) = &error {
+            tracing::warn!(
+                "Report {} of employee {} could not be sent: {}",
+                period_name,
+                employee.employee_id,
+                error
+            );
+        }
+        deliveries.push(
+            report_delivery::record_delivery(
+                employee.employee_id,
+                &period_name,
+                &employee.email,
+                error.as_deref(),
+                pool,
+            )
+            .await?,
+        );
+    }
+    lock.commit().await?;
+
+    Ok(deliveries)
+}
+
+pub async fn run(database_pool: PgPool) {
+    let config = match ReportDeliveryConfig::from_env() {
+        Ok(config) => config,
+        Err(err) => {
+            tracing::error!("Report delivery job is disabled: {:?}", err);
+            return;
+        }
+    };
+    let transport = match Transport::from_env() {
+        Ok(Some(transport)) => transport,
+        Ok(None) => {
+            tracing::info!("Report delivery job is disabled: MAIL_TRANSPORT is not set");
+            return;
+        }
+        Err(err) => {
+            tracing::error!("Report delivery job is disabled: {:?}", err);
+            return;
+        }
+    };
+
+    let mut interval = tokio::time::interval(config.check_interval);
+    loop {
+        interval.tick().await;
+
+        let today = time_utils::local_date(chrono::Utc::now(), &time_utils::company_timezone());
+        // later days catch up on the reports if the server was down on the day
+        if today.day() < config.day_of_month {
+            continue;
+        }
+
+        match deliver_reports(previous_month(today), &config, &transport, &database_pool).await {
+            Ok(deliveries) if deliveries.is_empty() => {}
+            Ok(deliveries) => tracing::info!("Sent {} monthly reports", deliveries.len()),
+            Err(err) => tracing::error!("Failed to send the monthly reports: {:?}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mail::FileDropTransport;
+    use crate::models::DeliveryStatus;
+
+    const JANUARY: MonthPeriod = MonthPeriod {
+        year: 2024,
+        month: 1,
+    };
+
+    #[test]
+    fn test_previous_month() {
+        assert_eq!(
+            previous_month(NaiveDate::from_ymd_opt(2024, 2, 1).unwrap()),
+            JANUARY
+        );
+        assert_eq!(
+            previous_month(NaiveDate::from_ymd_opt(2024, 1, 15).unwrap()),
+            MonthPeriod {
+                year: 2023,
+                month: 12
+            }
+        );
+    }
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/task.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+        "../../fixtures/worktime.sql"
+    ))]
+    async fn test_deliver_reports(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let directory = tempfile::tempdir()?;
+        let transport = FileDropTransport::new(directory.path(), "noreply@test.de".parse()?);
+        let config = ReportDeliveryConfig {
+            day_of_month: 1,
+            office_address: Some(String::from("office@test.de")),
+            locale: Locale::En,
+            check_interval: Duration::from_secs(60),
+            max_attempts: 2,
+        };
+        // given is an employee whose address is broken
+        sqlx::query!("UPDATE employee SET email = 'broken' WHERE employee_id = 1")
+            .execute(&pool)
+            .await?;
+        // and one who left the company before january
+        sqlx::query!("DELETE FROM worktime WHERE employee_id = 2")
+            .execute(&pool)
+            .await?;
+        // and the manager was on vacation in january
+        let vacation = absence::request_absence(
+            &3,
+            crate::models::AbsenceType::Vacation,
+            NaiveDate::from_ymd_opt(2024, 1, 22).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 26).unwrap(),
+            None,
+            &pool,
+        )
+        .await?;
+        absence::review_absence(vacation.absence_id, AbsenceStatus::Approved, &pool).await?;
+
+        // when another server is delivering the reports, then nothing is sent
+        let mut other_server = pool.begin().await?;
+        sqlx::query!("SELECT pg_advisory_xact_lock($1)", DELIVERY_LOCK)
+            .execute(&mut *other_server)
+            .await?;
+        assert!(deliver_reports(JANUARY, &config, &transport, &pool)
+            .await?
+            .is_empty());
+        other_server.rollback().await?;
+
+        // when the reports are delivered, then the attempt is recorded as failed
+        let deliveries = deliver_reports(JANUARY, &config, &transport, &pool).await?;
+        let employees: Vec<i32> = deliveries.iter().map(|d| d.employee_id).collect();
+        assert!(employees.contains(&1));
+        // the manager gets a report for the vacation, but not the employee who left
+        assert!(employees.contains(&3));
+        assert!(!employees.contains(&2));
+        let bob = deliveries.iter().find(|d| d.employee_id == 1).unwrap();
+        assert_eq!(bob.status, DeliveryStatus::Failed);
+        assert!(bob.error.is_some());
+
+        // when the second attempt fails as well, then the report is not tried again
+        let deliveries = deliver_reports(JANUARY, &config, &transport, &pool).await?;
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].status, DeliveryStatus::Failed);
+        assert!(deliver_reports(JANUARY, &config, &transport, &pool)
+            .await?
+            .is_empty());
+
+        // when the address is fixed and another attempt is allowed, then the report is sent
+        let config = ReportDeliveryConfig {
+            max_attempts: 3,
+            ..config
+        };
+        sqlx::query!("UPDATE employee SET email = 'test@test.de' WHERE employee_id = 1")
+            .execute(&pool)
+            .await?;
+        let deliveries = deliver_reports(JANUARY, &config, &transport, &pool).await?;
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].status, DeliveryStatus::Sent);
+        assert_eq!(deliveries[0].recipient, "test@test.de");
+        let mails: Vec<_> = std::fs::read_dir(directory.path())?
+            .map(|entry| std::fs::read_to_string(entry?.path()))
+            .collect::<Result<_, _>>()?;
+        assert!(mails.iter().any(|mail| mail.contains("To: test@test.de")
+            && mail.contains("2024-01.pdf")
+            && mail.contains("please find attached the time sheet for")));
+
+        // and not again
+        assert!(deliver_reports(JANUARY, &config, &transport, &pool)
+            .await?
+            .is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,224 @@
+use anyhow::{anyhow, Context};
+use sqlx::PgPool;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{
+    events::{TaskBudgetAlerts, TimerEvents},
+    mail::{Mail, MailTransport, Transport},
+    models::TaskBudgetAlert,
+    pdf::Locale,
+    service::task_budget,
+};
+
+pub struct TaskBudgetConfig {
+    /// percent of the estimate, ascending
+    pub thresholds: Vec<i32>,
+    /// gets a mail for every alert, e.g. the project lead
+    pub alert_address: Option<String>,
+    /// language of the alert mails
+    pub locale: Locale,
+}
+
+impl TaskBudgetConfig {
+    // reads the configuration from the environment, unset variables fall back to the defaults:
+    // TASK_BUDGET_THRESHOLDS=80,100, TASK_BUDGET_LOCALE=DE, no TASK_BUDGET_ALERT_EMAIL
+    pub fn from_env() -> anyhow::Result<Self> {
+        let thresholds = parse_thresholds(
+            &dotenvy::var("TASK_BUDGET_THRESHOLDS").unwrap_or_else(|_| String::from("80,100")),
+        )?;
+        let locale = match dotenvy::var("TASK_BUDGET_LOCALE") {
+            Ok(locale) => Locale::parse(&locale)
+                .with_context(|| format!("TASK_BUDGET_LOCALE '{}' is not supported.", locale))?,
+            Err(_) => Locale::default(),
+        };
+
+        Ok(Self {
+            thresholds,
+            alert_address: dotenvy::var("TASK_BUDGET_ALERT_EMAIL").ok(),
+            locale,
+        })
+    }
+}
+
+// a comma separated list of positive percentages like '80,100'
+fn parse_thresholds(thresholds: &str) -> anyhow::Result<Vec<i32>> {
+    let mut parsed = thresholds
+        .split(',')
+        .map(|threshold| {
+            threshold
+                .trim()
+                .parse::<i32>()
+                .ok()
+                .filter(|percent| *percent > 0)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "TASK_BUDGET_THRESHOLDS '{}' is not a proper percentage.",
+                        threshold
+                    )
+                })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    parsed.sort_unstable();
+    parsed.dedup();
+    Ok(parsed)
+}
+
+fn alert_mail(to: &str, alert: &TaskBudgetAlert, locale: Locale) -> Mail {
+    let catalog = locale.catalog();
+    let description = alert
+        .task_description
+        .clone()
+        .unwrap_or_else(|| format!("{} {}", catalog.alert_task, alert.task_id));
+    Mail {
+        to: String::from(to),
+        cc: None,
+        subject: format!(
+            "{}: {}% {}",
+            description, alert.threshold, catalog.alert_reached
+        ),
+        body: format!(
+            "{} \"{}\": {} {} {} {}.\n",
+            catalog.alert_booked,
+            description,
+            locale.format_hours(alert.booked_minutes.max(0) as u64),
+            catalog.alert_of,
+            locale.format_hours((alert.estimated_hours * 60.0).round() as u64),
+            catalog.alert_estimated
+        ),
+        attachments: Vec::new(),
+    }
+}
+
+/// Records the newly passed thresholds of the task, publishes them and mails them if an address
+/// is configured. Mails that fail are only logged, the alert is not sent again.
+pub async fn check_task(
+    task_id: i32,
+    config: &TaskBudgetConfig,
+    alerts: &TaskBudgetAlerts,
+    transport: Option<&impl MailTransport>,
+    pool: &PgPool,
+) -> anyhow::Result<Vec<TaskBudgetAlert>> {
+    let crossed = task_budget::record_crossed_thresholds(task_id, &config.thresholds, pool)
+        .await
+        .context("the thresholds could not be recorded")?;
+
+    for alert in &crossed {
+        alerts.publish(alert.clone());
+        if let (Some(transport), Some(address)) = (transport, &config.alert_address) {
+            if let Err(err) = transport
+                .send(&alert_mail(address, alert, config.locale))
+                .await
+            {
+                tracing::warn!(
+                    "Budget alert of task {} could not be sent: {:#}",
+                    task_id,
+                    err
+                );
+            }
+        }
+    }
+
+    Ok(crossed)
+}
+
+pub async fn run(database_pool: PgPool, timer_events: TimerEvents, alerts: TaskBudgetAlerts) {
+    let config = match TaskBudgetConfig::from_env() {
+        Ok(config) => config,
+        Err(err) => {
+            tracing::error!("Task budget job is disabled: {:?}", err);
+            return;
+        }
+    };
+    // without an address the alerts are only published to the subscriptions
+    let transport = match config.alert_address {
+        Some(_) => match Transport::from_env() {
+            Ok(transport) => transport,
+            Err(err) => {
+                tracing::error!("Task budget alerts are not mailed: {:?}", err);
+                None
+            }
+        },
+        None => None,
+    };
+
+    let mut receiver = timer_events.subscribe();
+    loop {
+        let event = match receiver.recv().await {
+            Ok(event) => event,
+            // the skipped tasks are checked again on their next booking
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => return,
+        };
+        // running timers are not booked yet
+        if event.worktime.end_time.is_none() {
+            continue;
+        }
+
+        if let Err(err) = check_task(
+            event.worktime.task_id,
+            &config,
+            &alerts,
+            transport.as_ref(),
+            &database_pool,
+        )
+        .await
+        {
+            tracing::error!("Failed to check the budget of a task: {:?}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mail::FileDropTransport;
+
+    #[test]
+    fn test_parse_thresholds() {
+        assert_eq!(parse_thresholds("100, 80").unwrap(), vec![80, 100]);
+        assert_eq!(parse_thresholds("50,50").unwrap(), vec![50]);
+        assert!(parse_thresholds("80,").is_err());
+        assert!(parse_thresholds("-10").is_err());
+    }
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/task.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+        "../../fixtures/worktime.sql"
+    ))]
+    async fn test_check_task(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let directory = tempfile::tempdir()?;
+        let transport = FileDropTransport::new(directory.path(), "noreply@test.de".parse()?);
+        let config = TaskBudgetConfig {
+            thresholds: vec![80, 100],
+            alert_address: Some(String::from("lead@test.de")),
+            locale: Locale::En,
+        };
+        let alerts = TaskBudgetAlerts::new();
+        let mut receiver = alerts.subscribe();
+
+        // given is a task whose booked time exceeds its estimate
+        task_budget::set_task_estimate(4, Some(0.01), None, &pool).await?;
+
+        // when it is checked, then both thresholds are published and mailed once
+        let crossed = check_task(4, &config, &alerts, Some(&transport), &pool).await?;
+        assert_eq!(crossed.len(), 2);
+        assert_eq!(receiver.recv().await?.threshold, 80);
+        assert_eq!(receiver.recv().await?.threshold, 100);
+        let mails: Vec<_> = std::fs::read_dir(directory.path())?
+            .map(|entry| std::fs::read_to_string(entry?.path()))
+            .collect::<Result<_, _>>()?;
+        assert_eq!(mails.len(), 2);
+        assert!(mails
+            .iter()
+            .any(|mail| mail.contains("100% of the budget reached")));
+
+        assert!(check_task(4, &config, &alerts, Some(&transport), &pool)
+            .await?
+            .is_empty());
+
+        Ok(())
+    }
+}
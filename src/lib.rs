@@ -1,8 +1,14 @@
 use tokio::signal;
 
 pub mod auth;
+pub mod calendar;
 pub mod database;
+pub mod download;
+pub mod events;
+pub mod export;
 pub mod graphql;
+pub mod jobs;
+pub mod mail;
 mod models;
 pub mod pdf;
 mod security;
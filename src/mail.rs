@@ -0,0 +1,202 @@
+use std::future::Future;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context};
+use lettre::message::{header::ContentType, Attachment, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+pub struct MailAttachment {
+    pub filename: String,
+    /// MIME type like 'application/pdf'
+    pub content_type: String,
+    pub content: Vec<u8>,
+}
+
+pub struct Mail {
+    pub to: String,
+    pub cc: Option<String>,
+    pub subject: String,
+    pub body: String,
+    pub attachments: Vec<MailAttachment>,
+}
+
+/// Sends mails, the server uses SMTP and the tests drop the mails into a directory.
+pub trait MailTransport {
+    fn send(&self, mail: &Mail) -> impl Future<Output = anyhow::Result<()>> + Send;
+}
+
+fn build_message(from: &Mailbox, mail: &Mail) -> anyhow::Result<Message> {
+    let mut builder = Message::builder()
+        .from(from.clone())
+        .to(mail
+            .to
+            .parse()
+            .context("the recipient is not a valid address")?)
+        .subject(&mail.subject);
+    if let Some(cc) = &mail.cc {
+        builder = builder.cc(cc.parse().context("the cc is not a valid address")?);
+    }
+
+    let mut body = MultiPart::mixed().singlepart(SinglePart::plain(mail.body.clone()));
+    for attachment in &mail.attachments {
+        body = body.singlepart(Attachment::new(attachment.filename.clone()).body(
+            attachment.content.clone(),
+            ContentType::parse(&attachment.content_type)?,
+        ));
+    }
+
+    Ok(builder.multipart(body)?)
+}
+
+pub struct SmtpTransport {
+    mailer: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl MailTransport for SmtpTransport {
+    async fn send(&self, mail: &Mail) -> anyhow::Result<()> {
+        self.mailer.send(build_message(&self.from, mail)?).await?;
+        Ok(())
+    }
+}
+
+/// Writes every mail as .eml file into the directory instead of sending it.
+pub struct FileDropTransport {
+    directory: PathBuf,
+    from: Mailbox,
+}
+
+impl FileDropTransport {
+    pub fn new(directory: impl Into<PathBuf>, from: Mailbox) -> Self {
+        Self {
+            directory: directory.into(),
+            from,
+        }
+    }
+}
+
+impl MailTransport for FileDropTransport {
+    async fn send(&self, mail: &Mail) -> anyhow::Result<()> {
+        let message = build_message(&self.from, mail)?;
+        // the time keeps the files in the order they were sent
+        let recipient: String = mail
+            .to
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let filename = format!(
+            "{}-{}.eml",
+            chrono::Utc::now().format("%Y%m%dT%H%M%S%.6f"),
+            recipient
+        );
+
+        tokio::fs::create_dir_all(&self.directory).await?;
+        tokio::fs::write(self.directory.join(filename), message.formatted()).await?;
+        Ok(())
+    }
+}
+
+/// The transport configured in the environment, None if mails are not sent at all.
+pub enum Transport {
+    Smtp(SmtpTransport),
+    FileDrop(FileDropTransport),
+}
+
+impl Transport {
+    // MAIL_TRANSPORT is 'smtp' or 'file', unset disables sending mails. Both need MAIL_FROM,
+    // 'smtp' SMTP_HOST, SMTP_PORT (default 587, STARTTLS), SMTP_USERNAME and SMTP_PASSWORD,
+    // 'file' MAIL_DROP_DIRECTORY
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        let Ok(kind) = dotenvy::var("MAIL_TRANSPORT") else {
+            return Ok(None);
+        };
+        let from: Mailbox = dotenvy::var("MAIL_FROM")
+            .context("MAIL_FROM is not set.")?
+            .parse()
+            .context("MAIL_FROM is not a valid address.")?;
+
+        match kind.as_str() {
+            "smtp" => {
+                let host = dotenvy::var("SMTP_HOST").context("SMTP_HOST is not set.")?;
+                let port = dotenvy::var("SMTP_PORT")
+                    .unwrap_or_else(|_| String::from("587"))
+                    .parse::<u16>()
+                    .context("SMTP_PORT is not a proper number.")?;
+                let mut builder =
+                    AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&host)?.port(port);
+                if let Ok(username) = dotenvy::var("SMTP_USERNAME") {
+                    let password =
+                        dotenvy::var("SMTP_PASSWORD").context("SMTP_PASSWORD is not set.")?;
+                    builder = builder.credentials(Credentials::new(username, password));
+                }
+
+                Ok(Some(Transport::Smtp(SmtpTransport {
+                    mailer: builder.build(),
+                    from,
+                })))
+            }
+            "file" => {
+                let directory = dotenvy::var("MAIL_DROP_DIRECTORY")
+                    .context("MAIL_DROP_DIRECTORY is not set.")?;
+                Ok(Some(Transport::FileDrop(FileDropTransport::new(
+                    directory, from,
+                ))))
+            }
+            other => Err(anyhow!("MAIL_TRANSPORT '{}' is not supported.", other)),
+        }
+    }
+}
+
+impl MailTransport for Transport {
+    async fn send(&self, mail: &Mail) -> anyhow::Result<()> {
+        match self {
+            Transport::Smtp(transport) => transport.send(mail).await,
+            Transport::FileDrop(transport) => transport.send(mail).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_file_drop_transport() -> anyhow::Result<()> {
+        let directory = tempfile::tempdir()?;
+        let transport =
+            FileDropTransport::new(directory.path(), "Zeiterfassung <noreply@test.de>".parse()?);
+
+        transport
+            .send(&Mail {
+                to: String::from("test@test.de"),
+                cc: Some(String::from("office@test.de")),
+                subject: String::from("Zeitenübersicht Januar 2024"),
+                body: String::from("Anbei"),
+                attachments: vec![MailAttachment {
+                    filename: String::from("2024-01.pdf"),
+                    content_type: String::from("application/pdf"),
+                    content: b"%PDF".to_vec(),
+                }],
+            })
+            .await?;
+
+        let files: Vec<_> = std::fs::read_dir(directory.path())?.collect::<Result<_, _>>()?;
+        assert_eq!(files.len(), 1);
+        let eml = std::fs::read_to_string(files[0].path())?;
+        assert!(eml.contains("To: test@test.de"));
+        assert!(eml.contains("Cc: office@test.de"));
+        assert!(eml.contains("2024-01.pdf"));
+
+        let invalid = Mail {
+            to: String::from("not an address"),
+            cc: None,
+            subject: String::new(),
+            body: String::new(),
+            attachments: Vec::new(),
+        };
+        assert!(transport.send(&invalid).await.is_err());
+
+        Ok(())
+    }
+}
@@ -1,10 +1,21 @@
-use axum::{middleware, routing::post, Extension, Router};
+use axum::{
+    middleware,
+    routing::{get, post},
+    Extension, Router,
+};
 use sqlx::PgPool;
 use tower_http::cors;
 use ttbackend::{
     auth::{auth, login, refresh},
+    calendar::calendar_feed,
     database::set_up_database,
-    graphql::{create_schema, graphql_handler},
+    download::{
+        archived_report_pdf, billing_report_pdf, datev_export, period_report, task_report,
+        team_report_pdf, verify_report, worktime_export,
+    },
+    events::{TaskBudgetAlerts, TimerEvents},
+    graphql::{create_schema, graphql_handler, graphql_ws_handler},
+    jobs::spawn_jobs,
     shutdown_signal,
     tracing_setup::{remove_old_logfiles, setup_tracing},
 };
@@ -12,7 +23,9 @@ use ttbackend::{
 #[cfg(debug_assertions)]
 async fn graphql_playground() -> impl axum::response::IntoResponse {
     use async_graphql::http::{playground_source, GraphQLPlaygroundConfig};
-    axum::response::Html(playground_source(GraphQLPlaygroundConfig::new("/graphql")))
+    axum::response::Html(playground_source(
+        GraphQLPlaygroundConfig::new("/graphql").subscription_endpoint("/ws"),
+    ))
 }
 
 #[tokio::main]
@@ -23,7 +36,19 @@ async fn main() {
 
     // setup database connection pool
     let database_pool = set_up_database().await;
-    let app = app(database_pool);
+
+    // shared between the graphql subscriptions and the background jobs
+    let timer_events = TimerEvents::new();
+    let budget_alerts = TaskBudgetAlerts::new();
+
+    // start background jobs like stopping forgotten timers
+    spawn_jobs(
+        database_pool.clone(),
+        timer_events.clone(),
+        budget_alerts.clone(),
+    );
+
+    let app = app(database_pool, timer_events, budget_alerts);
 
     #[cfg(debug_assertions)]
     let app = debug_route(app);
@@ -37,12 +62,16 @@ async fn main() {
         .unwrap();
 }
 
-fn app(database_pool: PgPool) -> Router {
-    let schema = create_schema(database_pool.clone());
+fn app(
+    database_pool: PgPool,
+    timer_events: TimerEvents,
+    budget_alerts: TaskBudgetAlerts,
+) -> Router {
+    let schema = create_schema(database_pool.clone(), timer_events, budget_alerts);
 
     let cors = cors::CorsLayer::new()
-        // allow `POST` when accessing the resource
-        .allow_methods([hyper::Method::POST])
+        // allow `POST` when accessing the resource and `GET` for downloads
+        .allow_methods([hyper::Method::POST, hyper::Method::GET])
         .allow_headers([http::header::AUTHORIZATION, http::header::CONTENT_TYPE])
         // allow requests from any origin
         .allow_origin(cors::Any);
@@ -53,16 +82,28 @@ fn app(database_pool: PgPool) -> Router {
             "/graphql",
             post(graphql_handler).layer(middleware::from_fn(auth)),
         )
+        // authenticated through the connection init payload
+        .route("/ws", get(graphql_ws_handler))
         .layer(Extension(schema))
         .route("/login", post(login))
         .route("/refresh", post(refresh))
+        // authenticated by bearer token or a download token in the query
+        .route("/reports/:file", get(period_report))
+        .route("/reports/team/:file", get(team_report_pdf))
+        .route("/reports/task/:file", get(task_report))
+        .route("/reports/billing/:file", get(billing_report_pdf))
+        .route("/reports/verify/:hash", get(verify_report))
+        .route("/reports/archive/:file", get(archived_report_pdf))
+        .route("/exports/:file", get(worktime_export))
+        .route("/exports/datev/:file", get(datev_export))
+        // authenticated by the secret calendar token in the path
+        .route("/calendar/:file", get(calendar_feed))
         .with_state(database_pool)
         .layer(cors)
 }
 
 #[cfg(debug_assertions)]
 fn debug_route(app: Router) -> Router {
-    use axum::routing::get;
     use tower_http::services::ServeFile;
     let debug = Router::new()
         .route("/playground", get(graphql_playground))
@@ -93,7 +134,7 @@ mod tests {
 
     async fn login() -> (Router, Authorization) {
         let database_pool = set_up_database().await;
-        let app = app(database_pool);
+        let app = app(database_pool, TimerEvents::new(), TaskBudgetAlerts::new());
 
         let json_payload = json!({
         "email": "mace.windu@deepcore.com",
@@ -196,4 +237,213 @@ mod tests {
         let body_string = String::from_utf8(body_bytes.to_vec()).unwrap();
         println!("Body timers: {}", body_string);
     }
+
+    #[tokio::test]
+    async fn test_report_download() {
+        let (app, claims) = login().await;
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("GET")
+                    .uri("/reports/2024-01.pdf")
+                    .header("authorization", format!("Bearer {}", claims.access_token))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[http::header::CONTENT_TYPE],
+            "application/pdf"
+        );
+        assert_eq!(
+            response.headers()[http::header::CONTENT_DISPOSITION],
+            "attachment; filename=\"report-2024-01.pdf\""
+        );
+        let body_bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(body_bytes.starts_with(b"%PDF"));
+
+        // without any token the report is not accessible
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("GET")
+                    .uri("/reports/2024-01.pdf")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_worktime_export() {
+        let (app, claims) = login().await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("GET")
+                    .uri("/exports/worktimes.csv?from=2024-01-01&to=2024-01-31&style=ISO")
+                    .header("authorization", format!("Bearer {}", claims.access_token))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[http::header::CONTENT_DISPOSITION],
+            "attachment; filename=\"worktimes-2024-01-01-2024-01-31.csv\""
+        );
+        let body_bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(body_bytes.starts_with(b"Employee,Date,Task"));
+    }
+
+    #[tokio::test]
+    async fn test_calendar_feed_unknown_token() {
+        let database_pool = set_up_database().await;
+        let app = app(database_pool, TimerEvents::new(), TaskBudgetAlerts::new());
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("GET")
+                    .uri("/calendar/unknown.ics")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_report_csv_download() {
+        let (app, claims) = login().await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("GET")
+                    .uri("/reports/2024-01.csv?style=ISO")
+                    .header("authorization", format!("Bearer {}", claims.access_token))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body_bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let csv = String::from_utf8(body_bytes.to_vec()).unwrap();
+        assert!(csv.starts_with("Date,Work (h),Ride (h),Break (h),Total (h),Net (h)\n2024-01-01,"));
+        // one line per day and the totals
+        assert_eq!(csv.lines().count(), 1 + 31 + 1);
+    }
+
+    #[tokio::test]
+    async fn test_team_report_download() {
+        let (app, claims) = login().await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("GET")
+                    .uri("/reports/team/company.pdf?from=2024-01-01&to=2024-01-31")
+                    .header("authorization", format!("Bearer {}", claims.access_token))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body_bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(body_bytes.starts_with(b"%PDF"));
+    }
+
+    #[tokio::test]
+    async fn test_task_report_unknown_task() {
+        let (app, claims) = login().await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("GET")
+                    .uri("/reports/task/999999.csv?from=2024-01-01&to=2024-01-31&style=ISO")
+                    .header("authorization", format!("Bearer {}", claims.access_token))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_billing_report_unknown_customer() {
+        let (app, claims) = login().await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("GET")
+                    .uri("/reports/billing/999999999.pdf?from=2024-01-01&to=2024-01-31")
+                    .header("authorization", format!("Bearer {}", claims.access_token))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_archived_report_unknown_report() {
+        let (app, claims) = login().await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("GET")
+                    .uri("/reports/archive/999999999.pdf")
+                    .header("authorization", format!("Bearer {}", claims.access_token))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_verify_unknown_report() {
+        let (app, _) = login().await;
+
+        // the verification works without an authorization header
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("GET")
+                    .uri(format!("/reports/verify/{}", "0".repeat(64)))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
 }
@@ -1,6 +1,13 @@
 use sqlx::postgres::types;
 
-use crate::service;
+use async_graphql::dataloader::DataLoader;
+
+use crate::{
+    graphql::loader::{
+        CustomerLoader, EmployeeLoader, ProjectLoader, TaskAssignmentLoader, TaskLoader,
+    },
+    service,
+};
 
 #[derive(async_graphql::Enum, Clone, Copy, PartialEq, Eq, Debug, sqlx::Type)]
 #[sqlx(type_name = "worktime_type", rename_all = "lowercase")]
@@ -10,7 +17,58 @@ pub enum WorktimeType {
     Work,
 }
 
-#[derive(async_graphql::SimpleObject, sqlx::FromRow)]
+#[derive(async_graphql::Enum, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SortDirection {
+    #[default]
+    Asc,
+    Desc,
+}
+
+#[derive(async_graphql::Enum, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum WorktimeSortField {
+    #[default]
+    StartTime,
+    Duration,
+}
+
+#[derive(async_graphql::Enum, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TaskSortField {
+    #[default]
+    Id,
+    Description,
+}
+
+#[derive(async_graphql::InputObject, Default)]
+pub struct WorktimeFilter {
+    pub task_id: Option<i32>,
+    pub work_type: Option<WorktimeType>,
+    /// inclusive lower bound of the start time
+    pub from: Option<chrono::DateTime<chrono::FixedOffset>>,
+    /// exclusive upper bound of the start time
+    pub to: Option<chrono::DateTime<chrono::FixedOffset>>,
+    /// only running (true) or only stopped (false) timers
+    pub open: Option<bool>,
+}
+
+#[derive(async_graphql::InputObject, Default)]
+pub struct TaskFilter {
+    /// case insensitive search in the task description
+    pub description_contains: Option<String>,
+    pub project_id: Option<i32>,
+    /// archived tasks are left out unless this is set
+    #[graphql(default)]
+    pub include_archived: bool,
+}
+
+#[derive(async_graphql::Enum, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, sqlx::Type)]
+#[sqlx(type_name = "employee_role", rename_all = "lowercase")]
+pub enum EmployeeRole {
+    Employee,
+    Manager,
+    Admin,
+}
+
+#[derive(async_graphql::SimpleObject, sqlx::FromRow, Clone)]
 #[graphql(complex)]
 pub struct Worktime {
     pub worktime_id: i32,
@@ -21,6 +79,10 @@ pub struct Worktime {
     #[graphql(skip)]
     pub timeduration: Option<types::PgInterval>,
     pub work_type: WorktimeType,
+    pub needs_review: bool,
+    pub comment: Option<String>,
+    /// false for time which must not be charged to the customer, breaks are never billed
+    pub billable: bool,
 }
 
 #[async_graphql::ComplexObject]
@@ -38,24 +100,173 @@ impl Worktime {
     }
 
     async fn task(&self, ctx: &async_graphql::Context<'_>) -> async_graphql::Result<Task> {
-        let pool = ctx.data::<sqlx::PgPool>()?;
-        service::task::get_task_by_id(self.task_id, pool)
-            .await
-            .map_err(async_graphql::Error::new_with_source)?
+        ctx.data::<DataLoader<TaskLoader>>()?
+            .load_one(self.task_id)
+            .await?
             .ok_or(async_graphql::Error::new(format!(
                 "Task with id '{}' could not be found.",
                 self.task_id
             )))
     }
+
+    async fn employee(&self, ctx: &async_graphql::Context<'_>) -> async_graphql::Result<Employee> {
+        ctx.data::<DataLoader<EmployeeLoader>>()?
+            .load_one(self.employee_id)
+            .await?
+            .ok_or(async_graphql::Error::new(format!(
+                "Employee with id '{}' could not be found.",
+                self.employee_id
+            )))
+    }
 }
 
-#[derive(async_graphql::SimpleObject)]
+#[derive(async_graphql::SimpleObject, sqlx::FromRow, Clone)]
+#[graphql(complex)]
 pub struct Task {
     pub task_id: i32,
     pub task_description: Option<String>,
+    /// tasks created before projects existed do not belong to one
+    pub project_id: Option<i32>,
+    /// no timers can be started on archived tasks
+    pub status: ArchiveStatus,
+    /// planned work and ride time
+    pub estimated_hours: Option<f64>,
+    /// planned amount in cents, unlike the estimate it triggers no alerts
+    pub budget_cents: Option<i64>,
 }
 
-#[derive(async_graphql::SimpleObject)]
+#[async_graphql::ComplexObject]
+impl Task {
+    async fn project(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+    ) -> async_graphql::Result<Option<Project>> {
+        let Some(project_id) = self.project_id else {
+            return Ok(None);
+        };
+
+        Ok(ctx
+            .data::<DataLoader<ProjectLoader>>()?
+            .load_one(project_id)
+            .await?)
+    }
+
+    /// Without assignments every employee may book on the task.
+    async fn assignments(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+    ) -> async_graphql::Result<Vec<TaskAssignment>> {
+        Ok(ctx
+            .data::<DataLoader<TaskAssignmentLoader>>()?
+            .load_one(self.task_id)
+            .await?
+            .unwrap_or_default())
+    }
+}
+
+/// Work and ride time booked on a task compared with its estimate.
+#[derive(async_graphql::SimpleObject, Clone, Debug)]
+pub struct TaskBudget {
+    pub task_id: i32,
+    pub task_description: Option<String>,
+    pub estimated_hours: f64,
+    pub budget_cents: Option<i64>,
+    pub booked_minutes: i64,
+    /// booked time in percent of the estimate, null for an estimate of 0 hours
+    pub percent_used: Option<f64>,
+    /// amount of the billable booked time with the applying billing rates
+    pub booked_cents: i64,
+    /// booked amount in percent of the budget, null without a budget
+    pub percent_of_budget: Option<f64>,
+}
+
+/// Sent once when the booked time of a task passes a threshold of its estimate.
+#[derive(async_graphql::SimpleObject, Clone, Debug)]
+pub struct TaskBudgetAlert {
+    pub task_id: i32,
+    pub task_description: Option<String>,
+    /// percent of the estimate, e.g. 80 or 100
+    pub threshold: i32,
+    pub estimated_hours: f64,
+    pub booked_minutes: i64,
+    pub crossed_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Either an employee or every member of a team may book on the task.
+#[derive(async_graphql::SimpleObject, Clone, Debug)]
+pub struct TaskAssignment {
+    pub assignment_id: i32,
+    pub task_id: i32,
+    pub employee_id: Option<i32>,
+    pub team_id: Option<i32>,
+}
+
+#[derive(async_graphql::Enum, Clone, Copy, PartialEq, Eq, Debug, sqlx::Type)]
+#[sqlx(type_name = "archive_status", rename_all = "lowercase")]
+pub enum ArchiveStatus {
+    Active,
+    Archived,
+}
+
+#[derive(async_graphql::SimpleObject, Clone, Debug)]
+#[graphql(complex)]
+pub struct Customer {
+    pub customer_id: i32,
+    pub name: String,
+    pub status: ArchiveStatus,
+}
+
+#[async_graphql::ComplexObject]
+impl Customer {
+    async fn projects(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        #[graphql(default)] include_archived: bool,
+    ) -> async_graphql::Result<Vec<Project>> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+
+        service::project::get_projects(Some(self.customer_id), include_archived, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+}
+
+#[derive(async_graphql::SimpleObject, Clone, Debug)]
+#[graphql(complex)]
+pub struct Project {
+    pub project_id: i32,
+    pub customer_id: i32,
+    pub name: String,
+    pub status: ArchiveStatus,
+    /// planned hours of the whole project
+    pub budget_hours: Option<f64>,
+    /// planned amount in cents
+    pub budget_cents: Option<i64>,
+}
+
+#[async_graphql::ComplexObject]
+impl Project {
+    async fn customer(&self, ctx: &async_graphql::Context<'_>) -> async_graphql::Result<Customer> {
+        ctx.data::<DataLoader<CustomerLoader>>()?
+            .load_one(self.customer_id)
+            .await?
+            .ok_or_else(|| async_graphql::Error::new("Customer not found"))
+    }
+
+    async fn tasks(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+        #[graphql(default)] include_archived: bool,
+    ) -> async_graphql::Result<Vec<Task>> {
+        let pool = ctx.data::<sqlx::PgPool>()?;
+
+        service::task::get_tasks_of_project(self.project_id, include_archived, pool)
+            .await
+            .map_err(async_graphql::Error::new_with_source)
+    }
+}
+
+#[derive(async_graphql::SimpleObject, Clone)]
 #[graphql(complex)]
 pub struct Employee {
     pub employee_id: i32,
@@ -65,6 +276,11 @@ pub struct Employee {
     #[graphql(skip)]
     pub weekly_time: Option<types::PgInterval>,
     pub address_id: i32,
+    pub role: EmployeeRole,
+    /// IANA timezone like 'Europe/Berlin', the company timezone is used if it is not set
+    pub timezone: Option<String>,
+    /// number of the employee in the payroll system, the employee id is used if it is not set
+    pub personnel_number: Option<String>,
 }
 
 #[async_graphql::ComplexObject]
@@ -93,3 +309,356 @@ impl Employee {
             .map_err(async_graphql::Error::new_with_source)
     }
 }
+
+#[derive(async_graphql::SimpleObject)]
+pub struct Notification {
+    pub notification_id: i32,
+    pub employee_id: i32,
+    pub message: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub read_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(async_graphql::SimpleObject)]
+pub struct Team {
+    pub team_id: i32,
+    pub name: String,
+    pub manager_id: Option<i32>,
+}
+
+/// Name, address, logo and colors of the company which are printed on every report.
+#[derive(async_graphql::SimpleObject, Clone)]
+#[graphql(complex)]
+pub struct CompanySettings {
+    pub name: String,
+    /// one line per line break
+    pub address: Option<String>,
+    #[graphql(skip)]
+    pub logo: Option<Vec<u8>>,
+    /// hex color like '#2d2d2d' of the report header
+    pub header_color: Option<String>,
+    /// hex color like '#f3f3f3' of the table headers
+    pub accent_color: Option<String>,
+}
+
+#[async_graphql::ComplexObject]
+impl CompanySettings {
+    async fn has_logo(&self) -> bool {
+        self.logo.is_some()
+    }
+}
+
+/// Checksum of a generated timesheet together with the employee and period it covers.
+pub struct IssuedReport {
+    pub employee_id: i32,
+    /// like in the download path, e.g. '2024-01'
+    pub period: String,
+    pub issued_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// A generated timesheet which can be downloaded again exactly as it was handed out.
+#[derive(async_graphql::SimpleObject, Clone, Debug)]
+#[graphql(complex)]
+pub struct ArchivedReport {
+    pub report_id: i32,
+    pub employee_id: i32,
+    /// like in the download path, e.g. '2024-01'
+    pub period: String,
+    /// the checksum printed on the report
+    pub hash: String,
+    pub size_bytes: i32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[async_graphql::ComplexObject]
+impl ArchivedReport {
+    /// Short-lived link to GET /reports/archive/{reportId}.pdf which works without an
+    /// authorization header.
+    async fn download_url(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+    ) -> async_graphql::Result<String> {
+        let employee_id = ctx.data::<i32>()?;
+
+        crate::download::create_archived_report_url(employee_id, self.report_id)
+            .map_err(|_| async_graphql::Error::new("Download link could not be created"))
+    }
+}
+
+/// The stored file of an archived report.
+pub struct ArchivedReportFile {
+    pub employee_id: i32,
+    pub period: String,
+    pub content: Vec<u8>,
+}
+
+/// Result of checking the checksum printed on a report against the current worktimes.
+#[derive(async_graphql::SimpleObject, serde::Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportVerification {
+    /// false if the worktimes of the period were changed after the report was generated
+    pub valid: bool,
+    pub employee_id: i32,
+    pub period: String,
+    pub issued_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(async_graphql::Enum, Clone, Copy, PartialEq, Eq, Debug, sqlx::Type)]
+#[sqlx(type_name = "delivery_status", rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    Sent,
+    Failed,
+}
+
+/// One attempt of the scheduler to mail the monthly report of an employee.
+#[derive(async_graphql::SimpleObject, Clone, Debug)]
+pub struct ReportDelivery {
+    pub delivery_id: i32,
+    pub employee_id: i32,
+    /// like in the download path, e.g. '2024-01'
+    pub period: String,
+    pub recipient: String,
+    pub status: DeliveryStatus,
+    /// why the report could not be sent
+    pub error: Option<String>,
+    pub attempted_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(async_graphql::SimpleObject, Clone, Debug, PartialEq)]
+pub struct TaskMinutes {
+    pub task_id: i32,
+    pub task_description: Option<String>,
+    /// work and ride time booked on the task
+    pub minutes: i64,
+}
+
+#[derive(async_graphql::SimpleObject, Debug)]
+pub struct EmployeeTotals {
+    pub employee_id: i32,
+    pub firstname: Option<String>,
+    pub lastname: Option<String>,
+    pub work_minutes: i64,
+    pub ride_minutes: i64,
+    pub break_minutes: i64,
+    /// target time of the approved absences within the period, which counts as worked
+    pub absence_minutes: i64,
+    /// weekly time of the employee spread over the weekdays of the period
+    pub target_minutes: i64,
+    /// work and ride time minus the breaks plus the absence time above the target time,
+    /// negative if less was worked
+    pub overtime_minutes: i64,
+    pub tasks: Vec<TaskMinutes>,
+}
+
+#[derive(async_graphql::SimpleObject, Debug)]
+pub struct TeamReport {
+    pub from: chrono::NaiveDate,
+    pub to: chrono::NaiveDate,
+    pub employees: Vec<EmployeeTotals>,
+    pub work_minutes: i64,
+    pub ride_minutes: i64,
+    pub break_minutes: i64,
+    pub absence_minutes: i64,
+    pub overtime_minutes: i64,
+    pub tasks: Vec<TaskMinutes>,
+}
+
+#[derive(async_graphql::SimpleObject, Debug)]
+pub struct TaskReportEntry {
+    pub employee_id: i32,
+    pub firstname: Option<String>,
+    pub lastname: Option<String>,
+    pub work_type: WorktimeType,
+    pub minutes: i64,
+}
+
+#[derive(async_graphql::SimpleObject, Debug)]
+pub struct TaskReport {
+    pub task_id: i32,
+    pub task_description: Option<String>,
+    pub from: chrono::NaiveDate,
+    pub to: chrono::NaiveDate,
+    /// minutes per employee and work type
+    pub entries: Vec<TaskReportEntry>,
+    pub total_minutes: i64,
+}
+
+/// An hourly rate for the worktimes matching every set scope, a rate without any scope is the
+/// default. The most specific one applies: a task rate before an employee rate before a work type
+/// rate.
+#[derive(async_graphql::SimpleObject, Clone, Debug)]
+pub struct BillingRate {
+    pub rate_id: i32,
+    pub employee_id: Option<i32>,
+    pub task_id: Option<i32>,
+    pub work_type: Option<WorktimeType>,
+    pub rate_cents: i64,
+}
+
+#[derive(async_graphql::SimpleObject, Debug)]
+pub struct BillingLine {
+    pub task_id: i32,
+    pub task_description: Option<String>,
+    pub billable_minutes: i64,
+    /// billable minutes which no rate applies to, they are not part of the amount
+    pub unrated_minutes: i64,
+    /// work and ride time which was flagged as not billable
+    pub non_billable_minutes: i64,
+    pub amount_cents: i64,
+}
+
+#[derive(async_graphql::SimpleObject, Debug)]
+pub struct BillingSummary {
+    /// all customers and tasks without a project if not set
+    pub customer_id: Option<i32>,
+    pub customer_name: Option<String>,
+    pub from: chrono::NaiveDate,
+    pub to: chrono::NaiveDate,
+    /// one line per task with booked work or ride time
+    pub lines: Vec<BillingLine>,
+    pub billable_minutes: i64,
+    pub unrated_minutes: i64,
+    pub amount_cents: i64,
+}
+
+/// Work and ride time of a day which was booked on one task.
+#[derive(async_graphql::SimpleObject, Clone, Debug, PartialEq)]
+pub struct DayTaskMinutes {
+    pub task_id: i32,
+    pub task_description: Option<String>,
+    pub work_minutes: i64,
+    pub ride_minutes: i64,
+    /// a timer on the task is still running, its minutes are counted up to the generation
+    pub in_progress: bool,
+}
+
+/// A break of a day, a break over midnight is listed on both days with their part of it.
+#[derive(async_graphql::SimpleObject, Clone, Debug, PartialEq)]
+pub struct DayBreak {
+    pub worktime_id: i32,
+    pub start_time: chrono::DateTime<chrono::Utc>,
+    /// null while the break is running
+    pub end_time: Option<chrono::DateTime<chrono::Utc>>,
+    pub minutes: i64,
+}
+
+#[derive(async_graphql::SimpleObject, Clone, Debug)]
+#[graphql(complex)]
+pub struct DayReport {
+    pub date: chrono::NaiveDate,
+    pub work_minutes: i64,
+    pub ride_minutes: i64,
+    pub break_minutes: i64,
+    /// sorted by task id, breaks are not booked on tasks
+    pub tasks: Vec<DayTaskMinutes>,
+    /// sorted by start time
+    pub breaks: Vec<DayBreak>,
+    /// a timer of the day is still running
+    pub in_progress: bool,
+}
+
+#[async_graphql::ComplexObject]
+impl DayReport {
+    /// work and ride time, the gross working time
+    async fn total_minutes(&self) -> i64 {
+        self.total()
+    }
+
+    /// gross working time minus the breaks
+    async fn net_minutes(&self) -> i64 {
+        self.net()
+    }
+}
+
+impl DayReport {
+    pub fn total(&self) -> i64 {
+        self.work_minutes + self.ride_minutes
+    }
+
+    pub fn net(&self) -> i64 {
+        (self.total() - self.break_minutes).max(0)
+    }
+}
+
+/// Times of one employee per day of a month or any other period, running timers are counted
+/// with the minutes up to the generation of the report.
+#[derive(async_graphql::SimpleObject, Clone, Debug)]
+#[graphql(complex)]
+pub struct MonthReport {
+    pub employee_id: i32,
+    pub from: chrono::NaiveDate,
+    pub to: chrono::NaiveDate,
+    /// every day of the period, also the ones without worktimes
+    pub days: Vec<DayReport>,
+    pub work_minutes: i64,
+    pub ride_minutes: i64,
+    pub break_minutes: i64,
+    /// running timers are counted up to this time
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+    pub in_progress: bool,
+}
+
+#[async_graphql::ComplexObject]
+impl MonthReport {
+    /// work and ride time, the gross working time
+    async fn total_minutes(&self) -> i64 {
+        self.total()
+    }
+
+    /// gross working time minus the breaks
+    async fn net_minutes(&self) -> i64 {
+        self.net()
+    }
+}
+
+impl MonthReport {
+    pub fn total(&self) -> i64 {
+        self.work_minutes + self.ride_minutes
+    }
+
+    pub fn net(&self) -> i64 {
+        (self.total() - self.break_minutes).max(0)
+    }
+}
+
+#[derive(async_graphql::Enum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TimerEventKind {
+    Started,
+    Stopped,
+    Updated,
+}
+
+#[derive(async_graphql::SimpleObject, Clone)]
+pub struct TimerEvent {
+    pub kind: TimerEventKind,
+    pub worktime: Worktime,
+}
+
+#[derive(async_graphql::Enum, Clone, Copy, PartialEq, Eq, Debug, sqlx::Type)]
+#[sqlx(type_name = "absence_type", rename_all = "lowercase")]
+pub enum AbsenceType {
+    Vacation,
+    Sickness,
+    Other,
+}
+
+#[derive(async_graphql::Enum, Clone, Copy, PartialEq, Eq, Debug, sqlx::Type)]
+#[sqlx(type_name = "absence_status", rename_all = "lowercase")]
+pub enum AbsenceStatus {
+    Requested,
+    Approved,
+    Rejected,
+}
+
+/// Whole days off of an employee, requested by the employee and approved or rejected by a manager.
+#[derive(async_graphql::SimpleObject, Clone, Debug)]
+pub struct Absence {
+    pub absence_id: i32,
+    pub employee_id: i32,
+    pub absence_type: AbsenceType,
+    pub start_date: chrono::NaiveDate,
+    /// inclusive
+    pub end_date: chrono::NaiveDate,
+    pub status: AbsenceStatus,
+    pub comment: Option<String>,
+}
@@ -1,17 +1,33 @@
 use crate::models::Worktime;
-use crate::service::{task, worktime};
+use crate::service::{archived_report, employee, report, worktime};
+use crate::time_utils;
 use anyhow::{anyhow, Context};
 use async_graphql::Enum;
 use base64::encode;
-use chrono::{DateTime, Datelike, Local, NaiveDate};
+use chrono::{Datelike, NaiveDate, Utc};
+use chrono_tz::Tz;
 use printpdf::*;
-use sqlx::postgres::types::PgInterval;
+use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::io::BufWriter;
 use std::io::Cursor;
 
-#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+use branding::Branding;
+pub use locale::Locale;
+pub use period::ReportPeriod;
+
+pub mod billing;
+pub mod branding;
+pub mod locale;
+pub mod period;
+mod table;
+pub mod task_report;
+pub mod team;
+pub mod verification;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum HeaderColor {
     TelekomFunk,
     HardworkingBrown,
@@ -19,6 +35,7 @@ pub enum HeaderColor {
     GrassyFields,
     BaumarktRot,
     SchmidtBrand,
+    #[default]
     DefaultGrey,
 }
 impl HeaderColor {
@@ -35,20 +52,6 @@ impl HeaderColor {
     }
 }
 
-fn add_month(given_month: &str) -> anyhow::Result<String> {
-    let year: i32 = given_month[0..4]
-        .parse()
-        .context("cannot extract year of given month")?;
-    let month: u32 = given_month[5..7]
-        .parse()
-        .context("cannot extract month of given month")?;
-
-    let new_month = if month == 12 { 1 } else { month + 1 };
-
-    let new_year = if month == 12 { year + 1 } else { year };
-    Ok(format!("{:04}-{:02}", new_year, new_month))
-}
-
 fn truncate_string(input: &str, max_length: usize) -> String {
     if input.chars().count() > max_length {
         let truncated: String = input.chars().take(max_length).collect();
@@ -58,151 +61,111 @@ fn truncate_string(input: &str, max_length: usize) -> String {
     }
 }
 
-fn get_weekday_abbreviation(date: chrono::NaiveDate) -> String {
-    match date.weekday() {
-        chrono::Weekday::Mon => "Mo".to_string(),
-        chrono::Weekday::Tue => "Di".to_string(),
-        chrono::Weekday::Wed => "Mi".to_string(),
-        chrono::Weekday::Thu => "Do".to_string(),
-        chrono::Weekday::Fri => "Fr".to_string(),
-        chrono::Weekday::Sat => "Sa".to_string(),
-        chrono::Weekday::Sun => "So".to_string(),
-    }
+fn get_weekday_abbreviation(date: chrono::NaiveDate, locale: Locale) -> String {
+    locale.weekday_abbreviation(date.weekday()).to_string()
 }
 
-fn add_durations(d1: &PgInterval, d2: &PgInterval) -> PgInterval {
-    PgInterval {
-        months: d1.months + d2.months,
-        days: d1.days + d2.days,
-        microseconds: d1.microseconds + d2.microseconds,
-    }
-}
-
-async fn generate_schedule(
-    worktimes: Vec<Worktime>,
+// worktimes of the employee which overlap the month, in the timezone of the employee
+pub(crate) async fn get_month_worktimes(
     year: i32,
     month: u32,
+    timezone: &Tz,
     database_pool: &PgPool,
-) -> anyhow::Result<Vec<Vec<String>>> {
-    let mut schedule: Vec<Vec<String>> = Vec::new();
-
-    // Determine the number of days in the month
-    let num_days = NaiveDate::from_ymd_opt(year, month, 1)
-        .and_then(|date| date.with_month(month + 1))
-        .map(|next_month| (next_month - chrono::Duration::days(1)).day())
-        .unwrap_or(31);
-
-    for day in 1..=num_days {
-        if let Some(date) = NaiveDate::from_ymd_opt(year, month, day) {
-            let mut day_entry = vec![get_weekday_abbreviation(date)];
-            let mut total_duration = PgInterval {
-                months: 0,
-                days: 0,
-                microseconds: 0,
-            };
-
-            // Filter worktimes for the current day
-            for worktime in worktimes
-                .iter()
-                .filter(|w| w.start_time.date_naive() == date)
-            {
-                let task_description_raw =
-                    match task::get_task_by_id(worktime.task_id, database_pool).await? {
-                        Some(task) => task.task_description,
-                        None => None,
-                    };
-
-                let task_description =
-                    task_description_raw.unwrap_or("No description available".to_string());
-
-                let truncated_task_description = truncate_string(&task_description, 14);
-
-                // Handle the duration
-                if let Some(d) = &worktime.timeduration {
-                    let formatted_duration = format_duration(d.clone());
-                    day_entry.push(format!(
-                        "{:?}, {}",
-                        worktime.work_type, truncated_task_description
-                    ));
-                    day_entry.push(formatted_duration);
-
-                    // Add to total duration
-                    total_duration = add_durations(&total_duration, d);
-                }
-            }
-
-            // Add total duration as the last entry
-            if total_duration.microseconds > 0 {
-                let formatted_total = format_duration(total_duration);
-                day_entry.push(format!("Total: {}", formatted_total));
-            }
+    employee_id: &i32,
+) -> anyhow::Result<Vec<Worktime>> {
+    // Datetime boundaries of the month in the timezone of the employee
+    let (datetime_start, datetime_end) = time_utils::month_bounds(year, month, timezone)
+        .context("given month has the wrong format")?;
 
-            // Add the day's entry to the schedule
-            schedule.push(day_entry);
-        } else {
-            // Return an error if the date couldn't be generated
-            return Err(anyhow!(
-                "Failed to generate schedule for year {} and month {} at day {}.",
-                year,
-                month,
-                day
-            ));
-        }
-    }
+    // Query for worktimes within the given date range
+    let worktimes =
+        worktime::get_timers_overlapping(employee_id, datetime_start, datetime_end, database_pool)
+            .await?;
 
-    Ok(schedule)
+    Ok(worktimes)
 }
 
-fn format_duration(interval: PgInterval) -> String {
-    let total_microseconds = interval.microseconds;
-
-    // Convert microseconds to total seconds
-    let total_seconds = total_microseconds / 1_000_000;
+// worktimes of the employee which overlap the days of the period
+async fn get_period_worktimes(
+    from: NaiveDate,
+    to: NaiveDate,
+    timezone: &Tz,
+    database_pool: &PgPool,
+    employee_id: &i32,
+) -> anyhow::Result<Vec<Worktime>> {
+    // Datetime boundaries of the days in the timezone of the employee
+    let (datetime_start, _) = time_utils::day_bounds(from, timezone);
+    let (_, datetime_end) = time_utils::day_bounds(to, timezone);
 
-    // Calculate hours and minutes
-    let hours = total_seconds / 3600;
-    let minutes = (total_seconds % 3600) / 60;
+    let worktimes =
+        worktime::get_timers_overlapping(employee_id, datetime_start, datetime_end, database_pool)
+            .await?;
 
-    // Return formatted string
-    format!("{:02}:{:02}", hours, minutes)
+    Ok(worktimes)
 }
 
-async fn get_month_times(
-    given_month: &str,
-    database_pool: &PgPool, // Pass the database_pool by reference
+pub async fn generate_pdf(
+    period: ReportPeriod,
+    color_for_header: HeaderColor,
+    locale: Locale,
+    database_pool: &PgPool,
     employee_id: &i32,
-) -> anyhow::Result<Vec<Vec<String>>> {
-    let next_month = add_month(given_month)?;
-    let year: i32 = given_month[0..4].parse()?;
-    let month: u32 = given_month[5..7].parse()?;
-
-    // Construct datetime boundaries for the query
-    let datetime_start_stc = format!("{}-01T00:00:00Z", given_month);
-    let datetime_start = DateTime::parse_from_rfc3339(&datetime_start_stc)?;
+) -> anyhow::Result<String> {
+    let pdf_buffer =
+        render_pdf(period, color_for_header, locale, database_pool, employee_id).await?;
 
-    let datetime_end_stc = format!("{}-01T00:00:00Z", next_month);
-    let datetime_end = DateTime::parse_from_rfc3339(&datetime_end_stc)?;
+    Ok(encode(pdf_buffer))
+}
 
-    // Query for worktimes within the given date range
-    let worktimes =
-        worktime::get_timers_in_boundary(employee_id, datetime_start, datetime_end, database_pool)
-            .await?;
+// checks the 'YYYY-MM' format before the month is sliced into year and month
+pub fn validate_month(given_month: &str) -> anyhow::Result<NaiveDate> {
+    if given_month.len() != 7 {
+        return Err(anyhow!("given month has the wrong format"));
+    }
+    NaiveDate::parse_from_str(&format!("{}-01", given_month), "%Y-%m-%d")
+        .context("given month has the wrong format")
+}
 
-    // Generate schedule, handling any potential error
-    let schedule = generate_schedule(worktimes, year, month, database_pool).await?;
+/// Renders the report and keeps the file in the archive, so exactly this file can be downloaded
+/// again later on. Provisional reports with a running timer are not archived.
+pub async fn render_pdf(
+    period: ReportPeriod,
+    color_for_header: HeaderColor,
+    locale: Locale,
+    database_pool: &PgPool,
+    employee_id: &i32,
+) -> anyhow::Result<Vec<u8>> {
+    let (pdf, hash) =
+        render_report(period, color_for_header, locale, database_pool, employee_id).await?;
+    if let Some(hash) = hash {
+        archived_report::save_archived_report(
+            *employee_id,
+            &period.to_string(),
+            &hash,
+            &pdf,
+            database_pool,
+        )
+        .await?;
+    }
 
-    Ok(schedule)
+    Ok(pdf)
 }
 
-pub async fn generate_pdf(
-    given_month: String,
+// the document is not Send, so it has to be gone before the file is archived
+async fn render_report(
+    period: ReportPeriod,
     color_for_header: HeaderColor,
+    locale: Locale,
     database_pool: &PgPool,
     employee_id: &i32,
-) -> anyhow::Result<String> {
+) -> anyhow::Result<(Vec<u8>, Option<String>)> {
+    let (from, to) = period.bounds()?;
+    let catalog = locale.catalog();
+
     let pdf_height = 297.0;
     let pdf_width = 210.0;
     let zero = 0.0;
+    let branding = Branding::load(database_pool).await?;
     let employee_info = sqlx::query!(
         "SELECT firstname, lastname, email FROM employee  WHERE employee_id = $1",
         employee_id
@@ -213,10 +176,25 @@ pub async fn generate_pdf(
     let first_name = employee_info.firstname.unwrap_or(String::from(""));
     let last_name = employee_info.lastname.unwrap_or(String::from(""));
     let email = employee_info.email;
-    let used_schedule = get_month_times(&given_month, database_pool, employee_id).await?;
+    let timezone = employee::get_timezone(employee_id, database_pool).await?;
+    let worktimes = get_period_worktimes(from, to, &timezone, database_pool, employee_id).await?;
+    let hash = verification::issue_report(*employee_id, &period, &worktimes, database_pool).await?;
+    let report = report::get_month_report_of_worktimes(
+        *employee_id,
+        &worktimes,
+        from,
+        to,
+        &timezone,
+        database_pool,
+    )
+    .await?;
 
-    let (doc, page1, layer1) =
-        PdfDocument::new("Zeiterfassungen", Mm(pdf_width), Mm(pdf_height), "Layer 1");
+    let (doc, page1, layer1) = PdfDocument::new(
+        catalog.document_title,
+        Mm(pdf_width),
+        Mm(pdf_height),
+        "Layer 1",
+    );
 
     let font_bold = doc
         .add_external_font(std::io::Cursor::new(include_bytes!(
@@ -236,12 +214,14 @@ pub async fn generate_pdf(
 
     let mut current_layer = doc.get_page(page1).get_layer(layer1);
 
-    let mut month_time_work = 0;
-    let mut month_time_ride = 0;
-
     let pdf_header_height_start = 217.0;
 
-    let header_color = color_for_header.to_rgb();
+    // the brand color of the company replaces the default
+    let header_color = match color_for_header {
+        HeaderColor::DefaultGrey => branding.header_color_or(color_for_header.to_rgb()),
+        color => color.to_rgb(),
+    };
+    let accent_color = branding.accent_color_or(Color::Rgb(Rgb::new(0.952, 0.952, 0.952, None)));
 
     let rectangle = create_rectangle(zero, pdf_width, pdf_height, pdf_header_height_start);
     current_layer.set_fill_color(header_color);
@@ -250,52 +230,33 @@ pub async fn generate_pdf(
     // Header Text
     let text_color = Color::Rgb(Rgb::new(1.0, 1.0, 1.0, None));
     current_layer.set_fill_color(text_color);
-    current_layer.use_text("Zeitenübersicht", 24.0, Mm(21.0), Mm(271.0), &font_medium);
+    current_layer.use_text(catalog.title, 24.0, Mm(21.0), Mm(271.0), &font_medium);
 
     // First Name
-    current_layer.use_text("Vorname:", 12.0, Mm(21.0), Mm(244.0), &font_light);
+    current_layer.use_text(catalog.firstname, 12.0, Mm(21.0), Mm(244.0), &font_light);
     current_layer.use_text(first_name, 12.0, Mm(60.0), Mm(244.0), &font_light);
 
     // Last Name
-    current_layer.use_text("Nachname:", 12.0, Mm(21.0), Mm(239.0), &font_light);
+    current_layer.use_text(catalog.lastname, 12.0, Mm(21.0), Mm(239.0), &font_light);
     current_layer.use_text(last_name, 12.0, Mm(60.0), Mm(239.0), &font_light);
 
     // Email
-    current_layer.use_text("Email-Adresse:", 12.0, Mm(21.0), Mm(234.0), &font_light);
+    current_layer.use_text(catalog.email, 12.0, Mm(21.0), Mm(234.0), &font_light);
     current_layer.use_text(email, 12.0, Mm(60.0), Mm(234.0), &font_light);
 
     // Day on which the pdf was requested
-    let current_date = Local::now().date_naive();
-    let formatted_date = current_date.format("%d.%m.%Y").to_string();
-
-    // Month of the requested
-    let given_date = NaiveDate::parse_from_str(&format!("{}-01", given_month), "%Y-%m-%d")
-        .context("given month has the wrong format")?;
-    let given_date_year = given_date.year();
-    let given_date_month = given_date.month();
-
-    // German schedaddles
-    let german_months = [
-        "Januar",
-        "Februar",
-        "März",
-        "April",
-        "Mai",
-        "Juni",
-        "Juli",
-        "August",
-        "September",
-        "Oktober",
-        "November",
-        "Dezember",
-    ];
-    let month_name = german_months[(given_date_month - 1) as usize];
-    let formatted_given_month = format!("{} {}", month_name, given_date_year);
+    let current_date = time_utils::local_date(Utc::now(), &timezone);
+    let formatted_date = locale.format_date(current_date);
 
-    // Month
-    current_layer.use_text("Monat:", 12.0, Mm(148.0), Mm(239.0), &font_light);
+    // Period
+    let period_label = if period.is_month() {
+        catalog.month
+    } else {
+        catalog.period
+    };
+    current_layer.use_text(period_label, 12.0, Mm(148.0), Mm(239.0), &font_light);
     current_layer.use_text(
-        formatted_given_month,
+        period.label(locale),
         12.0,
         Mm(168.0),
         Mm(239.0),
@@ -303,17 +264,16 @@ pub async fn generate_pdf(
     );
 
     // Date
-    current_layer.use_text("Datum:", 12.0, Mm(148.0), Mm(234.0), &font_light);
+    current_layer.use_text(catalog.date, 12.0, Mm(148.0), Mm(234.0), &font_light);
     current_layer.use_text(formatted_date, 12.0, Mm(168.0), Mm(234.0), &font_light);
 
-    // Schmidt's Handwerksbetrieb
-    current_layer.use_text("Schmidt's", 12.0, Mm(170.0), Mm(275.0), &font_light);
-    current_layer.use_text("Handwerksbetrieb", 12.0, Mm(152.0), Mm(270.0), &font_light);
+    // Company name, address and logo
+    branding.draw(&current_layer, &font_light);
 
     let pdf_body_x_left = 21.0;
     let pdf_body_x_right = 188.0;
     let pdf_body_y_top = 201.0;
-    let pdf_body_y_bottom = 150.0;
+    let pdf_body_y_bottom = 141.0;
 
     let rectangle = create_rectangle(
         pdf_body_x_left,
@@ -321,66 +281,61 @@ pub async fn generate_pdf(
         pdf_body_y_top,
         pdf_body_y_bottom,
     );
-    let header_color = Color::Rgb(Rgb::new(0.952, 0.952, 0.952, None));
-    current_layer.set_fill_color(header_color);
+    current_layer.set_fill_color(accent_color.clone());
     current_layer.add_shape(rectangle);
 
     // Body Info Text
     let text_color = Color::Rgb(Rgb::new(0.0, 0.0, 0.0, None));
     current_layer.set_fill_color(text_color);
 
-    let mut days_in_month = 0;
-
-    // Iterate Days of Month
-    for day_entry in used_schedule.iter() {
-        days_in_month += 1;
-
-        // Combined total work and ride times for that day (for the header)
-        let mut combined_work_time: u64 = 0;
-        let mut combined_ride_time: u64 = 0;
-
-        // first entry of each entry is the abbreviation of the weekday, therefore skiped
-        let mut iter = day_entry.iter().skip(1);
-        let mut task_times: HashMap<String, (u64, u64)> = HashMap::new();
-
-        // Entries of one Day
-        while let Some(entry) = iter.next() {
-            let task_id = entry.split(", ").nth(1).unwrap_or("").to_string();
-            let time_str = iter
-                .next()
-                .map(|s| s.to_string())
-                .unwrap_or("00:00".to_string());
-            let time_minutes = parse_time_to_minutes(&time_str);
-
-            if entry.starts_with("Work") {
-                let task_entry = task_times.entry(task_id.clone()).or_insert((0, 0));
-                task_entry.0 += time_minutes;
-                combined_work_time += time_minutes;
-                month_time_work += combined_work_time;
-            } else if entry.starts_with("Ride") {
-                let task_entry = task_times.entry(task_id.clone()).or_insert((0, 0));
-                task_entry.1 += time_minutes;
-                combined_ride_time += time_minutes;
-                month_time_ride += combined_ride_time;
-            }
-        }
+    let month_time_work = report.work_minutes as u64;
+    let month_time_ride = report.ride_minutes as u64;
+    let month_time_break = report.break_minutes as u64;
+    let days_in_month = report.days.len() as u64;
+    // work, ride and break minutes and days of every month for the yearly summary
+    let mut totals_per_month: BTreeMap<u32, (u64, u64, u64, u64)> = BTreeMap::new();
+    for day in &report.days {
+        let month_totals = totals_per_month.entry(day.date.month()).or_default();
+        month_totals.0 += day.work_minutes as u64;
+        month_totals.1 += day.ride_minutes as u64;
+        month_totals.2 += day.break_minutes as u64;
+        month_totals.3 += 1;
     }
 
+    let (work_label, ride_label, total_label, break_label, net_label) = if period.is_month() {
+        (
+            catalog.work_this_month,
+            catalog.ride_this_month,
+            catalog.total_this_month,
+            catalog.break_this_month,
+            catalog.net_this_month,
+        )
+    } else {
+        (
+            catalog.work_in_period,
+            catalog.ride_in_period,
+            catalog.total_in_period,
+            catalog.break_in_period,
+            catalog.net_in_period,
+        )
+    };
+
     // Worktime
-    current_layer.use_text(
-        "Arbeitszeit diesen Monat:",
-        10.0,
-        Mm(29.0),
-        Mm(192.0),
-        &font_medium,
-    );
+    current_layer.use_text(work_label, 10.0, Mm(29.0), Mm(192.0), &font_medium);
     let month_time_work_text = format_minutes_as_time(month_time_work);
     let parts: Vec<&str> = month_time_work_text.split(':').collect();
     let formatted_text = format!("{}h {}m", parts[0], parts[1]);
     current_layer.use_text(formatted_text, 10.0, Mm(130.0), Mm(192.0), &font_bold);
+    current_layer.use_text(
+        locale.format_hours(month_time_work),
+        10.0,
+        Mm(160.0),
+        Mm(192.0),
+        &font_light,
+    );
 
     current_layer.use_text(
-        "durchschnittliche Zeit pro Tag:",
+        catalog.average_per_day,
         10.0,
         Mm(29.0),
         Mm(187.0),
@@ -390,22 +345,30 @@ pub async fn generate_pdf(
     let parts: Vec<&str> = month_time_work_average_text.split(':').collect();
     let formatted_text = format!("{}h {}m", parts[0], parts[1]);
     current_layer.use_text(formatted_text, 10.0, Mm(130.0), Mm(187.0), &font_bold);
-
-    // Traveltime
     current_layer.use_text(
-        "Fahrstunden diesen Monat:",
+        locale.format_hours(month_time_work / days_in_month),
         10.0,
-        Mm(29.0),
-        Mm(177.0),
-        &font_medium,
+        Mm(160.0),
+        Mm(187.0),
+        &font_light,
     );
+
+    // Traveltime
+    current_layer.use_text(ride_label, 10.0, Mm(29.0), Mm(177.0), &font_medium);
     let month_time_ride_text = format_minutes_as_time(month_time_ride);
     let parts: Vec<&str> = month_time_ride_text.split(':').collect();
     let formatted_text = format!("{}h {}m", parts[0], parts[1]);
     current_layer.use_text(formatted_text, 10.0, Mm(130.0), Mm(177.0), &font_bold);
+    current_layer.use_text(
+        locale.format_hours(month_time_ride),
+        10.0,
+        Mm(160.0),
+        Mm(177.0),
+        &font_light,
+    );
 
     current_layer.use_text(
-        "durchschnittliche Zeit pro Tag:",
+        catalog.average_per_day,
         10.0,
         Mm(29.0),
         Mm(172.0),
@@ -415,22 +378,30 @@ pub async fn generate_pdf(
     let parts: Vec<&str> = month_time_ride_average_text.split(':').collect();
     let formatted_text = format!("{}h {}m", parts[0], parts[1]);
     current_layer.use_text(formatted_text, 10.0, Mm(130.0), Mm(172.0), &font_bold);
-
-    // Timetime
     current_layer.use_text(
-        "Gesamtzeit diesen Monat:",
+        locale.format_hours(month_time_ride / days_in_month),
         10.0,
-        Mm(29.0),
-        Mm(162.0),
-        &font_medium,
+        Mm(160.0),
+        Mm(172.0),
+        &font_light,
     );
+
+    // Timetime
+    current_layer.use_text(total_label, 10.0, Mm(29.0), Mm(162.0), &font_medium);
     let month_time_combined_text = format_minutes_as_time(month_time_ride + month_time_work);
     let parts: Vec<&str> = month_time_combined_text.split(':').collect();
     let formatted_text = format!("{}h {}m", parts[0], parts[1]);
     current_layer.use_text(formatted_text, 10.0, Mm(130.0), Mm(162.0), &font_bold);
+    current_layer.use_text(
+        locale.format_hours(month_time_ride + month_time_work),
+        10.0,
+        Mm(160.0),
+        Mm(162.0),
+        &font_light,
+    );
 
     current_layer.use_text(
-        "durchschnittliche Gesamtzeit pro Tag:",
+        catalog.total_average_per_day,
         10.0,
         Mm(29.0),
         Mm(157.0),
@@ -441,15 +412,57 @@ pub async fn generate_pdf(
     let parts: Vec<&str> = month_time_combined_average_text.split(':').collect();
     let formatted_text = format!("{}h {}m", parts[0], parts[1]);
     current_layer.use_text(formatted_text, 10.0, Mm(130.0), Mm(157.0), &font_bold);
-
     current_layer.use_text(
-        "Gesamtübersicht erfasster Zeiten:",
-        14.0,
-        Mm(21.0),
-        Mm(135.0),
-        &font_medium,
+        locale.format_hours((month_time_work + month_time_ride) / days_in_month),
+        10.0,
+        Mm(160.0),
+        Mm(157.0),
+        &font_light,
     );
 
+    // Breaks and the net time without them
+    for (label, minutes, y) in [
+        (break_label, month_time_break, 151.0),
+        (net_label, report.net() as u64, 146.0),
+    ] {
+        current_layer.use_text(label, 10.0, Mm(29.0), Mm(y), &font_medium);
+        let parts = format_minutes_as_time(minutes);
+        let (hours, minutes_of_hour) = parts.split_once(':').unwrap_or_default();
+        current_layer.use_text(
+            format!("{}h {}m", hours, minutes_of_hour),
+            10.0,
+            Mm(130.0),
+            Mm(y),
+            &font_bold,
+        );
+        current_layer.use_text(
+            locale.format_hours(minutes),
+            10.0,
+            Mm(160.0),
+            Mm(y),
+            &font_light,
+        );
+    }
+
+    current_layer.use_text(catalog.overview, 14.0, Mm(21.0), Mm(135.0), &font_medium);
+
+    // Running timers are counted up to the generation of the report
+    if report.in_progress {
+        let generated_at = report.generated_at.with_timezone(&timezone);
+        current_layer.use_text(
+            format!(
+                "* {} {} {}",
+                catalog.in_progress,
+                locale.format_date(generated_at.date_naive()),
+                generated_at.format("%H:%M")
+            ),
+            9.0,
+            Mm(110.0),
+            Mm(135.0),
+            &font_light,
+        );
+    }
+
     let pdf_table_header_x_left = 21.0;
     let pdf_table_header_x_right = 188.0;
     let pdf_table_header_y_top = 127.0;
@@ -461,222 +474,231 @@ pub async fn generate_pdf(
         pdf_table_header_y_top,
         pdf_table_header_y_bottom,
     );
-    let header_color = Color::Rgb(Rgb::new(0.952, 0.952, 0.952, None));
-    current_layer.set_fill_color(header_color);
+    current_layer.set_fill_color(accent_color.clone());
     current_layer.add_shape(rectangle);
 
     let text_color = Color::Rgb(Rgb::new(0.0, 0.0, 0.0, None));
     current_layer.set_fill_color(text_color);
 
-    let column_widths = [Mm(25.0), Mm(55.0), Mm(90.0), Mm(120.0), Mm(155.0)];
+    let column_widths = [
+        Mm(25.0),
+        Mm(52.0),
+        Mm(77.0),
+        Mm(102.0),
+        Mm(127.0),
+        Mm(150.0),
+    ];
     let column_heights = Mm(120.0);
 
-    current_layer.use_text("Datum", 11.0, column_widths[0], column_heights, &font_bold);
-    current_layer.use_text(
-        "Arbeitszeit",
-        11.0,
-        column_widths[1],
-        column_heights,
-        &font_bold,
-    );
-    current_layer.use_text(
-        "Fahrzeit",
-        11.0,
-        column_widths[2],
-        column_heights,
-        &font_bold,
-    );
-    current_layer.use_text(
-        "Gesamtzeit",
-        11.0,
-        column_widths[3],
-        column_heights,
-        &font_bold,
-    );
-    current_layer.use_text(
-        "Aufgabe",
-        11.0,
-        column_widths[4],
-        column_heights,
-        &font_bold,
-    );
+    for (column, width) in catalog.columns.iter().zip(column_widths) {
+        current_layer.use_text(*column, 11.0, width, column_heights, &font_bold);
+    }
 
     let font_size = 10.0;
     let line_height = Mm(8.0);
     let mut current_y_pos = Mm(110.0) - Mm(3.0);
     let mut current_page = 1;
 
-    // Iterate Days of Month
-    for (day_num, day_entry) in used_schedule.iter().enumerate() {
-        // Combined total work and ride times for that day (for the header)
-        let mut combined_work_time: u64 = 0;
-        let mut combined_ride_time: u64 = 0;
-        let mut combined_total_time: u64 = 0;
+    // Iterate Days of the period, every day has a row followed by one row per task and break,
+    // running timers are marked with a star
+    let star = |in_progress: bool| if in_progress { " *" } else { "" };
+    for day in &report.days {
+        let mut rows = vec![(
+            [
+                format!(
+                    "{}{}",
+                    locale.format_day(
+                        &get_weekday_abbreviation(day.date, locale),
+                        day.date,
+                        !period.is_month(),
+                    ),
+                    star(day.in_progress)
+                ),
+                format_minutes_as_time(day.work_minutes as u64),
+                format_minutes_as_time(day.ride_minutes as u64),
+                format_minutes_as_time(day.break_minutes as u64),
+                format_minutes_as_time(day.net() as u64),
+                String::new(),
+            ],
+            false,
+        )];
+        rows.extend(day.tasks.iter().map(|task| {
+            let task_description = task
+                .task_description
+                .as_deref()
+                .unwrap_or(catalog.no_description);
+            (
+                [
+                    String::new(),
+                    format_minutes_as_time(task.work_minutes as u64),
+                    format_minutes_as_time(task.ride_minutes as u64),
+                    String::new(),
+                    format_minutes_as_time((task.work_minutes + task.ride_minutes) as u64),
+                    format!(
+                        "{}{}",
+                        truncate_string(task_description, 14),
+                        star(task.in_progress)
+                    ),
+                ],
+                true,
+            )
+        }));
+        rows.extend(day.breaks.iter().map(|pause| {
+            let local_time = |time: chrono::DateTime<Utc>| {
+                time.with_timezone(&timezone).format("%H:%M").to_string()
+            };
+            (
+                [
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                    format_minutes_as_time(pause.minutes as u64),
+                    String::new(),
+                    format!(
+                        "{} {}-{}{}",
+                        catalog.break_entry,
+                        local_time(pause.start_time),
+                        pause.end_time.map(local_time).unwrap_or_default(),
+                        star(pause.end_time.is_none())
+                    ),
+                ],
+                true,
+            )
+        }));
+
+        for (cells, is_task) in rows {
+            // New Page
+            if current_y_pos <= Mm(25.0) {
+                let current_page_text = current_page.to_string();
+                current_layer.use_text(current_page_text, 13.0, Mm(185.0), Mm(14.0), &font_medium);
 
-        // New Page
-        if current_y_pos <= Mm(25.0) {
-            let current_page_text = current_page.to_string();
-            current_layer.use_text(current_page_text, 13.0, Mm(185.0), Mm(14.0), &font_medium);
+                let (new_layer, new_y_pos, new_page) = add_new_page(
+                    &doc,
+                    current_page,
+                    &font_bold,
+                    &catalog.columns,
+                    &column_widths,
+                    &accent_color,
+                    line_height,
+                );
 
-            let (new_layer, _new_y_pos, new_page) =
-                add_new_page(&doc, current_page, &font_bold, &column_widths, line_height);
+                current_layer = new_layer;
+                current_y_pos = new_y_pos;
+                current_page = new_page;
+            }
 
-            current_layer = new_layer;
-            current_y_pos = Mm(273.0) - line_height;
-            current_page = new_page;
-        }
+            // the rows of the tasks and breaks are highlighted below the row of their day
+            if is_task {
+                let rectangle = create_rectangle(
+                    21.0,
+                    188.0,
+                    current_y_pos.0 + line_height.0 - 3.0,
+                    current_y_pos.0 - 3.0,
+                );
+                current_layer.set_fill_color(accent_color.clone());
+                current_layer.add_shape(rectangle);
+                current_layer.set_fill_color(Color::Rgb(Rgb::new(0.0, 0.0, 0.0, None)));
+            }
 
-        // first entry of each entry is the abbreviation of the weekday, therefore skiped
-        let mut iter = day_entry.iter().skip(1);
-        let mut task_times: HashMap<String, (u64, u64)> = HashMap::new();
-
-        // Entries of one Day
-        while let Some(entry) = iter.next() {
-            let task_id = entry.split(", ").nth(1).unwrap_or("").to_string();
-            let time_str = iter
-                .next()
-                .map(|s| s.to_string())
-                .unwrap_or("00:00".to_string());
-            let time_minutes = parse_time_to_minutes(&time_str);
-
-            if entry.starts_with("Work") {
-                let task_entry = task_times.entry(task_id.clone()).or_insert((0, 0));
-                task_entry.0 += time_minutes; // Add to work_time
-                combined_work_time += time_minutes;
-                month_time_work += combined_work_time;
-            } else if entry.starts_with("Ride") {
-                let task_entry = task_times.entry(task_id.clone()).or_insert((0, 0));
-                task_entry.1 += time_minutes; // Add to ride_time
-                combined_ride_time += time_minutes;
-                month_time_ride += combined_ride_time;
-            } else if entry.starts_with("Total:") {
-                combined_total_time += parse_time_to_minutes(&entry.replacen("Total: ", "", 1));
+            for (cell, width) in cells.iter().zip(column_widths) {
+                current_layer.use_text(cell, font_size, width, current_y_pos, &font_medium);
             }
-        }
 
-        // Display the header for the day
-        let col1 = format!("{}, {:02}.", day_entry[0], day_num + 1);
-        let col2 = format_minutes_as_time(combined_work_time);
-        let col3 = format_minutes_as_time(combined_ride_time);
-        let col4 = format_minutes_as_time(combined_total_time);
-        let col5 = ""; // Empty for the header
+            current_y_pos -= line_height;
+        }
+    }
 
-        // Output header row for the day
-        current_layer.use_text(
-            &col1,
-            font_size,
-            column_widths[0],
-            current_y_pos,
-            &font_medium,
-        );
-        current_layer.use_text(
-            &col2,
-            font_size,
-            column_widths[1],
-            current_y_pos,
-            &font_medium,
-        );
-        current_layer.use_text(
-            &col3,
-            font_size,
-            column_widths[2],
-            current_y_pos,
-            &font_medium,
-        );
-        current_layer.use_text(
-            &col4,
-            font_size,
-            column_widths[3],
-            current_y_pos,
-            &font_medium,
+    // Yearly summary with one line per month on its own page
+    if let ReportPeriod::Year(_) = period {
+        let current_page_text = current_page.to_string();
+        current_layer.use_text(current_page_text, 13.0, Mm(185.0), Mm(14.0), &font_medium);
+
+        let (new_layer, new_y_pos, new_page) = add_new_page(
+            &doc,
+            current_page,
+            &font_bold,
+            &catalog.year_columns,
+            &column_widths,
+            &accent_color,
+            line_height,
         );
+        current_layer = new_layer;
+        current_y_pos = new_y_pos;
+        current_page = new_page;
+
         current_layer.use_text(
-            col5,
-            font_size,
-            column_widths[4],
-            current_y_pos,
+            catalog.year_overview,
+            14.0,
+            Mm(21.0),
+            Mm(289.0),
             &font_medium,
         );
 
-        // Move the position down for the next row
-        current_y_pos -= line_height;
-
-        // Output the task rows (combine "Work" and "Ride" times for the same task_id)
-        for (task_id, (work_time, ride_time)) in task_times.iter() {
-            // New Page
-            if current_y_pos <= Mm(25.0) {
-                let current_page_text = current_page.to_string();
-                current_layer.use_text(current_page_text, 13.0, Mm(185.0), Mm(14.0), &font_medium);
-
-                // Call the refactored function to add a new page
-                let (new_layer, _new_y_pos, new_page) =
-                    add_new_page(&doc, current_page, &font_bold, &column_widths, line_height);
-
-                // Update current_layer, current_y_pos, and current_page with the returned values
-                current_layer = new_layer;
-                current_y_pos = Mm(273.0) - line_height;
-                current_page = new_page;
+        let mut summary_rows: Vec<(String, u64, u64, u64, u64)> = totals_per_month
+            .iter()
+            .map(|(month, (work, ride, pause, days))| {
+                (
+                    locale.month_name(*month).to_string(),
+                    *work,
+                    *ride,
+                    *pause,
+                    *days,
+                )
+            })
+            .collect();
+        summary_rows.push((
+            catalog.total.to_string(),
+            month_time_work,
+            month_time_ride,
+            month_time_break,
+            days_in_month,
+        ));
+
+        let total_row = summary_rows.len() - 1;
+        for (row, (label, work, ride, pause, days)) in summary_rows.into_iter().enumerate() {
+            let net = (work + ride).saturating_sub(pause);
+            let font = if row == total_row {
+                &font_bold
+            } else {
+                &font_medium
+            };
+            let cells = [
+                label,
+                format_minutes_as_time(work),
+                format_minutes_as_time(ride),
+                format_minutes_as_time(pause),
+                format_minutes_as_time(net),
+                format_minutes_as_time(net / days.max(1)),
+            ];
+            for (cell, width) in cells.iter().zip(column_widths) {
+                current_layer.use_text(cell, font_size, width, current_y_pos, font);
             }
-
-            let work_time_str = format_minutes_as_time(*work_time);
-            let ride_time_str = format_minutes_as_time(*ride_time);
-
-            // Calculate the total time (work + ride)
-            let total_time = work_time + ride_time;
-            let total_time_str = format_minutes_as_time(total_time);
-
-            let pdf_day_entry_x_left = 21.0;
-            let pdf_day_entry_x_right = 188.0;
-            let pdf_day_entry_y_top = current_y_pos.0 + line_height.0 - 3.0;
-            let pdf_day_entry_y_bottom = current_y_pos.0 - 3.0;
-
-            let rectangle = create_rectangle(
-                pdf_day_entry_x_left,
-                pdf_day_entry_x_right,
-                pdf_day_entry_y_top,
-                pdf_day_entry_y_bottom,
-            );
-            let header_color = Color::Rgb(Rgb::new(0.952, 0.952, 0.952, None));
-            current_layer.set_fill_color(header_color);
-            current_layer.add_shape(rectangle);
-
-            let text_color = Color::Rgb(Rgb::new(0.0, 0.0, 0.0, None));
-            current_layer.set_fill_color(text_color);
-
-            // Output task_id rows
-            current_layer.use_text("", font_size, column_widths[0], current_y_pos, &font_medium); // Empty first column
-            current_layer.use_text(
-                &work_time_str,
-                font_size,
-                column_widths[1],
-                current_y_pos,
-                &font_medium,
-            );
-            current_layer.use_text(
-                &ride_time_str,
-                font_size,
-                column_widths[2],
-                current_y_pos,
-                &font_medium,
-            );
-            current_layer.use_text(
-                &total_time_str,
-                font_size,
-                column_widths[3],
-                current_y_pos,
-                &font_medium,
-            );
-            current_layer.use_text(
-                task_id,
-                font_size,
-                column_widths[4],
-                current_y_pos,
-                &font_medium,
-            );
-
             current_y_pos -= line_height;
         }
     }
+
+    // Signatures and checksum below the last table, on a page of their own if there is no space
+    if current_y_pos.0 - verification::SIGNATURE_BLOCK_HEIGHT < 20.0 {
+        let current_page_text = current_page.to_string();
+        current_layer.use_text(current_page_text, 13.0, Mm(185.0), Mm(14.0), &font_medium);
+
+        let (new_page, new_layer) = doc.add_page(
+            Mm(pdf_width),
+            Mm(pdf_height),
+            format!("Page {}, Layer 1", current_page),
+        );
+        current_layer = doc.get_page(new_page).get_layer(new_layer);
+        current_y_pos = Mm(285.0);
+        current_page += 1;
+    }
+    verification::draw_signature_block(
+        &current_layer,
+        catalog,
+        hash.as_deref(),
+        current_y_pos.0,
+        &font_light,
+    )?;
+
     let current_page_text = current_page.to_string();
     current_layer.use_text(current_page_text, 13.0, Mm(185.0), Mm(14.0), &font_medium);
 
@@ -688,20 +710,8 @@ pub async fn generate_pdf(
         let mut writer = BufWriter::new(cursor);
         doc.save(&mut writer)?;
     }
-    let pdf_base64 = encode(&pdf_buffer);
-
-    Ok(pdf_base64)
-}
 
-// Helper function to parse time string (e.g., "08:00") to minutes
-fn parse_time_to_minutes(time_str: &str) -> u64 {
-    let parts: Vec<&str> = time_str.split(':').collect();
-    if parts.len() != 2 {
-        return 0;
-    }
-    let hours: u64 = parts[0].parse().unwrap_or(0);
-    let minutes: u64 = parts[1].parse().unwrap_or(0);
-    hours * 60 + minutes
+    Ok((pdf_buffer, hash))
 }
 
 // Helper function to format minutes as time (e.g., 480 minutes -> "08:00")
@@ -715,7 +725,9 @@ fn add_new_page(
     doc: &PdfDocumentReference,
     current_page: i32,
     font_bold: &IndirectFontRef,
+    columns: &[&str],
     column_widths: &[Mm],
+    accent_color: &Color,
     line_height: Mm,
 ) -> (PdfLayerReference, Mm, i32) {
     let (new_page, new_layer) = doc.add_page(
@@ -736,18 +748,15 @@ fn add_new_page(
         pdf_table_header_y_top,
         pdf_table_header_y_bottom,
     );
-    let header_color = Color::Rgb(Rgb::new(0.952, 0.952, 0.952, None));
-    current_layer.set_fill_color(header_color);
+    current_layer.set_fill_color(accent_color.clone());
     current_layer.add_shape(rectangle);
 
     let text_color = Color::Rgb(Rgb::new(0.0, 0.0, 0.0, None));
     current_layer.set_fill_color(text_color);
 
-    current_layer.use_text("Datum", 11.0, column_widths[0], Mm(278.0), font_bold);
-    current_layer.use_text("Arbeitszeit", 11.0, column_widths[1], Mm(278.0), font_bold);
-    current_layer.use_text("Fahrzeit", 11.0, column_widths[2], Mm(278.0), font_bold);
-    current_layer.use_text("Gesamtzeit", 11.0, column_widths[3], Mm(278.0), font_bold);
-    current_layer.use_text("Aufgabe", 11.0, column_widths[4], Mm(278.0), font_bold);
+    for (column, width) in columns.iter().zip(column_widths) {
+        current_layer.use_text(*column, 11.0, *width, Mm(278.0), font_bold);
+    }
 
     let new_y_pos = Mm(273.0) - line_height;
 
@@ -823,8 +832,14 @@ mod tests {
         "../fixtures/worktime.sql"
     ))]
     fn test_generate_pdf(pool: sqlx::PgPool) -> Result<(), Box<dyn Error>> {
-        let generated_pdf =
-            generate_pdf("2024-01".to_string(), HeaderColor::DefaultGrey, &pool, &1).await?;
+        let generated_pdf = generate_pdf(
+            "2024-01".parse()?,
+            HeaderColor::DefaultGrey,
+            Locale::De,
+            &pool,
+            &1,
+        )
+        .await?;
 
         let output_path = "test/generated_output.b64";
         println!("{}", output_path);
@@ -842,92 +857,104 @@ mod tests {
         Ok(())
     }
 
-    #[test]
-    fn test_parse_time_to_minutes() {
-        assert_eq!(parse_time_to_minutes("01:00"), 60);
-        assert_eq!(parse_time_to_minutes("00:00"), 0);
-        assert_eq!(parse_time_to_minutes("00:01"), 1);
-        assert_eq!(parse_time_to_minutes("00:10"), 10);
-        assert_eq!(parse_time_to_minutes("01:01"), 61);
-        assert_eq!(parse_time_to_minutes("100:00"), 6000);
+    #[sqlx::test(fixtures(
+        "../fixtures/truncate.sql",
+        "../fixtures/task.sql",
+        "../fixtures/address.sql",
+        "../fixtures/employee.sql",
+        "../fixtures/worktime.sql"
+    ))]
+    async fn test_render_other_periods(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        // a year has more days than fit on one page and a summary page per month, the current
+        // month has the running timer of the fixtures
+        let current_month = Utc::now().format("%Y-%m").to_string();
+        for period in ["2024-W01", "2024", "2023-12-20_2024-01-10", &current_month] {
+            let pdf = render_pdf(
+                period.parse()?,
+                HeaderColor::DefaultGrey,
+                Locale::En,
+                &pool,
+                &1,
+            )
+            .await?;
+
+            assert!(pdf.starts_with(b"%PDF"), "{}", period);
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(
+        "../fixtures/truncate.sql",
+        "../fixtures/task.sql",
+        "../fixtures/address.sql",
+        "../fixtures/employee.sql",
+        "../fixtures/worktime.sql"
+    ))]
+    async fn test_render_pdf_is_archived(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let pdf = render_pdf(
+            "2024-01".parse()?,
+            HeaderColor::DefaultGrey,
+            Locale::De,
+            &pool,
+            &1,
+        )
+        .await?;
+
+        // the archive has the exact file
+        let reports = archived_report::get_archived_reports(1, Some("2024-01"), &pool).await?;
+        assert_eq!(reports.len(), 1);
+        let file = archived_report::get_archived_report_file(reports[0].report_id, &pool)
+            .await?
+            .unwrap();
+        assert_eq!(file.content, pdf);
+
+        Ok(())
     }
 
     #[test]
-    fn test_add_month() {
-        assert_eq!(add_month("2024-01").unwrap(), "2024-02");
-        assert_eq!(add_month("2024-12").unwrap(), "2025-01");
-        assert_eq!(add_month("2023-11").unwrap(), "2023-12");
+    fn test_validate_month() {
+        assert!(validate_month("2024-01").is_ok());
+        assert!(validate_month("2024-13").is_err());
+        assert!(validate_month("2024-1").is_err());
+        assert!(validate_month("24-01").is_err());
+        assert!(validate_month("").is_err());
     }
 
     #[test]
     fn test_get_weekday_abbreviation() {
         assert_eq!(
-            get_weekday_abbreviation(NaiveDate::from_ymd_opt(2024, 9, 2).unwrap()),
+            get_weekday_abbreviation(NaiveDate::from_ymd_opt(2024, 9, 2).unwrap(), Locale::De),
             "Mo"
         );
         assert_eq!(
-            get_weekday_abbreviation(NaiveDate::from_ymd_opt(2024, 9, 3).unwrap()),
+            get_weekday_abbreviation(NaiveDate::from_ymd_opt(2024, 9, 3).unwrap(), Locale::De),
             "Di"
         );
         assert_eq!(
-            get_weekday_abbreviation(NaiveDate::from_ymd_opt(2024, 9, 4).unwrap()),
+            get_weekday_abbreviation(NaiveDate::from_ymd_opt(2024, 9, 4).unwrap(), Locale::De),
             "Mi"
         );
         assert_eq!(
-            get_weekday_abbreviation(NaiveDate::from_ymd_opt(2024, 9, 5).unwrap()),
+            get_weekday_abbreviation(NaiveDate::from_ymd_opt(2024, 9, 5).unwrap(), Locale::De),
             "Do"
         );
         assert_eq!(
-            get_weekday_abbreviation(NaiveDate::from_ymd_opt(2024, 9, 6).unwrap()),
+            get_weekday_abbreviation(NaiveDate::from_ymd_opt(2024, 9, 6).unwrap(), Locale::De),
             "Fr"
         );
         assert_eq!(
-            get_weekday_abbreviation(NaiveDate::from_ymd_opt(2024, 9, 7).unwrap()),
+            get_weekday_abbreviation(NaiveDate::from_ymd_opt(2024, 9, 7).unwrap(), Locale::De),
             "Sa"
         );
         assert_eq!(
-            get_weekday_abbreviation(NaiveDate::from_ymd_opt(2024, 9, 8).unwrap()),
+            get_weekday_abbreviation(NaiveDate::from_ymd_opt(2024, 9, 8).unwrap(), Locale::De),
             "So"
         );
-    }
-
-    #[test]
-    fn test_add_durations() {
-        let d1 = PgInterval {
-            months: 2,
-            days: 10,
-            microseconds: 5_000_000,
-        };
-        let d2 = PgInterval {
-            months: 3,
-            days: 5,
-            microseconds: 7_000_000,
-        };
-
-        let result = add_durations(&d1, &d2);
-
-        assert_eq!(result.months, 5);
-        assert_eq!(result.days, 15);
-        assert_eq!(result.microseconds, 12_000_000);
-    }
-
-    #[test]
-    fn test_format_duration() {
-        let duration = PgInterval {
-            months: 0,
-            days: 0,
-            microseconds: 3660 * 1_000_000, // 1 hour, 1 minute
-        };
-
-        assert_eq!(format_duration(duration), "01:01");
-
-        let duration_zero = PgInterval {
-            months: 0,
-            days: 0,
-            microseconds: 0,
-        };
-
-        assert_eq!(format_duration(duration_zero), "00:00");
+        assert_eq!(
+            get_weekday_abbreviation(NaiveDate::from_ymd_opt(2024, 9, 8).unwrap(), Locale::En),
+            "Sun"
+        );
     }
 
     #[test]
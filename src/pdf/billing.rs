@@ -0,0 +1,116 @@
+use super::branding::Branding;
+use super::table::{format_signed_minutes, render_table_report, Table};
+use super::{truncate_string, Locale};
+use crate::models::BillingSummary;
+
+/// Renders the billable hours and amounts per task, e.g. as basis for an invoice.
+pub fn render_billing_pdf(
+    summary: &BillingSummary,
+    locale: Locale,
+    branding: &Branding,
+) -> anyhow::Result<Vec<u8>> {
+    let catalog = locale.catalog();
+    let mut rows: Vec<(Vec<String>, bool)> = summary
+        .lines
+        .iter()
+        .filter(|line| line.billable_minutes > 0)
+        .map(|line| {
+            (
+                vec![
+                    truncate_string(
+                        line.task_description
+                            .as_deref()
+                            .unwrap_or(catalog.no_description),
+                        40,
+                    ),
+                    format_signed_minutes(line.billable_minutes),
+                    locale.format_cents(line.amount_cents),
+                ],
+                false,
+            )
+        })
+        .collect();
+    rows.push((
+        vec![
+            String::from(catalog.total),
+            format_signed_minutes(summary.billable_minutes),
+            locale.format_cents(summary.amount_cents),
+        ],
+        true,
+    ));
+
+    let mut info = vec![
+        (
+            catalog.customer,
+            truncate_string(
+                summary
+                    .customer_name
+                    .as_deref()
+                    .unwrap_or(catalog.all_customers),
+                40,
+            ),
+        ),
+        (
+            catalog.period,
+            format!(
+                "{} - {}",
+                locale.format_date(summary.from),
+                locale.format_date(summary.to)
+            ),
+        ),
+    ];
+    // these hours are missing in the amount until a rate is set for them
+    if summary.unrated_minutes > 0 {
+        info.push((
+            catalog.unrated,
+            format_signed_minutes(summary.unrated_minutes),
+        ));
+    }
+
+    render_table_report(
+        catalog.billing_title,
+        &info,
+        &[Table {
+            headers: &catalog.billing_columns,
+            columns: &[23.0, 125.0, 155.0],
+            rows,
+        }],
+        locale,
+        branding,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::BillingLine;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_render_billing_pdf() -> anyhow::Result<()> {
+        let summary = BillingSummary {
+            customer_id: Some(1),
+            customer_name: Some(String::from("Cloud City")),
+            from: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            to: NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+            lines: vec![BillingLine {
+                task_id: 3,
+                task_description: Some(String::from("Repair Millennium Falcon")),
+                billable_minutes: 540,
+                unrated_minutes: 60,
+                non_billable_minutes: 0,
+                amount_cents: 72000,
+            }],
+            billable_minutes: 540,
+            unrated_minutes: 60,
+            amount_cents: 72000,
+        };
+
+        for locale in [Locale::De, Locale::En] {
+            let pdf = render_billing_pdf(&summary, locale, &Branding::default())?;
+
+            assert!(pdf.starts_with(b"%PDF"));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,187 @@
+use anyhow::anyhow;
+use printpdf::image_crate::{self, DynamicImage, ImageFormat};
+use printpdf::*;
+use sqlx::PgPool;
+
+use super::truncate_string;
+use crate::models::CompanySettings;
+use crate::service::company;
+
+// the logo is scaled into this box in the top right corner of the header
+const LOGO_MAX_WIDTH: f64 = 38.0;
+const LOGO_MAX_HEIGHT: f64 = 12.0;
+
+/// Parses a hex color like '#a50d0d'.
+pub fn parse_hex_color(color: &str) -> Option<Rgb> {
+    let hex = color.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |index: usize| {
+        u8::from_str_radix(&hex[index..index + 2], 16)
+            .ok()
+            .map(|value| value as f64 / 255.0)
+    };
+
+    Some(Rgb::new(channel(0)?, channel(2)?, channel(4)?, None))
+}
+
+/// Decodes an uploaded logo, only PNG and JPEG are accepted.
+pub fn decode_logo(logo: &[u8]) -> anyhow::Result<DynamicImage> {
+    let format = image_crate::guess_format(logo)?;
+    if !matches!(format, ImageFormat::Png | ImageFormat::Jpeg) {
+        return Err(anyhow!("the logo has to be a PNG or JPEG image"));
+    }
+
+    Ok(image_crate::load_from_memory_with_format(logo, format)?)
+}
+
+/// Company settings prepared for drawing.
+pub struct Branding {
+    pub name: String,
+    pub address_lines: Vec<String>,
+    logo: Option<DynamicImage>,
+    pub header_color: Option<Color>,
+    pub accent_color: Option<Color>,
+}
+
+impl Branding {
+    pub async fn load(pool: &PgPool) -> anyhow::Result<Self> {
+        Ok(Self::from(company::get_company_settings(pool).await?))
+    }
+
+    pub fn header_color_or(&self, default: Color) -> Color {
+        self.header_color.clone().unwrap_or(default)
+    }
+
+    pub fn accent_color_or(&self, default: Color) -> Color {
+        self.accent_color.clone().unwrap_or(default)
+    }
+
+    /// Writes the name and address of the company and its logo into the top right corner, the
+    /// fill color of the layer is used for the text.
+    pub fn draw(&self, layer: &PdfLayerReference, font: &IndirectFontRef) {
+        if let Some(logo) = &self.logo {
+            // the dpi scales the image into the box without changing its aspect ratio
+            let dpi = f64::max(
+                logo.height() as f64 * 25.4 / LOGO_MAX_HEIGHT,
+                logo.width() as f64 * 25.4 / LOGO_MAX_WIDTH,
+            );
+            let width = logo.width() as f64 * 25.4 / dpi;
+            // without the alpha channel, printpdf can not embed transparent images
+            Image::from_dynamic_image(&DynamicImage::ImageRgb8(logo.to_rgb8())).add_to_layer(
+                layer.clone(),
+                ImageTransform {
+                    translate_x: Some(Mm(188.0 - width)),
+                    translate_y: Some(Mm(279.0)),
+                    dpi: Some(dpi),
+                    ..Default::default()
+                },
+            );
+        }
+
+        // the text starts in the right third of the page and is cut before the margin
+        layer.use_text(
+            truncate_string(&self.name, 28),
+            12.0,
+            Mm(130.0),
+            Mm(273.0),
+            font,
+        );
+        let mut y = 268.0;
+        for line in &self.address_lines {
+            layer.use_text(truncate_string(line, 38), 9.0, Mm(130.0), Mm(y), font);
+            y -= 4.0;
+        }
+    }
+}
+
+// plain reports without any company details for the tests of the renderers
+#[cfg(test)]
+impl Default for Branding {
+    fn default() -> Self {
+        Self {
+            name: String::from("Test GmbH"),
+            address_lines: Vec::new(),
+            logo: None,
+            header_color: None,
+            accent_color: None,
+        }
+    }
+}
+
+impl From<CompanySettings> for Branding {
+    fn from(settings: CompanySettings) -> Self {
+        Self {
+            name: settings.name,
+            address_lines: settings
+                .address
+                .map(|address| address.lines().map(String::from).collect())
+                .unwrap_or_default(),
+            // a broken logo should not prevent the report
+            logo: settings.logo.and_then(|logo| decode_logo(&logo).ok()),
+            header_color: settings
+                .header_color
+                .as_deref()
+                .and_then(parse_hex_color)
+                .map(Color::Rgb),
+            accent_color: settings
+                .accent_color
+                .as_deref()
+                .and_then(parse_hex_color)
+                .map(Color::Rgb),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // smallest valid png with a single red pixel
+    fn red_pixel_png() -> Vec<u8> {
+        let mut png = Vec::new();
+        DynamicImage::ImageRgb8(image_crate::RgbImage::from_pixel(
+            1,
+            1,
+            image_crate::Rgb([255, 0, 0]),
+        ))
+        .write_to(&mut std::io::Cursor::new(&mut png), ImageFormat::Png)
+        .unwrap();
+        png
+    }
+
+    #[test]
+    fn test_parse_hex_color() {
+        assert_eq!(
+            parse_hex_color("#ff0000"),
+            Some(Rgb::new(1.0, 0.0, 0.0, None))
+        );
+        assert_eq!(parse_hex_color("ff0000"), None);
+        assert_eq!(parse_hex_color("#ff00"), None);
+        assert_eq!(parse_hex_color("#gg0000"), None);
+    }
+
+    #[test]
+    fn test_draw_logo() -> anyhow::Result<()> {
+        let branding = Branding {
+            logo: Some(decode_logo(&red_pixel_png())?),
+            address_lines: vec![String::from("Hauptstraße 1"), String::from("12345 Berlin")],
+            ..Default::default()
+        };
+        let (doc, page, layer) = PdfDocument::new("Logo", Mm(210.0), Mm(297.0), "Layer 1");
+        let font = doc.add_builtin_font(BuiltinFont::Helvetica)?;
+
+        branding.draw(&doc.get_page(page).get_layer(layer), &font);
+
+        assert!(doc.save_to_bytes()?.starts_with(b"%PDF"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_logo() {
+        assert!(decode_logo(&red_pixel_png()).is_ok());
+        assert!(decode_logo(b"GIF89a").is_err());
+        assert!(decode_logo(b"no image").is_err());
+    }
+}
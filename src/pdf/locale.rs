@@ -0,0 +1,401 @@
+use async_graphql::Enum;
+use chrono::{NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// Language of the labels and the formatting of dates and numbers in a report.
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Locale {
+    #[default]
+    De,
+    En,
+}
+
+/// Labels of the monthly report.
+pub struct Catalog {
+    pub document_title: &'static str,
+    pub title: &'static str,
+    pub firstname: &'static str,
+    pub lastname: &'static str,
+    pub email: &'static str,
+    pub month: &'static str,
+    pub period: &'static str,
+    pub date: &'static str,
+    pub work_this_month: &'static str,
+    pub ride_this_month: &'static str,
+    pub total_this_month: &'static str,
+    pub work_in_period: &'static str,
+    pub ride_in_period: &'static str,
+    pub total_in_period: &'static str,
+    pub break_this_month: &'static str,
+    pub break_in_period: &'static str,
+    pub net_this_month: &'static str,
+    pub net_in_period: &'static str,
+    pub average_per_day: &'static str,
+    pub total_average_per_day: &'static str,
+    pub overview: &'static str,
+    /// date, work, ride, break, net and task column
+    pub columns: [&'static str; 6],
+    pub year_overview: &'static str,
+    /// month, work, ride, break, net and average net column of the yearly summary
+    pub year_columns: [&'static str; 6],
+    pub total: &'static str,
+    pub no_description: &'static str,
+    pub break_entry: &'static str,
+    /// note for running timers, followed by the time of the generation
+    pub in_progress: &'static str,
+    pub employee_signature: &'static str,
+    pub manager_signature: &'static str,
+    pub checksum: &'static str,
+    pub verify_at: &'static str,
+    /// instead of the checksum while a timer is running
+    pub no_checksum: &'static str,
+    /// salutation of the mail with the report, followed by the first name
+    pub mail_greeting: &'static str,
+    /// text of the mail with the report, followed by the period
+    pub mail_text: &'static str,
+    /// budget alert mails, the task is named by its number if it has no description
+    pub alert_task: &'static str,
+    /// subject of the alert, after the percentage of the estimate
+    pub alert_reached: &'static str,
+    /// text of the alert, followed by the task, the booked hours, `alert_of` and the estimate
+    pub alert_booked: &'static str,
+    pub alert_of: &'static str,
+    pub alert_estimated: &'static str,
+    pub team_title: &'static str,
+    pub team: &'static str,
+    pub all_employees: &'static str,
+    /// employee, work, ride, break, absence, target and overtime column of the team report
+    pub team_columns: [&'static str; 7],
+    /// task and hours column
+    pub task_hours_columns: [&'static str; 2],
+    pub task_report_title: &'static str,
+    pub task: &'static str,
+    /// employee, work type and hours column of the task report
+    pub task_report_columns: [&'static str; 3],
+    /// work, ride and break
+    pub work_types: [&'static str; 3],
+    pub billing_title: &'static str,
+    pub customer: &'static str,
+    pub all_customers: &'static str,
+    /// billable time which no rate applies to
+    pub unrated: &'static str,
+    /// task, hours and amount column of the billing summary
+    pub billing_columns: [&'static str; 3],
+    months: [&'static str; 12],
+    weekdays: [&'static str; 7],
+}
+
+const GERMAN: Catalog = Catalog {
+    document_title: "Zeiterfassungen",
+    title: "Zeitenübersicht",
+    firstname: "Vorname:",
+    lastname: "Nachname:",
+    email: "Email-Adresse:",
+    month: "Monat:",
+    period: "Zeitraum:",
+    date: "Datum:",
+    work_this_month: "Arbeitszeit diesen Monat:",
+    ride_this_month: "Fahrstunden diesen Monat:",
+    total_this_month: "Gesamtzeit diesen Monat:",
+    work_in_period: "Arbeitszeit im Zeitraum:",
+    ride_in_period: "Fahrstunden im Zeitraum:",
+    total_in_period: "Gesamtzeit im Zeitraum:",
+    break_this_month: "Pausen diesen Monat:",
+    break_in_period: "Pausen im Zeitraum:",
+    net_this_month: "Nettoarbeitszeit diesen Monat:",
+    net_in_period: "Nettoarbeitszeit im Zeitraum:",
+    average_per_day: "durchschnittliche Zeit pro Tag:",
+    total_average_per_day: "durchschnittliche Gesamtzeit pro Tag:",
+    overview: "Gesamtübersicht erfasster Zeiten:",
+    columns: [
+        "Datum",
+        "Arbeitszeit",
+        "Fahrzeit",
+        "Pause",
+        "Netto",
+        "Aufgabe",
+    ],
+    year_overview: "Übersicht nach Monaten:",
+    year_columns: [
+        "Monat",
+        "Arbeitszeit",
+        "Fahrzeit",
+        "Pause",
+        "Netto",
+        "pro Tag",
+    ],
+    total: "Gesamt",
+    no_description: "Keine Beschreibung vorhanden",
+    break_entry: "Pause",
+    in_progress: "läuft noch, Stand",
+    employee_signature: "Datum, Unterschrift Mitarbeiter",
+    manager_signature: "Datum, Unterschrift Vorgesetzter",
+    checksum: "Prüfsumme:",
+    verify_at: "Prüfung unter:",
+    no_checksum: "Vorläufiger Bericht: keine Prüfsumme, solange ein Timer läuft.",
+    mail_greeting: "Hallo",
+    mail_text: "anbei die Zeitenübersicht für",
+    alert_task: "Aufgabe",
+    alert_reached: "des Budgets erreicht",
+    alert_booked: "Gebucht auf",
+    alert_of: "von",
+    alert_estimated: "geschätzt",
+    team_title: "Teambericht",
+    team: "Team:",
+    all_employees: "Alle Mitarbeiter",
+    team_columns: [
+        "Mitarbeiter",
+        "Arbeit",
+        "Fahrt",
+        "Pause",
+        "Abwes.",
+        "Soll",
+        "Überstd.",
+    ],
+    task_hours_columns: ["Aufgabe", "Stunden"],
+    task_report_title: "Aufgabenbericht",
+    task: "Aufgabe:",
+    task_report_columns: ["Mitarbeiter", "Art", "Stunden"],
+    work_types: ["Arbeit", "Fahrt", "Pause"],
+    billing_title: "Abrechnung",
+    customer: "Kunde:",
+    all_customers: "Alle Kunden",
+    unrated: "Ohne Stundensatz:",
+    billing_columns: ["Aufgabe", "Stunden", "Betrag"],
+    months: [
+        "Januar",
+        "Februar",
+        "März",
+        "April",
+        "Mai",
+        "Juni",
+        "Juli",
+        "August",
+        "September",
+        "Oktober",
+        "November",
+        "Dezember",
+    ],
+    weekdays: ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"],
+};
+
+const ENGLISH: Catalog = Catalog {
+    document_title: "Time records",
+    title: "Time sheet",
+    firstname: "First name:",
+    lastname: "Last name:",
+    email: "Email address:",
+    month: "Month:",
+    period: "Period:",
+    date: "Date:",
+    work_this_month: "Work time this month:",
+    ride_this_month: "Travel time this month:",
+    total_this_month: "Total time this month:",
+    work_in_period: "Work time in period:",
+    ride_in_period: "Travel time in period:",
+    total_in_period: "Total time in period:",
+    break_this_month: "Breaks this month:",
+    break_in_period: "Breaks in period:",
+    net_this_month: "Net working time this month:",
+    net_in_period: "Net working time in period:",
+    average_per_day: "Average time per day:",
+    total_average_per_day: "Average total time per day:",
+    overview: "Overview of recorded times:",
+    columns: ["Date", "Work time", "Travel time", "Break", "Net", "Task"],
+    year_overview: "Overview by month:",
+    year_columns: [
+        "Month",
+        "Work time",
+        "Travel time",
+        "Break",
+        "Net",
+        "Per day",
+    ],
+    total: "Total",
+    no_description: "No description available",
+    break_entry: "Break",
+    in_progress: "in progress as of",
+    employee_signature: "Date, signature of the employee",
+    manager_signature: "Date, signature of the manager",
+    checksum: "Checksum:",
+    verify_at: "Verify at:",
+    no_checksum: "Provisional report: no checksum while a timer is running.",
+    mail_greeting: "Hello",
+    mail_text: "please find attached the time sheet for",
+    alert_task: "Task",
+    alert_reached: "of the budget reached",
+    alert_booked: "Booked on",
+    alert_of: "of",
+    alert_estimated: "estimated",
+    team_title: "Team report",
+    team: "Team:",
+    all_employees: "All employees",
+    team_columns: [
+        "Employee", "Work", "Ride", "Break", "Absent", "Target", "Overtime",
+    ],
+    task_hours_columns: ["Task", "Hours"],
+    task_report_title: "Task report",
+    task: "Task:",
+    task_report_columns: ["Employee", "Type", "Hours"],
+    work_types: ["Work", "Ride", "Break"],
+    billing_title: "Billing",
+    customer: "Customer:",
+    all_customers: "All customers",
+    unrated: "Without rate:",
+    billing_columns: ["Task", "Hours", "Amount"],
+    months: [
+        "January",
+        "February",
+        "March",
+        "April",
+        "May",
+        "June",
+        "July",
+        "August",
+        "September",
+        "October",
+        "November",
+        "December",
+    ],
+    weekdays: ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+};
+
+impl Locale {
+    // 'DE' or 'EN' like in the query of the download links
+    pub fn parse(locale: &str) -> Option<Self> {
+        match locale {
+            "DE" => Some(Locale::De),
+            "EN" => Some(Locale::En),
+            _ => None,
+        }
+    }
+
+    pub fn catalog(self) -> &'static Catalog {
+        match self {
+            Locale::De => &GERMAN,
+            Locale::En => &ENGLISH,
+        }
+    }
+
+    pub fn weekday_abbreviation(self, weekday: Weekday) -> &'static str {
+        self.catalog().weekdays[weekday.num_days_from_monday() as usize]
+    }
+
+    // 1 is "Januar" or "January"
+    pub fn month_name(self, month: u32) -> &'static str {
+        self.catalog().months[(month.clamp(1, 12) - 1) as usize]
+    }
+
+    // the ISO week, e.g. "KW 05/2024" or "Week 05, 2024"
+    pub fn format_week(self, year: i32, week: u32) -> String {
+        match self {
+            Locale::De => format!("KW {:02}/{}", week, year),
+            Locale::En => format!("Week {:02}, {}", week, year),
+        }
+    }
+
+    // e.g. "31.01.2024" or "31 Jan 2024"
+    pub fn format_date(self, date: NaiveDate) -> String {
+        match self {
+            Locale::De => date.format("%d.%m.%Y").to_string(),
+            Locale::En => date.format("%d %b %Y").to_string(),
+        }
+    }
+
+    // the day column of the report, e.g. "Mo, 01." or "Mon 01", reports which are longer than
+    // a month need the month as well like "Mo, 01.02." or "Mon 01 Feb"
+    pub fn format_day(
+        self,
+        weekday_abbreviation: &str,
+        date: NaiveDate,
+        with_month: bool,
+    ) -> String {
+        match (self, with_month) {
+            (Locale::De, false) => format!("{}, {}", weekday_abbreviation, date.format("%d.")),
+            (Locale::De, true) => format!("{}, {}", weekday_abbreviation, date.format("%d.%m.")),
+            (Locale::En, false) => format!("{} {}", weekday_abbreviation, date.format("%d")),
+            (Locale::En, true) => format!("{} {}", weekday_abbreviation, date.format("%d %b")),
+        }
+    }
+
+    // euros with the separators of the language, e.g. "1.234,56 €" or "€1,234.56"
+    pub fn format_cents(self, cents: i64) -> String {
+        let sign = if cents < 0 { "-" } else { "" };
+        let cents = cents.abs();
+        let (group_separator, decimal_separator) = match self {
+            Locale::De => ('.', ','),
+            Locale::En => (',', '.'),
+        };
+        let euros = (cents / 100).to_string();
+        let mut grouped = String::new();
+        for (i, digit) in euros.chars().enumerate() {
+            if i > 0 && (euros.len() - i).is_multiple_of(3) {
+                grouped.push(group_separator);
+            }
+            grouped.push(digit);
+        }
+        match self {
+            Locale::De => format!(
+                "{}{}{}{:02} €",
+                sign,
+                grouped,
+                decimal_separator,
+                cents % 100
+            ),
+            Locale::En => format!(
+                "{}€{}{}{:02}",
+                sign,
+                grouped,
+                decimal_separator,
+                cents % 100
+            ),
+        }
+    }
+
+    // decimal hours with the decimal separator of the language, e.g. "8,50 h" or "8.50 h"
+    pub fn format_hours(self, minutes: u64) -> String {
+        let hours = format!("{:.2}", minutes as f64 / 60.0);
+        match self {
+            Locale::De => format!("{} h", hours.replace('.', ",")),
+            Locale::En => format!("{} h", hours),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_dates() {
+        let date = NaiveDate::from_ymd_opt(2024, 3, 5).unwrap();
+
+        assert_eq!(Locale::De.format_date(date), "05.03.2024");
+        assert_eq!(Locale::En.format_date(date), "05 Mar 2024");
+        assert_eq!(Locale::De.month_name(3), "März");
+        assert_eq!(Locale::En.month_name(3), "March");
+        assert_eq!(Locale::De.format_day("Di", date, false), "Di, 05.");
+        assert_eq!(Locale::En.format_day("Tue", date, false), "Tue 05");
+        assert_eq!(Locale::De.format_day("Di", date, true), "Di, 05.03.");
+        assert_eq!(Locale::En.format_day("Tue", date, true), "Tue 05 Mar");
+        assert_eq!(Locale::De.format_week(2024, 5), "KW 05/2024");
+    }
+
+    #[test]
+    fn test_format_cents() {
+        assert_eq!(Locale::De.format_cents(5), "0,05 €");
+        assert_eq!(Locale::De.format_cents(123456), "1.234,56 €");
+        assert_eq!(Locale::De.format_cents(100000000), "1.000.000,00 €");
+        assert_eq!(Locale::De.format_cents(-2550), "-25,50 €");
+        assert_eq!(Locale::En.format_cents(123456), "€1,234.56");
+        assert_eq!(Locale::En.format_cents(-2550), "-€25.50");
+    }
+
+    #[test]
+    fn test_format_hours() {
+        assert_eq!(Locale::De.format_hours(510), "8,50 h");
+        assert_eq!(Locale::En.format_hours(510), "8.50 h");
+        assert_eq!(Locale::En.format_hours(0), "0.00 h");
+    }
+}
@@ -0,0 +1,265 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context};
+use async_graphql::{InputObject, OneofObject};
+use chrono::{NaiveDate, Weekday};
+
+use super::Locale;
+
+// longer ranges should be requested as whole years
+const MAX_RANGE_DAYS: i64 = 366;
+
+#[derive(InputObject, Copy, Clone, Eq, PartialEq, Debug)]
+pub struct MonthPeriod {
+    pub year: i32,
+    /// 1 to 12
+    pub month: u32,
+}
+
+#[derive(InputObject, Copy, Clone, Eq, PartialEq, Debug)]
+pub struct WeekPeriod {
+    /// ISO week-numbering year, which differs from the calendar year around new year
+    pub year: i32,
+    /// ISO week from 1 to 53
+    pub week: u32,
+}
+
+#[derive(InputObject, Copy, Clone, Eq, PartialEq, Debug)]
+pub struct DateRange {
+    pub from: NaiveDate,
+    /// inclusive
+    pub to: NaiveDate,
+}
+
+/// Days covered by a report, exactly one of the fields has to be set.
+#[derive(OneofObject, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ReportPeriod {
+    Month(MonthPeriod),
+    Week(WeekPeriod),
+    Year(i32),
+    Range(DateRange),
+}
+
+impl ReportPeriod {
+    /// First and last day (both inclusive) of the period, fails for periods which do not exist.
+    pub fn bounds(&self) -> anyhow::Result<(NaiveDate, NaiveDate)> {
+        match *self {
+            ReportPeriod::Month(MonthPeriod { year, month }) => {
+                let from = NaiveDate::from_ymd_opt(year, month, 1)
+                    .ok_or_else(|| anyhow!("{}-{} is not a valid month", year, month))?;
+                let to = from
+                    .checked_add_months(chrono::Months::new(1))
+                    .and_then(|next_month| next_month.pred_opt())
+                    .context("the month is out of range")?;
+                Ok((from, to))
+            }
+            ReportPeriod::Week(WeekPeriod { year, week }) => {
+                let from = NaiveDate::from_isoywd_opt(year, week, Weekday::Mon)
+                    .ok_or_else(|| anyhow!("{} has no week {}", year, week))?;
+                let to = NaiveDate::from_isoywd_opt(year, week, Weekday::Sun)
+                    .context("the week is out of range")?;
+                Ok((from, to))
+            }
+            ReportPeriod::Year(year) => {
+                let from = NaiveDate::from_ymd_opt(year, 1, 1)
+                    .filter(|_| (1..=9999).contains(&year))
+                    .ok_or_else(|| anyhow!("{} is not a valid year", year))?;
+                let to =
+                    NaiveDate::from_ymd_opt(year, 12, 31).context("the year is out of range")?;
+                Ok((from, to))
+            }
+            ReportPeriod::Range(DateRange { from, to }) => {
+                if to < from {
+                    return Err(anyhow!("the end of the period is before its start"));
+                }
+                if (to - from).num_days() >= MAX_RANGE_DAYS {
+                    return Err(anyhow!("the period is longer than {} days", MAX_RANGE_DAYS));
+                }
+                Ok((from, to))
+            }
+        }
+    }
+
+    pub fn is_month(&self) -> bool {
+        matches!(self, ReportPeriod::Month(_))
+    }
+
+    /// The period like it is printed in the report header.
+    pub fn label(&self, locale: Locale) -> String {
+        match *self {
+            ReportPeriod::Month(MonthPeriod { year, month }) => {
+                format!("{} {}", locale.month_name(month), year)
+            }
+            ReportPeriod::Week(WeekPeriod { year, week }) => locale.format_week(year, week),
+            ReportPeriod::Year(year) => year.to_string(),
+            ReportPeriod::Range(DateRange { from, to }) => {
+                format!("{} - {}", locale.format_date(from), locale.format_date(to))
+            }
+        }
+    }
+}
+
+/// The period in the download path: '2024-01', '2024-W05', '2024' or '2024-01-01_2024-01-31'.
+impl fmt::Display for ReportPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportPeriod::Month(MonthPeriod { year, month }) => {
+                write!(f, "{:04}-{:02}", year, month)
+            }
+            ReportPeriod::Week(WeekPeriod { year, week }) => write!(f, "{:04}-W{:02}", year, week),
+            ReportPeriod::Year(year) => write!(f, "{:04}", year),
+            ReportPeriod::Range(DateRange { from, to }) => write!(f, "{}_{}", from, to),
+        }
+    }
+}
+
+impl FromStr for ReportPeriod {
+    type Err = anyhow::Error;
+
+    fn from_str(period: &str) -> anyhow::Result<Self> {
+        let parse_date = |date: &str| {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d").context("the date has the wrong format")
+        };
+
+        let parsed = if let Some((from, to)) = period.split_once('_') {
+            ReportPeriod::Range(DateRange {
+                from: parse_date(from)?,
+                to: parse_date(to)?,
+            })
+        } else if let Some((year, week)) = period.split_once("-W") {
+            ReportPeriod::Week(WeekPeriod {
+                year: year.parse()?,
+                week: week.parse()?,
+            })
+        } else if let Some((year, month)) = period.split_once('-') {
+            ReportPeriod::Month(MonthPeriod {
+                year: year.parse()?,
+                month: month.parse()?,
+            })
+        } else {
+            ReportPeriod::Year(period.parse()?)
+        };
+
+        // only the canonical form is accepted, e.g. no '2024-1'
+        if parsed.to_string() != period {
+            return Err(anyhow!("{} is not a valid period", period));
+        }
+        parsed.bounds()?;
+
+        Ok(parsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_bounds() -> anyhow::Result<()> {
+        assert_eq!(
+            ReportPeriod::Month(MonthPeriod {
+                year: 2024,
+                month: 2
+            })
+            .bounds()?,
+            (date(2024, 2, 1), date(2024, 2, 29))
+        );
+        assert_eq!(
+            ReportPeriod::Month(MonthPeriod {
+                year: 2024,
+                month: 12
+            })
+            .bounds()?,
+            (date(2024, 12, 1), date(2024, 12, 31))
+        );
+        // the first ISO week of 2025 starts in 2024
+        assert_eq!(
+            ReportPeriod::Week(WeekPeriod {
+                year: 2025,
+                week: 1
+            })
+            .bounds()?,
+            (date(2024, 12, 30), date(2025, 1, 5))
+        );
+        assert_eq!(
+            ReportPeriod::Year(2024).bounds()?,
+            (date(2024, 1, 1), date(2024, 12, 31))
+        );
+
+        assert!(ReportPeriod::Month(MonthPeriod {
+            year: 2024,
+            month: 13
+        })
+        .bounds()
+        .is_err());
+        assert!(ReportPeriod::Week(WeekPeriod {
+            year: 2024,
+            week: 53
+        })
+        .bounds()
+        .is_err());
+        assert!(ReportPeriod::Range(DateRange {
+            from: date(2024, 2, 1),
+            to: date(2024, 1, 1)
+        })
+        .bounds()
+        .is_err());
+        assert!(ReportPeriod::Range(DateRange {
+            from: date(2024, 1, 1),
+            to: date(2025, 1, 1)
+        })
+        .bounds()
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_period() -> anyhow::Result<()> {
+        for period in ["2024-01", "2024-W05", "2024", "2024-01-01_2024-01-31"] {
+            assert_eq!(period.parse::<ReportPeriod>()?.to_string(), period);
+        }
+        assert_eq!(
+            "2020-W53".parse::<ReportPeriod>()?,
+            ReportPeriod::Week(WeekPeriod {
+                year: 2020,
+                week: 53
+            })
+        );
+
+        for period in [
+            "2024-1",
+            "2024-13",
+            "24-01",
+            "2024-W5",
+            "2024-W54",
+            "",
+            "2024_2024",
+        ] {
+            assert!(period.parse::<ReportPeriod>().is_err(), "{}", period);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_label() {
+        let week = ReportPeriod::Week(WeekPeriod {
+            year: 2024,
+            week: 5,
+        });
+        assert_eq!(week.label(Locale::De), "KW 05/2024");
+        assert_eq!(week.label(Locale::En), "Week 05, 2024");
+
+        let range = ReportPeriod::Range(DateRange {
+            from: date(2024, 1, 1),
+            to: date(2024, 1, 14),
+        });
+        assert_eq!(range.label(Locale::De), "01.01.2024 - 14.01.2024");
+    }
+}
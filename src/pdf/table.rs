@@ -0,0 +1,208 @@
+use anyhow::Context;
+use chrono::Utc;
+use printpdf::*;
+
+use super::branding::Branding;
+use super::{create_rectangle, Locale};
+use crate::time_utils;
+
+const PAGE_WIDTH: f64 = 210.0;
+const PAGE_HEIGHT: f64 = 297.0;
+const LINE_HEIGHT: f64 = 8.0;
+
+// e.g. 150 -> "02:30" and -90 -> "-01:30"
+pub(super) fn format_signed_minutes(minutes: i64) -> String {
+    let sign = if minutes < 0 { "-" } else { "" };
+    let minutes = minutes.abs();
+    format!("{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+}
+
+/// A table which continues on the next page with its header when the page is full.
+pub(super) struct Table<'a> {
+    pub headers: &'a [&'a str],
+    /// x position of every column in mm
+    pub columns: &'a [f64],
+    /// cells of the row and whether it is written in bold
+    pub rows: Vec<(Vec<String>, bool)>,
+}
+
+struct Fonts {
+    bold: IndirectFontRef,
+    medium: IndirectFontRef,
+    light: IndirectFontRef,
+}
+
+// writes the cells of a row at the x positions of the columns
+fn write_row(
+    layer: &PdfLayerReference,
+    cells: &[String],
+    columns: &[f64],
+    y: f64,
+    font: &IndirectFontRef,
+) {
+    for (cell, x) in cells.iter().zip(columns) {
+        layer.use_text(cell.clone(), 10.0, Mm(*x), Mm(y), font);
+    }
+}
+
+fn write_table_header(
+    layer: &PdfLayerReference,
+    table: &Table,
+    y: f64,
+    font: &IndirectFontRef,
+    branding: &Branding,
+) {
+    let rectangle = create_rectangle(21.0, 188.0, y + 7.0, y - 5.0);
+    layer.set_fill_color(branding.accent_color_or(Color::Rgb(Rgb::new(0.952, 0.952, 0.952, None))));
+    layer.add_shape(rectangle);
+    layer.set_fill_color(Color::Rgb(Rgb::new(0.0, 0.0, 0.0, None)));
+
+    let headers: Vec<String> = table.headers.iter().map(|h| h.to_string()).collect();
+    write_row(layer, &headers, table.columns, y, font);
+}
+
+/// Renders a report with a header of label and value lines followed by the tables.
+pub(super) fn render_table_report(
+    heading: &str,
+    info: &[(&str, String)],
+    tables: &[Table],
+    locale: Locale,
+    branding: &Branding,
+) -> anyhow::Result<Vec<u8>> {
+    let (doc, page1, layer1) =
+        PdfDocument::new(heading, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+
+    let fonts = Fonts {
+        bold: doc
+            .add_external_font(std::io::Cursor::new(include_bytes!(
+                "../../fonts/ntn-Bold.ttf"
+            )))
+            .context("cannot apply bold font")?,
+        medium: doc
+            .add_external_font(std::io::Cursor::new(include_bytes!(
+                "../../fonts/ntn-Medium.ttf"
+            )))
+            .context("cannot apply medium font")?,
+        light: doc
+            .add_external_font(std::io::Cursor::new(include_bytes!(
+                "../../fonts/ntn-Light.ttf"
+            )))
+            .context("cannot apply light font")?,
+    };
+
+    let mut layer = doc.get_page(page1).get_layer(layer1);
+    let mut page = 1;
+
+    // Header
+    let rectangle = create_rectangle(0.0, PAGE_WIDTH, PAGE_HEIGHT, 237.0);
+    layer.set_fill_color(branding.header_color_or(Color::Rgb(Rgb::new(0.352, 0.352, 0.352, None))));
+    layer.add_shape(rectangle);
+    layer.set_fill_color(Color::Rgb(Rgb::new(1.0, 1.0, 1.0, None)));
+    layer.use_text(heading, 24.0, Mm(21.0), Mm(271.0), &fonts.medium);
+    branding.draw(&layer, &fonts.light);
+
+    let mut info_y = 254.0;
+    for (label, value) in info {
+        layer.use_text(*label, 12.0, Mm(21.0), Mm(info_y), &fonts.light);
+        layer.use_text(value.clone(), 12.0, Mm(60.0), Mm(info_y), &fonts.light);
+        info_y -= 5.0;
+    }
+    layer.use_text(
+        locale.catalog().date,
+        12.0,
+        Mm(148.0),
+        Mm(249.0),
+        &fonts.light,
+    );
+    layer.use_text(
+        locale.format_date(time_utils::local_date(
+            Utc::now(),
+            &time_utils::company_timezone(),
+        )),
+        12.0,
+        Mm(168.0),
+        Mm(249.0),
+        &fonts.light,
+    );
+    layer.set_fill_color(Color::Rgb(Rgb::new(0.0, 0.0, 0.0, None)));
+
+    let mut y = 220.0 + LINE_HEIGHT;
+    for table in tables {
+        y -= LINE_HEIGHT;
+        let mut needs_header = true;
+
+        for (cells, bold) in &table.rows {
+            let header_height = if needs_header { LINE_HEIGHT + 2.0 } else { 0.0 };
+            if y - header_height <= 25.0 {
+                layer.use_text(page.to_string(), 13.0, Mm(185.0), Mm(14.0), &fonts.medium);
+                page += 1;
+                let (new_page, new_layer) = doc.add_page(
+                    Mm(PAGE_WIDTH),
+                    Mm(PAGE_HEIGHT),
+                    format!("Page {}, Layer 1", page),
+                );
+                layer = doc.get_page(new_page).get_layer(new_layer);
+                y = 278.0;
+                needs_header = true;
+            }
+            if needs_header {
+                write_table_header(&layer, table, y, &fonts.bold, branding);
+                y -= LINE_HEIGHT + 2.0;
+                needs_header = false;
+            }
+
+            let font = if *bold { &fonts.bold } else { &fonts.light };
+            write_row(&layer, cells, table.columns, y, font);
+            y -= LINE_HEIGHT;
+        }
+    }
+    layer.use_text(page.to_string(), 13.0, Mm(185.0), Mm(14.0), &fonts.medium);
+
+    let mut pdf_buffer = Vec::new();
+    {
+        let mut writer = std::io::BufWriter::new(std::io::Cursor::new(&mut pdf_buffer));
+        doc.save(&mut writer)?;
+    }
+
+    Ok(pdf_buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_signed_minutes() {
+        assert_eq!(format_signed_minutes(150), "02:30");
+        assert_eq!(format_signed_minutes(-90), "-01:30");
+        assert_eq!(format_signed_minutes(0), "00:00");
+    }
+
+    #[test]
+    fn test_render_table_report() -> anyhow::Result<()> {
+        // given are more rows than fit on one page
+        let table = Table {
+            headers: &["Aufgabe", "Stunden"],
+            columns: &[23.0, 166.0],
+            rows: (0..60)
+                .map(|row| {
+                    (
+                        vec![format!("Aufgabe {}", row), String::from("01:00")],
+                        false,
+                    )
+                })
+                .collect(),
+        };
+
+        let pdf = render_table_report(
+            "Bericht",
+            &[("Zeitraum:", String::from("Januar"))],
+            &[table],
+            Locale::En,
+            &Branding::default(),
+        )?;
+
+        assert!(pdf.starts_with(b"%PDF"));
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+use super::branding::Branding;
+use super::table::{format_signed_minutes, render_table_report, Table};
+use super::{truncate_string, Locale};
+use crate::models::{TaskReport, WorktimeType};
+
+fn work_type_label(work_type: WorktimeType, locale: Locale) -> &'static str {
+    let [work, ride, pause] = locale.catalog().work_types;
+    match work_type {
+        WorktimeType::Work => work,
+        WorktimeType::Ride => ride,
+        WorktimeType::Break => pause,
+    }
+}
+
+/// Renders the hours booked on a task per employee and work type.
+pub fn render_task_pdf(
+    report: &TaskReport,
+    locale: Locale,
+    branding: &Branding,
+) -> anyhow::Result<Vec<u8>> {
+    let catalog = locale.catalog();
+    let mut rows: Vec<(Vec<String>, bool)> = report
+        .entries
+        .iter()
+        .map(|entry| {
+            let name = format!(
+                "{} {}",
+                entry.firstname.as_deref().unwrap_or_default(),
+                entry.lastname.as_deref().unwrap_or_default()
+            );
+            (
+                vec![
+                    truncate_string(name.trim(), 40),
+                    work_type_label(entry.work_type, locale).to_string(),
+                    format_signed_minutes(entry.minutes),
+                ],
+                false,
+            )
+        })
+        .collect();
+    rows.push((
+        vec![
+            String::from(catalog.total),
+            String::new(),
+            format_signed_minutes(report.total_minutes),
+        ],
+        true,
+    ));
+
+    render_table_report(
+        catalog.task_report_title,
+        &[
+            (
+                catalog.task,
+                truncate_string(
+                    report
+                        .task_description
+                        .as_deref()
+                        .unwrap_or(catalog.no_description),
+                    40,
+                ),
+            ),
+            (
+                catalog.period,
+                format!(
+                    "{} - {}",
+                    locale.format_date(report.from),
+                    locale.format_date(report.to)
+                ),
+            ),
+        ],
+        &[Table {
+            headers: &catalog.task_report_columns,
+            columns: &[23.0, 120.0, 166.0],
+            rows,
+        }],
+        locale,
+        branding,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TaskReportEntry;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_render_task_pdf() -> anyhow::Result<()> {
+        let report = TaskReport {
+            task_id: 2,
+            task_description: Some(String::from("second task")),
+            from: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            to: NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+            entries: vec![TaskReportEntry {
+                employee_id: 1,
+                firstname: Some(String::from("bob")),
+                lastname: Some(String::from("baumeister")),
+                work_type: WorktimeType::Work,
+                minutes: 540,
+            }],
+            total_minutes: 540,
+        };
+
+        for locale in [Locale::De, Locale::En] {
+            let pdf = render_task_pdf(&report, locale, &Branding::default())?;
+
+            assert!(pdf.starts_with(b"%PDF"));
+        }
+        assert_eq!(work_type_label(WorktimeType::Ride, Locale::En), "Ride");
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+use super::branding::Branding;
+use super::table::{format_signed_minutes, render_table_report, Table};
+use super::{truncate_string, Locale};
+use crate::models::TeamReport;
+
+/// Renders the totals of every employee and the hours per task of a team report, the report of
+/// the whole company has no team name.
+pub fn render_team_pdf(
+    report: &TeamReport,
+    team_name: Option<&str>,
+    locale: Locale,
+    branding: &Branding,
+) -> anyhow::Result<Vec<u8>> {
+    let catalog = locale.catalog();
+    let mut employee_rows: Vec<(Vec<String>, bool)> = report
+        .employees
+        .iter()
+        .map(|employee| {
+            let name = format!(
+                "{} {}",
+                employee.firstname.as_deref().unwrap_or_default(),
+                employee.lastname.as_deref().unwrap_or_default()
+            );
+            (
+                vec![
+                    truncate_string(name.trim(), 22),
+                    format_signed_minutes(employee.work_minutes),
+                    format_signed_minutes(employee.ride_minutes),
+                    format_signed_minutes(employee.break_minutes),
+                    format_signed_minutes(employee.absence_minutes),
+                    format_signed_minutes(employee.target_minutes),
+                    format_signed_minutes(employee.overtime_minutes),
+                ],
+                false,
+            )
+        })
+        .collect();
+    employee_rows.push((
+        vec![
+            String::from(catalog.total),
+            format_signed_minutes(report.work_minutes),
+            format_signed_minutes(report.ride_minutes),
+            format_signed_minutes(report.break_minutes),
+            format_signed_minutes(report.absence_minutes),
+            format_signed_minutes(report.employees.iter().map(|e| e.target_minutes).sum()),
+            format_signed_minutes(report.overtime_minutes),
+        ],
+        true,
+    ));
+
+    let task_rows = report
+        .tasks
+        .iter()
+        .map(|task| {
+            (
+                vec![
+                    truncate_string(
+                        task.task_description
+                            .as_deref()
+                            .unwrap_or(catalog.no_description),
+                        60,
+                    ),
+                    format_signed_minutes(task.minutes),
+                ],
+                false,
+            )
+        })
+        .collect();
+
+    render_table_report(
+        catalog.team_title,
+        &[
+            (
+                catalog.team,
+                truncate_string(team_name.unwrap_or(catalog.all_employees), 40),
+            ),
+            (
+                catalog.period,
+                format!(
+                    "{} - {}",
+                    locale.format_date(report.from),
+                    locale.format_date(report.to)
+                ),
+            ),
+        ],
+        &[
+            Table {
+                headers: &catalog.team_columns,
+                columns: &[23.0, 72.0, 92.0, 112.0, 132.0, 152.0, 172.0],
+                rows: employee_rows,
+            },
+            Table {
+                headers: &catalog.task_hours_columns,
+                columns: &[23.0, 166.0],
+                rows: task_rows,
+            },
+        ],
+        locale,
+        branding,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{EmployeeTotals, TaskMinutes};
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_render_team_pdf() -> anyhow::Result<()> {
+        // given is a report with more employees than fit on one page
+        let employees = (0..40)
+            .map(|employee_id| EmployeeTotals {
+                employee_id,
+                firstname: Some(String::from("bob")),
+                lastname: Some(String::from("baumeister")),
+                work_minutes: 480,
+                ride_minutes: 60,
+                break_minutes: 30,
+                absence_minutes: 0,
+                target_minutes: 456,
+                overtime_minutes: 84,
+                tasks: Vec::new(),
+            })
+            .collect();
+        let report = TeamReport {
+            from: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            to: NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+            employees,
+            work_minutes: 40 * 480,
+            ride_minutes: 40 * 60,
+            break_minutes: 40 * 30,
+            absence_minutes: 0,
+            overtime_minutes: 40 * 84,
+            tasks: vec![TaskMinutes {
+                task_id: 1,
+                task_description: Some(String::from("first task")),
+                minutes: 40 * 540,
+            }],
+        };
+
+        for locale in [Locale::De, Locale::En] {
+            let pdf = render_team_pdf(&report, Some("Montage"), locale, &Branding::default())?;
+
+            assert!(pdf.starts_with(b"%PDF"));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,325 @@
+use std::fmt::Write;
+
+use hmac::{Hmac, Mac};
+use printpdf::*;
+use qrcode::{EcLevel, QrCode};
+use sha2::Sha256;
+use sqlx::PgPool;
+
+use super::locale::Catalog;
+use super::{create_rectangle, get_period_worktimes, ReportPeriod};
+use crate::download::{public_url, verification_path};
+use crate::models::{ReportVerification, Worktime};
+use crate::service::{employee, issued_report};
+
+/// Space the signature lines and the checksum need at the end of the report.
+pub(super) const SIGNATURE_BLOCK_HEIGHT: f64 = 58.0;
+
+// edge length of the QR code in mm
+const QR_CODE_SIZE: f64 = 24.0;
+
+// the checksum is the public id of the report, without the secret of the server nobody can compute
+// it from guessed worktimes
+fn verification_secret() -> String {
+    dotenvy::var("REPORT_VERIFICATION_SECRET")
+        .or_else(|_| dotenvy::var("JWT_SECRET"))
+        .expect("No secret was provided.")
+}
+
+fn keyed_hash(secret: &str, content: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC can take a key of any size");
+    mac.update(content.as_bytes());
+
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .fold(String::new(), |mut output, byte| {
+            let _ = write!(output, "{byte:02x}");
+            output
+        })
+}
+
+/// HMAC-SHA-256 with the secret of the server over everything the report is generated from,
+/// running timers are part of it without an end.
+pub fn content_hash(employee_id: i32, period: &ReportPeriod, worktimes: &[Worktime]) -> String {
+    let mut worktimes: Vec<&Worktime> = worktimes.iter().collect();
+    worktimes.sort_by_key(|w| (w.start_time, w.worktime_id));
+
+    let mut content = format!("timesheet:v2\n{}\n{}\n", employee_id, period);
+    for worktime in worktimes {
+        let _ = writeln!(
+            content,
+            "{};{};{:?};{};{}",
+            worktime.worktime_id,
+            worktime.task_id,
+            worktime.work_type,
+            worktime.start_time.to_rfc3339(),
+            worktime
+                .end_time
+                .map(|end| end.to_rfc3339())
+                .unwrap_or_default()
+        );
+    }
+
+    keyed_hash(&verification_secret(), &content)
+}
+
+/// Stores the checksum of the report so that it can be verified later on. While a timer of the
+/// period is running the report is provisional, its times still change and it gets no checksum.
+pub(super) async fn issue_report(
+    employee_id: i32,
+    period: &ReportPeriod,
+    worktimes: &[Worktime],
+    pool: &PgPool,
+) -> anyhow::Result<Option<String>> {
+    if worktimes.iter().any(|w| w.end_time.is_none()) {
+        return Ok(None);
+    }
+
+    let hash = content_hash(employee_id, period, worktimes);
+    issued_report::save_issued_report(&hash, employee_id, &period.to_string(), pool).await?;
+
+    Ok(Some(hash))
+}
+
+/// Compares the checksum of an issued report with the worktimes as they are stored now, None if
+/// no report with the checksum was generated.
+pub async fn verify_report(
+    hash: &str,
+    pool: &PgPool,
+) -> anyhow::Result<Option<ReportVerification>> {
+    let hash = hash.trim().to_ascii_lowercase();
+    let Some(issued) = issued_report::get_issued_report(&hash, pool).await? else {
+        return Ok(None);
+    };
+
+    let period: ReportPeriod = issued.period.parse()?;
+    let (from, to) = period.bounds()?;
+    let timezone = employee::get_timezone(&issued.employee_id, pool).await?;
+    let worktimes = get_period_worktimes(from, to, &timezone, pool, &issued.employee_id).await?;
+
+    Ok(Some(ReportVerification {
+        valid: content_hash(issued.employee_id, &period, &worktimes) == hash,
+        employee_id: issued.employee_id,
+        period: issued.period,
+        issued_at: issued.issued_at,
+    }))
+}
+
+fn draw_line(layer: &PdfLayerReference, x_left: f64, x_right: f64, y: f64) {
+    layer.add_shape(Line {
+        points: vec![
+            (Point::new(Mm(x_left), Mm(y)), false),
+            (Point::new(Mm(x_right), Mm(y)), false),
+        ],
+        is_closed: false,
+        has_fill: false,
+        has_stroke: true,
+        is_clipping_path: false,
+    });
+}
+
+// every dark module of the code is a filled square, the top left corner is at (x, y)
+fn draw_qr_code(layer: &PdfLayerReference, data: &str, x: f64, y: f64) -> anyhow::Result<()> {
+    let code = QrCode::with_error_correction_level(data, EcLevel::M)?;
+    let width = code.width();
+    let module_size = QR_CODE_SIZE / width as f64;
+
+    layer.set_fill_color(Color::Rgb(Rgb::new(0.0, 0.0, 0.0, None)));
+    for (index, module) in code.to_colors().into_iter().enumerate() {
+        if module != qrcode::Color::Dark {
+            continue;
+        }
+        let left = x + (index % width) as f64 * module_size;
+        let top = y - (index / width) as f64 * module_size;
+        layer.add_shape(create_rectangle(
+            left,
+            left + module_size,
+            top,
+            top - module_size,
+        ));
+    }
+
+    Ok(())
+}
+
+/// Draws the signature lines of employee and manager and below them the checksum with a QR code
+/// of the verification link, `top` is the upper edge of the block.
+pub(super) fn draw_signature_block(
+    layer: &PdfLayerReference,
+    catalog: &Catalog,
+    hash: Option<&str>,
+    top: f64,
+    font: &IndirectFontRef,
+) -> anyhow::Result<()> {
+    let black = Color::Rgb(Rgb::new(0.0, 0.0, 0.0, None));
+    layer.set_outline_color(black.clone());
+    layer.set_outline_thickness(0.5);
+    layer.set_fill_color(black);
+
+    let signature_y = top - 16.0;
+    draw_line(layer, 21.0, 95.0, signature_y);
+    draw_line(layer, 114.0, 188.0, signature_y);
+    layer.use_text(
+        catalog.employee_signature,
+        9.0,
+        Mm(21.0),
+        Mm(signature_y - 4.0),
+        font,
+    );
+    layer.use_text(
+        catalog.manager_signature,
+        9.0,
+        Mm(114.0),
+        Mm(signature_y - 4.0),
+        font,
+    );
+
+    let qr_top = signature_y - 10.0;
+    let Some(hash) = hash else {
+        layer.use_text(catalog.no_checksum, 8.0, Mm(21.0), Mm(qr_top - 6.0), font);
+        return Ok(());
+    };
+    let url = public_url(&verification_path(hash));
+    draw_qr_code(layer, &url, 21.0, qr_top)?;
+
+    layer.use_text(catalog.checksum, 8.0, Mm(50.0), Mm(qr_top - 6.0), font);
+    layer.use_text(hash, 8.0, Mm(50.0), Mm(qr_top - 10.0), font);
+    layer.use_text(catalog.verify_at, 8.0, Mm(50.0), Mm(qr_top - 16.0), font);
+    layer.use_text(url, 8.0, Mm(50.0), Mm(qr_top - 20.0), font);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::WorktimeType;
+    use crate::pdf::period::MonthPeriod;
+    use crate::service::worktime;
+    use chrono::{TimeZone, Utc};
+
+    const JANUARY: ReportPeriod = ReportPeriod::Month(MonthPeriod {
+        year: 2024,
+        month: 1,
+    });
+
+    fn worktime(worktime_id: i32, end_hour: Option<u32>) -> Worktime {
+        Worktime {
+            worktime_id,
+            employee_id: 1,
+            task_id: 1,
+            start_time: Utc.with_ymd_and_hms(2024, 1, 2, 8, 0, 0).unwrap(),
+            end_time: end_hour.map(|hour| Utc.with_ymd_and_hms(2024, 1, 2, hour, 0, 0).unwrap()),
+            timeduration: None,
+            work_type: WorktimeType::Work,
+            needs_review: false,
+            comment: None,
+            billable: true,
+        }
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let hash = content_hash(1, &JANUARY, &[worktime(1, Some(12))]);
+
+        assert_eq!(hash.len(), 64);
+        // the comment is not printed
+        let mut commented = worktime(1, Some(12));
+        commented.comment = Some(String::from("changed"));
+        assert_eq!(content_hash(1, &JANUARY, &[commented]), hash);
+        // but running timers are
+        assert_ne!(
+            content_hash(1, &JANUARY, &[worktime(1, Some(12)), worktime(2, None)]),
+            hash
+        );
+        // the end time, the employee and the period are
+        assert_ne!(content_hash(1, &JANUARY, &[worktime(1, Some(13))]), hash);
+        assert_ne!(content_hash(2, &JANUARY, &[worktime(1, Some(12))]), hash);
+        assert_ne!(
+            content_hash(1, &ReportPeriod::Year(2024), &[worktime(1, Some(12))]),
+            hash
+        );
+    }
+
+    #[test]
+    fn test_keyed_hash() {
+        let hash = keyed_hash("secret", "timesheet");
+
+        // the checksum depends on the secret and is not the plain SHA-256 of the content
+        assert_eq!(hash.len(), 64);
+        assert_eq!(keyed_hash("secret", "timesheet"), hash);
+        assert_ne!(keyed_hash("other secret", "timesheet"), hash);
+        assert_ne!(
+            hash,
+            "96dd98d8b3acd3ca63242eae418ce8755e99917e95f7c607cf705379a9388dce"
+        );
+    }
+
+    #[test]
+    fn test_draw_signature_block() -> anyhow::Result<()> {
+        let (doc, page, layer) = PdfDocument::new("Signature", Mm(210.0), Mm(297.0), "Layer 1");
+        let font = doc.add_builtin_font(BuiltinFont::Helvetica)?;
+
+        let layer = doc.get_page(page).get_layer(layer);
+        let catalog = crate::pdf::Locale::De.catalog();
+
+        draw_signature_block(&layer, catalog, Some(&"0".repeat(64)), 100.0, &font)?;
+        // provisional reports have no checksum
+        draw_signature_block(&layer, catalog, None, 40.0, &font)?;
+
+        assert!(doc.save_to_bytes()?.starts_with(b"%PDF"));
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/task.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+        "../../fixtures/worktime.sql"
+    ))]
+    async fn test_verify_report(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        // given is the report of january
+        crate::pdf::render_pdf(
+            JANUARY,
+            crate::pdf::HeaderColor::DefaultGrey,
+            crate::pdf::Locale::De,
+            &pool,
+            &1,
+        )
+        .await?;
+        let hash = sqlx::query_scalar!(r#"SELECT hash as "hash!: String" FROM issued_report"#)
+            .fetch_one(&pool)
+            .await?;
+
+        // then the unchanged report is valid, also with an upper case checksum
+        let verification = verify_report(&hash.to_uppercase(), &pool).await?.unwrap();
+        assert!(verification.valid);
+        assert_eq!(verification.employee_id, 1);
+        assert_eq!(verification.period, "2024-01");
+
+        // when a worktime of the month is changed afterwards
+        let first = worktime::get_timers_in_boundary(
+            &1,
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap().into(),
+            Utc.with_ymd_and_hms(2024, 2, 1, 0, 0, 0).unwrap().into(),
+            &pool,
+        )
+        .await?
+        .remove(0);
+        sqlx::query!(
+            "UPDATE worktime SET end_time = end_time + INTERVAL '1 minute' WHERE worktime_id = $1",
+            first.worktime_id
+        )
+        .execute(&pool)
+        .await?;
+
+        // then the report does not match anymore
+        assert!(!verify_report(&hash, &pool).await?.unwrap().valid);
+        assert!(verify_report(&"0".repeat(64), &pool).await?.is_none());
+
+        Ok(())
+    }
+}
@@ -1,3 +1,16 @@
+pub mod absence;
+pub mod archived_report;
+pub mod billing;
+pub mod company;
 pub mod employee;
+pub mod issued_report;
+pub mod notification;
+pub mod pagination;
+pub mod project;
+pub mod report;
+pub mod report_delivery;
 pub mod task;
+pub mod task_assignment;
+pub mod task_budget;
+pub mod team;
 pub mod worktime;
@@ -0,0 +1,246 @@
+use chrono::NaiveDate;
+use sqlx::postgres::types::PgInterval;
+
+use crate::{
+    models::{self, AbsenceStatus, AbsenceType},
+    service::{notification, report},
+};
+
+/// Absences of the employees which overlap the days between `from` and `to` (both inclusive),
+/// every status if none is given.
+pub(crate) async fn get_absences(
+    employee_ids: &[i32],
+    from: NaiveDate,
+    to: NaiveDate,
+    status: Option<AbsenceStatus>,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Vec<models::Absence>> {
+    sqlx::query_as!(
+        models::Absence,
+        r#"SELECT absence_id, employee_id, absence_type as "absence_type: AbsenceType", start_date, end_date, status as "status: AbsenceStatus", comment
+        FROM absence WHERE employee_id = ANY($1) AND start_date <= $3 AND end_date >= $2 AND ($4::ABSENCE_STATUS IS NULL OR status = $4)
+        ORDER BY employee_id, start_date"#,
+        employee_ids,
+        from,
+        to,
+        status as Option<AbsenceStatus>
+    )
+    .fetch_all(pool)
+    .await
+}
+
+// rejected absences do not block the days
+pub(crate) async fn has_overlapping_absence(
+    employee_id: &i32,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<bool> {
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM absence WHERE employee_id = $1 AND start_date <= $3 AND end_date >= $2 AND status <> 'rejected') as "exists!""#,
+        employee_id,
+        start_date,
+        end_date
+    )
+    .fetch_one(pool)
+    .await
+}
+
+pub(crate) async fn request_absence(
+    employee_id: &i32,
+    absence_type: AbsenceType,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    comment: Option<&str>,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<models::Absence> {
+    sqlx::query_as!(
+        models::Absence,
+        r#"INSERT INTO absence (employee_id, absence_type, start_date, end_date, comment) VALUES ($1, $2, $3, $4, $5)
+        RETURNING absence_id, employee_id, absence_type as "absence_type: AbsenceType", start_date, end_date, status as "status: AbsenceStatus", comment"#,
+        employee_id,
+        absence_type as AbsenceType,
+        start_date,
+        end_date,
+        comment
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Deletes an absence of the employee as long as it is not reviewed.
+pub(crate) async fn cancel_absence(
+    absence_id: i32,
+    employee_id: &i32,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Option<models::Absence>> {
+    sqlx::query_as!(
+        models::Absence,
+        r#"DELETE FROM absence WHERE absence_id = $1 AND employee_id = $2 AND status = 'requested'
+        RETURNING absence_id, employee_id, absence_type as "absence_type: AbsenceType", start_date, end_date, status as "status: AbsenceStatus", comment"#,
+        absence_id,
+        employee_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Approves or rejects an absence and notifies the employee about it.
+pub(crate) async fn review_absence(
+    absence_id: i32,
+    status: AbsenceStatus,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Option<models::Absence>> {
+    let mut transaction = pool.begin().await?;
+
+    let absence = sqlx::query_as!(
+        models::Absence,
+        r#"UPDATE absence SET status = $2 WHERE absence_id = $1
+        RETURNING absence_id, employee_id, absence_type as "absence_type: AbsenceType", start_date, end_date, status as "status: AbsenceStatus", comment"#,
+        absence_id,
+        status as AbsenceStatus
+    )
+    .fetch_optional(&mut *transaction)
+    .await?;
+
+    if let Some(absence) = &absence {
+        let message = format!(
+            "Your absence from {} to {} has been {}.",
+            absence.start_date,
+            absence.end_date,
+            match absence.status {
+                AbsenceStatus::Requested => "reset to requested",
+                AbsenceStatus::Approved => "approved",
+                AbsenceStatus::Rejected => "rejected",
+            }
+        );
+        notification::create_notification(&absence.employee_id, &message, &mut *transaction)
+            .await?;
+    }
+
+    transaction.commit().await?;
+
+    Ok(absence)
+}
+
+/// Target time of the absent weekdays between `from` and `to` (both inclusive), which is what
+/// the absence counts as worked.
+pub(crate) fn absence_minutes(
+    absence: &models::Absence,
+    weekly_time: Option<&PgInterval>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> i64 {
+    let start = absence.start_date.max(from);
+    let end = absence.end_date.min(to);
+    if end < start {
+        return 0;
+    }
+
+    report::target_minutes(weekly_time, start, end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+    ))]
+    async fn test_request_and_review_absence(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        // given is a requested vacation from thursday to tuesday
+        let absence = request_absence(
+            &1,
+            AbsenceType::Vacation,
+            day(4),
+            day(9),
+            Some("Ski"),
+            &pool,
+        )
+        .await?;
+        assert_eq!(absence.status, AbsenceStatus::Requested);
+        assert!(has_overlapping_absence(&1, day(9), day(12), &pool).await?);
+        assert!(!has_overlapping_absence(&1, day(10), day(12), &pool).await?);
+        assert!(!has_overlapping_absence(&2, day(4), day(9), &pool).await?);
+
+        // when it is approved
+        let approved = review_absence(absence.absence_id, AbsenceStatus::Approved, &pool)
+            .await?
+            .unwrap();
+
+        // then the employee is notified and can not cancel it anymore
+        assert_eq!(approved.status, AbsenceStatus::Approved);
+        let notifications = notification::get_notifications(&1, &pool).await?;
+        assert_eq!(notifications.len(), 1);
+        assert!(notifications[0].message.contains("approved"));
+        assert!(cancel_absence(absence.absence_id, &1, &pool)
+            .await?
+            .is_none());
+
+        // and only the overlapping approved absences are listed
+        let listed = get_absences(
+            &[1, 2],
+            day(1),
+            day(4),
+            Some(AbsenceStatus::Approved),
+            &pool,
+        )
+        .await?;
+        assert_eq!(listed.len(), 1);
+        assert!(get_absences(&[1], day(10), day(31), None, &pool)
+            .await?
+            .is_empty());
+
+        // requested absences can only be cancelled by their employee
+        let sickness =
+            request_absence(&1, AbsenceType::Sickness, day(15), day(15), None, &pool).await?;
+        assert!(cancel_absence(sickness.absence_id, &2, &pool)
+            .await?
+            .is_none());
+        assert!(cancel_absence(sickness.absence_id, &1, &pool)
+            .await?
+            .is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_absence_minutes() {
+        let weekly_time = PgInterval {
+            months: 0,
+            days: 0,
+            microseconds: 40 * 60 * 60 * 1_000_000,
+        };
+        // thursday to tuesday has four weekdays
+        let absence = models::Absence {
+            absence_id: 1,
+            employee_id: 1,
+            absence_type: AbsenceType::Vacation,
+            start_date: day(4),
+            end_date: day(9),
+            status: AbsenceStatus::Approved,
+            comment: None,
+        };
+
+        assert_eq!(
+            absence_minutes(&absence, Some(&weekly_time), day(1), day(31)),
+            4 * 8 * 60
+        );
+        // only the days within the period count
+        assert_eq!(
+            absence_minutes(&absence, Some(&weekly_time), day(8), day(31)),
+            2 * 8 * 60
+        );
+        assert_eq!(
+            absence_minutes(&absence, Some(&weekly_time), day(10), day(31)),
+            0
+        );
+        assert_eq!(absence_minutes(&absence, None, day(1), day(31)), 0);
+    }
+}
@@ -0,0 +1,127 @@
+use crate::models;
+
+// there is no update, the archive keeps the files as they were generated. A report with the
+// same checksum is only archived once, its first file is kept, also if two are saved at once.
+pub(crate) async fn save_archived_report(
+    employee_id: i32,
+    period: &str,
+    hash: &str,
+    content: &[u8],
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<i32> {
+    sqlx::query!(
+        "INSERT INTO archived_report (employee_id, period, hash, content) VALUES ($1, $2, $3, $4)
+        ON CONFLICT (employee_id, period, hash) DO NOTHING",
+        employee_id,
+        period,
+        hash,
+        content
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query_scalar!(
+        "SELECT report_id FROM archived_report WHERE employee_id = $1 AND period = $2 AND hash = $3",
+        employee_id,
+        period,
+        hash
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// The reports of the employee, the newest first, only the ones of the period if it is given.
+pub(crate) async fn get_archived_reports(
+    employee_id: i32,
+    period: Option<&str>,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Vec<models::ArchivedReport>> {
+    sqlx::query_as!(
+        models::ArchivedReport,
+        r#"SELECT report_id, employee_id, period, hash, OCTET_LENGTH(content) as "size_bytes!", created_at FROM archived_report
+        WHERE employee_id = $1 AND ($2::VARCHAR IS NULL OR period = $2)
+        ORDER BY created_at DESC, report_id DESC"#,
+        employee_id,
+        period
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub(crate) async fn get_archived_report_file(
+    report_id: i32,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Option<models::ArchivedReportFile>> {
+    sqlx::query_as!(
+        models::ArchivedReportFile,
+        "SELECT employee_id, period, content FROM archived_report WHERE report_id = $1",
+        report_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::issued_report;
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql"
+    ))]
+    async fn test_archived_report(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        let hash = "a".repeat(64);
+        let changed_hash = "b".repeat(64);
+        issued_report::save_issued_report(&hash, 1, "2024-01", &pool).await?;
+        issued_report::save_issued_report(&changed_hash, 1, "2024-01", &pool).await?;
+
+        // when the same report is archived twice
+        let first = save_archived_report(1, "2024-01", &hash, b"%PDF-1", &pool).await?;
+        assert_eq!(
+            save_archived_report(1, "2024-01", &hash, b"%PDF-1b", &pool).await?,
+            first
+        );
+        // and once more after the worktimes changed
+        let second = save_archived_report(1, "2024-01", &changed_hash, b"%PDF-22", &pool).await?;
+
+        // then the first file of every checksum is kept, the newest is listed first
+        let reports = get_archived_reports(1, None, &pool).await?;
+        assert_eq!(
+            reports.iter().map(|r| r.report_id).collect::<Vec<_>>(),
+            vec![second, first]
+        );
+        assert_eq!(reports[0].size_bytes, 7);
+        assert!(get_archived_reports(1, Some("2024-02"), &pool)
+            .await?
+            .is_empty());
+        assert!(get_archived_reports(2, None, &pool).await?.is_empty());
+
+        let file = get_archived_report_file(first, &pool).await?.unwrap();
+        assert_eq!(file.content, b"%PDF-1");
+        assert_eq!(file.employee_id, 1);
+
+        // and can not be changed or deleted afterwards, not even with the employee
+        assert!(sqlx::query!(
+            "UPDATE archived_report SET content = '' WHERE report_id = $1",
+            first
+        )
+        .execute(&pool)
+        .await
+        .is_err());
+        assert!(
+            sqlx::query!("DELETE FROM archived_report WHERE report_id = $1", first)
+                .execute(&pool)
+                .await
+                .is_err()
+        );
+        assert!(sqlx::query!("DELETE FROM employee WHERE employee_id = 1")
+            .execute(&pool)
+            .await
+            .is_err());
+        assert_eq!(get_archived_reports(1, None, &pool).await?.len(), 2);
+
+        Ok(())
+    }
+}
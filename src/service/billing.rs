@@ -0,0 +1,211 @@
+use chrono::NaiveDate;
+
+use crate::{
+    models::{self, WorktimeType},
+    service::project,
+    time_utils,
+};
+
+pub(crate) async fn get_rates(pool: &sqlx::PgPool) -> sqlx::Result<Vec<models::BillingRate>> {
+    sqlx::query_as!(
+        models::BillingRate,
+        r#"SELECT rate_id, employee_id, task_id, work_type as "work_type: WorktimeType", rate_cents FROM billing_rate
+        ORDER BY task_id NULLS FIRST, employee_id NULLS FIRST, work_type NULLS FIRST"#
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Creates the rate of the scope or replaces the amount of the existing one.
+pub(crate) async fn set_rate(
+    employee_id: Option<i32>,
+    task_id: Option<i32>,
+    work_type: Option<WorktimeType>,
+    rate_cents: i64,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<models::BillingRate> {
+    sqlx::query_as!(
+        models::BillingRate,
+        r#"INSERT INTO billing_rate (employee_id, task_id, work_type, rate_cents) VALUES ($1, $2, $3, $4)
+        ON CONFLICT (employee_id, task_id, work_type) DO UPDATE SET rate_cents = EXCLUDED.rate_cents
+        RETURNING rate_id, employee_id, task_id, work_type as "work_type: WorktimeType", rate_cents"#,
+        employee_id,
+        task_id,
+        work_type as Option<WorktimeType>,
+        rate_cents
+    )
+    .fetch_one(pool)
+    .await
+}
+
+pub(crate) async fn delete_rate(
+    rate_id: i32,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Option<models::BillingRate>> {
+    sqlx::query_as!(
+        models::BillingRate,
+        r#"DELETE FROM billing_rate WHERE rate_id = $1
+        RETURNING rate_id, employee_id, task_id, work_type as "work_type: WorktimeType", rate_cents"#,
+        rate_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Billable work and ride time per task between `from` and `to` (both inclusive) with the amount
+/// of the applying rates, only of the projects of the customer if it is set. The days are taken
+/// in the company timezone and running timers are left out.
+pub(crate) async fn get_billing_summary(
+    customer_id: Option<i32>,
+    from: NaiveDate,
+    to: NaiveDate,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<models::BillingSummary> {
+    let customer_name = match customer_id {
+        Some(customer_id) => Some(
+            project::get_customer(customer_id, pool)
+                .await?
+                .ok_or(sqlx::Error::RowNotFound)?
+                .name,
+        ),
+        None => None,
+    };
+    let timezone = time_utils::company_timezone();
+    let (start, _) = time_utils::day_bounds(from, &timezone);
+    let (_, end) = time_utils::day_bounds(to, &timezone);
+
+    // worktimes over the bounds are billed with their part within
+    let lines = sqlx::query_as!(
+        models::BillingLine,
+        r#"SELECT b.task_id as "task_id!", t.task_description,
+        COALESCE((EXTRACT(EPOCH FROM SUM(b.duration) FILTER (WHERE b.billable)) / 60)::BIGINT, 0) as "billable_minutes!",
+        COALESCE((EXTRACT(EPOCH FROM SUM(b.duration) FILTER (WHERE b.billable AND b.rate_cents IS NULL)) / 60)::BIGINT, 0) as "unrated_minutes!",
+        COALESCE((EXTRACT(EPOCH FROM SUM(b.duration) FILTER (WHERE NOT b.billable)) / 60)::BIGINT, 0) as "non_billable_minutes!",
+        COALESCE(ROUND(SUM(EXTRACT(EPOCH FROM b.duration) / 3600 * b.rate_cents) FILTER (WHERE b.billable))::BIGINT, 0) as "amount_cents!"
+        FROM (
+            SELECT w.task_id, w.billable, LEAST(w.end_time, $2) - GREATEST(w.start_time, $1) as duration, r.rate_cents
+            FROM worktime w
+            LEFT JOIN LATERAL (
+                SELECT rate_cents FROM billing_rate r
+                WHERE (r.employee_id IS NULL OR r.employee_id = w.employee_id)
+                AND (r.task_id IS NULL OR r.task_id = w.task_id)
+                AND (r.work_type IS NULL OR r.work_type = w.work_type)
+                ORDER BY r.task_id IS NOT NULL DESC, r.employee_id IS NOT NULL DESC, r.work_type IS NOT NULL DESC
+                LIMIT 1
+            ) r ON TRUE
+            WHERE w.work_type <> 'break' AND w.end_time IS NOT NULL AND w.start_time < $2 AND w.end_time > $1
+        ) b
+        JOIN task t ON t.task_id = b.task_id
+        LEFT JOIN project p ON p.project_id = t.project_id
+        WHERE $3::INTEGER IS NULL OR p.customer_id = $3
+        GROUP BY b.task_id, t.task_description
+        ORDER BY b.task_id"#,
+        start,
+        end,
+        customer_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(models::BillingSummary {
+        customer_id,
+        customer_name,
+        from,
+        to,
+        billable_minutes: lines.iter().map(|line| line.billable_minutes).sum(),
+        unrated_minutes: lines.iter().map(|line| line.unrated_minutes).sum(),
+        amount_cents: lines.iter().map(|line| line.amount_cents).sum(),
+        lines,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::worktime;
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/task.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+        "../../fixtures/worktime.sql"
+    ))]
+    async fn test_get_billing_summary(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        let from = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+        let task_line = |summary: &models::BillingSummary, task_id: i32| {
+            summary
+                .lines
+                .iter()
+                .find(|line| line.task_id == task_id)
+                .map(|line| {
+                    (
+                        line.billable_minutes,
+                        line.unrated_minutes,
+                        line.non_billable_minutes,
+                        line.amount_cents,
+                    )
+                })
+        };
+        // the work and rides of bob on task 2, the break of leck is never billed
+        let work = 3 * 3 * 60;
+        let rides = 120 + 150 + 120 + 150 + 120 + 180 + 150 + 120;
+        let total = work + rides;
+
+        // given are no rates, then the time is billable but unrated
+        let summary = get_billing_summary(None, from, to, &pool).await?;
+        assert_eq!(task_line(&summary, 2), Some((total, total, 0, 0)));
+        assert_eq!(summary.amount_cents, 0);
+
+        // when there is a default, a ride and a task rate
+        set_rate(None, None, None, 6000, &pool).await?;
+        set_rate(None, None, Some(WorktimeType::Ride), 3000, &pool).await?;
+        set_rate(None, Some(3), None, 9000, &pool).await?;
+
+        // then the most specific one applies
+        let summary = get_billing_summary(None, from, to, &pool).await?;
+        assert_eq!(
+            task_line(&summary, 2),
+            Some((total, 0, 0, work * 100 + rides * 50))
+        );
+        let (task_3_minutes, _, _, task_3_amount) = task_line(&summary, 3).unwrap();
+        assert_eq!(task_3_amount, task_3_minutes * 150);
+        assert_eq!(
+            summary.amount_cents,
+            summary
+                .lines
+                .iter()
+                .map(|line| line.amount_cents)
+                .sum::<i64>()
+        );
+
+        // when a ride is not billable and bob has an own rate
+        worktime::update_timer(4, None, None, None, None, None, Some(false), &pool).await?;
+        let rate = set_rate(Some(1), None, None, 7000, &pool).await?;
+        // setting the same scope again only changes the amount
+        assert_eq!(
+            set_rate(Some(1), None, None, 7200, &pool).await?.rate_id,
+            rate.rate_id
+        );
+        assert_eq!(get_rates(&pool).await?.len(), 4);
+
+        // then the employee rate wins over the work type rate
+        let summary = get_billing_summary(None, from, to, &pool).await?;
+        assert_eq!(
+            task_line(&summary, 2),
+            Some((total - 120, 0, 120, (total - 120) * 120))
+        );
+
+        // a customer without projects has nothing to bill
+        let customer = project::create_customer("Cloud City", &pool).await?;
+        let summary = get_billing_summary(Some(customer.customer_id), from, to, &pool).await?;
+        assert_eq!(summary.customer_name.as_deref(), Some("Cloud City"));
+        assert!(summary.lines.is_empty());
+
+        assert!(delete_rate(rate.rate_id, &pool).await?.is_some());
+        assert!(delete_rate(rate.rate_id, &pool).await?.is_none());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+use crate::models;
+
+pub(crate) async fn get_company_settings(
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<models::CompanySettings> {
+    sqlx::query_as!(
+        models::CompanySettings,
+        r#"SELECT name, address, logo, header_color as "header_color: String", accent_color as "accent_color: String" FROM company_settings"#
+    )
+    .fetch_one(pool)
+    .await
+}
+
+pub(crate) async fn update_company_settings(
+    name: &str,
+    address: Option<&str>,
+    header_color: Option<&str>,
+    accent_color: Option<&str>,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<models::CompanySettings> {
+    sqlx::query_as!(
+        models::CompanySettings,
+        r#"UPDATE company_settings SET name = $1, address = $2, header_color = $3, accent_color = $4
+        RETURNING name, address, logo, header_color as "header_color: String", accent_color as "accent_color: String""#,
+        name,
+        address,
+        header_color,
+        accent_color
+    )
+    .fetch_one(pool)
+    .await
+}
+
+// None removes the logo from the reports
+pub(crate) async fn set_company_logo(logo: Option<&[u8]>, pool: &sqlx::PgPool) -> sqlx::Result<()> {
+    sqlx::query!("UPDATE company_settings SET logo = $1", logo)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[sqlx::test]
+    async fn test_update_company_settings(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        // given are the settings created by the migration
+        let settings = get_company_settings(&pool).await?;
+        assert_eq!(settings.name, "Schmidt's Handwerksbetrieb");
+        assert!(settings.logo.is_none());
+
+        // when they are changed
+        let settings = update_company_settings(
+            "Meister GmbH",
+            Some("Hauptstraße 1\n12345 Berlin"),
+            Some("#a50d0d"),
+            None,
+            &pool,
+        )
+        .await?;
+        set_company_logo(Some(&[1, 2, 3]), &pool).await?;
+
+        // then the single row is updated
+        assert_eq!(settings.name, "Meister GmbH");
+        assert_eq!(settings.header_color.as_deref(), Some("#a50d0d"));
+        assert_eq!(get_company_settings(&pool).await?.logo, Some(vec![1, 2, 3]));
+
+        Ok(())
+    }
+}
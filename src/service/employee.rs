@@ -1,8 +1,10 @@
+use rand::{distributions::Alphanumeric, Rng};
 use sqlx::PgPool;
 
 use crate::{
     models,
     security::{create_salt, hash_password},
+    time_utils,
 };
 
 pub async fn update_password(
@@ -15,7 +17,7 @@ pub async fn update_password(
     let hashed_password = hash_password(new_password, pool, employee_id).await?;
     sqlx::query_as!(
         models::Employee,
-        "UPDATE employee SET password = $2 WHERE employee_id = $1 RETURNING employee_id, firstname, lastname, email, weekly_time, address_id",
+        r#"UPDATE employee SET password = $2 WHERE employee_id = $1 RETURNING employee_id, firstname, lastname, email, weekly_time, address_id, role as "role: models::EmployeeRole", timezone, personnel_number"#,
         employee_id,
         hashed_password,
     )
@@ -24,7 +26,122 @@ pub async fn update_password(
 }
 
 pub async fn get_employee(employee_id: &i32, pool: &PgPool) -> sqlx::Result<models::Employee> {
-    sqlx::query_as!(models::Employee, "SELECT employee_id, firstname, lastname, email, weekly_time, address_id FROM employee WHERE employee_id = $1", employee_id,).fetch_one(pool).await
+    sqlx::query_as!(models::Employee, r#"SELECT employee_id, firstname, lastname, email, weekly_time, address_id, role as "role: models::EmployeeRole", timezone, personnel_number FROM employee WHERE employee_id = $1"#, employee_id,).fetch_one(pool).await
+}
+
+pub async fn get_employees_by_ids(
+    employee_ids: &[i32],
+    pool: &PgPool,
+) -> sqlx::Result<Vec<models::Employee>> {
+    sqlx::query_as!(
+        models::Employee,
+        r#"SELECT employee_id, firstname, lastname, email, weekly_time, address_id, role as "role: models::EmployeeRole", timezone, personnel_number FROM employee WHERE employee_id = ANY($1)"#,
+        employee_ids,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_all_employees(pool: &PgPool) -> sqlx::Result<Vec<models::Employee>> {
+    sqlx::query_as!(
+        models::Employee,
+        r#"SELECT employee_id, firstname, lastname, email, weekly_time, address_id, role as "role: models::EmployeeRole", timezone, personnel_number FROM employee ORDER BY employee_id"#,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn update_timezone(
+    employee_id: &i32,
+    timezone: Option<String>,
+    pool: &PgPool,
+) -> sqlx::Result<models::Employee> {
+    sqlx::query_as!(
+        models::Employee,
+        r#"UPDATE employee SET timezone = $2 WHERE employee_id = $1 RETURNING employee_id, firstname, lastname, email, weekly_time, address_id, role as "role: models::EmployeeRole", timezone, personnel_number"#,
+        employee_id,
+        timezone,
+    )
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn update_personnel_number(
+    employee_id: &i32,
+    personnel_number: Option<String>,
+    pool: &PgPool,
+) -> sqlx::Result<models::Employee> {
+    sqlx::query_as!(
+        models::Employee,
+        r#"UPDATE employee SET personnel_number = $2 WHERE employee_id = $1 RETURNING employee_id, firstname, lastname, email, weekly_time, address_id, role as "role: models::EmployeeRole", timezone, personnel_number"#,
+        employee_id,
+        personnel_number,
+    )
+    .fetch_one(pool)
+    .await
+}
+
+// replaces the calendar token of the employee, links with the old token stop working
+pub async fn create_calendar_token(employee_id: &i32, pool: &PgPool) -> sqlx::Result<String> {
+    let token: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(48)
+        .map(char::from)
+        .collect();
+
+    sqlx::query!(
+        "UPDATE employee SET calendar_token = $2 WHERE employee_id = $1",
+        employee_id,
+        token,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(token)
+}
+
+pub async fn delete_calendar_token(employee_id: &i32, pool: &PgPool) -> sqlx::Result<bool> {
+    let result = sqlx::query!(
+        "UPDATE employee SET calendar_token = NULL WHERE employee_id = $1 AND calendar_token IS NOT NULL",
+        employee_id,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn get_employee_id_by_calendar_token(
+    token: &str,
+    pool: &PgPool,
+) -> sqlx::Result<Option<i32>> {
+    sqlx::query_scalar!(
+        "SELECT employee_id FROM employee WHERE calendar_token = $1",
+        token,
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+// the timezone of the employee or the company timezone if the employee has none
+pub async fn get_timezone(employee_id: &i32, pool: &PgPool) -> sqlx::Result<chrono_tz::Tz> {
+    let timezone = sqlx::query_scalar!(
+        "SELECT timezone FROM employee WHERE employee_id = $1",
+        employee_id,
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(time_utils::resolve_timezone(timezone.as_deref()))
+}
+
+pub async fn get_role(employee_id: &i32, pool: &PgPool) -> sqlx::Result<models::EmployeeRole> {
+    sqlx::query_scalar!(
+        r#"SELECT role as "role: models::EmployeeRole" FROM employee WHERE employee_id = $1"#,
+        employee_id,
+    )
+    .fetch_one(pool)
+    .await
 }
 
 pub async fn initial_password(employee_id: &i32, pool: &PgPool) -> sqlx::Result<bool> {
@@ -56,6 +173,86 @@ mod tests {
         assert!(employee.firstname.is_some());
         assert_eq!(employee.firstname.as_ref().unwrap(), "bob");
         assert_eq!(employee.address_id, 1);
+        assert_eq!(employee.role, models::EmployeeRole::Employee);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+    ))]
+    async fn test_get_employees_by_ids(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        let employees = get_employees_by_ids(&[2, 100], &pool).await?;
+
+        assert_eq!(employees.len(), 1);
+        assert_eq!(employees[0].employee_id, 2);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+    ))]
+    async fn test_update_timezone(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        std::env::set_var("COMPANY_TIMEZONE", "Europe/Berlin");
+        assert_eq!(get_timezone(&1, &pool).await?, chrono_tz::Europe::Berlin);
+
+        let employee = update_timezone(&1, Some(String::from("America/New_York")), &pool).await?;
+
+        assert_eq!(employee.timezone.as_deref(), Some("America/New_York"));
+        assert_eq!(get_timezone(&1, &pool).await?, chrono_tz::America::New_York);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+    ))]
+    async fn test_calendar_token(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        let token = create_calendar_token(&1, &pool).await?;
+        assert_eq!(
+            get_employee_id_by_calendar_token(&token, &pool).await?,
+            Some(1)
+        );
+
+        // a new token replaces the old one
+        let new_token = create_calendar_token(&1, &pool).await?;
+        assert_eq!(
+            get_employee_id_by_calendar_token(&token, &pool).await?,
+            None
+        );
+
+        assert!(delete_calendar_token(&1, &pool).await?);
+        assert!(!delete_calendar_token(&1, &pool).await?);
+        assert_eq!(
+            get_employee_id_by_calendar_token(&new_token, &pool).await?,
+            None
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+    ))]
+    async fn test_update_personnel_number(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        let employee = update_personnel_number(&2, Some(String::from("00042")), &pool).await?;
+        assert_eq!(employee.personnel_number.as_deref(), Some("00042"));
+
+        // personnel numbers are unique
+        assert!(
+            update_personnel_number(&1, Some(String::from("00042")), &pool)
+                .await
+                .is_err()
+        );
 
         Ok(())
     }
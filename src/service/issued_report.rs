@@ -0,0 +1,62 @@
+use crate::models;
+
+// the same worktimes always give the same checksum, a report which is generated again is kept
+// with the date it was issued first
+pub(crate) async fn save_issued_report(
+    hash: &str,
+    employee_id: i32,
+    period: &str,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<()> {
+    sqlx::query!(
+        "INSERT INTO issued_report (hash, employee_id, period) VALUES ($1, $2, $3) ON CONFLICT (hash) DO NOTHING",
+        hash,
+        employee_id,
+        period
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub(crate) async fn get_issued_report(
+    hash: &str,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Option<models::IssuedReport>> {
+    sqlx::query_as!(
+        models::IssuedReport,
+        r#"SELECT employee_id, period, issued_at FROM issued_report WHERE hash = $1"#,
+        hash
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql"
+    ))]
+    async fn test_save_issued_report(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        let hash = "a".repeat(64);
+
+        // when the same report is saved twice
+        save_issued_report(&hash, 1, "2024-01", &pool).await?;
+        let first = get_issued_report(&hash, &pool).await?.unwrap();
+        save_issued_report(&hash, 1, "2024-01", &pool).await?;
+
+        // then it is kept with the first date
+        let second = get_issued_report(&hash, &pool).await?.unwrap();
+        assert_eq!(second.employee_id, 1);
+        assert_eq!(second.period, "2024-01");
+        assert_eq!(second.issued_at, first.issued_at);
+        assert!(get_issued_report(&"b".repeat(64), &pool).await?.is_none());
+
+        Ok(())
+    }
+}
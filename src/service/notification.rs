@@ -0,0 +1,87 @@
+use crate::models;
+
+pub(crate) async fn get_notifications(
+    employee_id: &i32,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Vec<models::Notification>> {
+    sqlx::query_as!(
+        models::Notification,
+        "SELECT notification_id, employee_id, message, created_at, read_at FROM notification WHERE employee_id = $1 ORDER BY created_at DESC",
+        employee_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub(crate) async fn create_notification(
+    employee_id: &i32,
+    message: &str,
+    executor: impl sqlx::PgExecutor<'_>,
+) -> sqlx::Result<models::Notification> {
+    sqlx::query_as!(
+        models::Notification,
+        "INSERT INTO notification (employee_id, message) VALUES ($1, $2) RETURNING notification_id, employee_id, message, created_at, read_at",
+        employee_id,
+        message
+    )
+    .fetch_one(executor)
+    .await
+}
+
+pub(crate) async fn mark_notification_read(
+    notification_id: i32,
+    employee_id: &i32,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Option<models::Notification>> {
+    sqlx::query_as!(
+        models::Notification,
+        "UPDATE notification SET read_at = COALESCE(read_at, NOW()) WHERE notification_id = $1 AND employee_id = $2 RETURNING notification_id, employee_id, message, created_at, read_at",
+        notification_id,
+        employee_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+    ))]
+    async fn test_create_and_get_notifications(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        create_notification(&1, "first", &pool).await?;
+        create_notification(&2, "other employee", &pool).await?;
+
+        let notifications = get_notifications(&1, &pool).await?;
+
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].employee_id, 1);
+        assert_eq!(notifications[0].message, "first");
+        assert_eq!(notifications[0].read_at, None);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+    ))]
+    async fn test_mark_notification_read(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        let notification = create_notification(&1, "first", &pool).await?;
+
+        // other employees can not mark the notification as read
+        let foreign = mark_notification_read(notification.notification_id, &2, &pool).await?;
+        assert!(foreign.is_none());
+
+        let read = mark_notification_read(notification.notification_id, &1, &pool).await?;
+        assert!(read.is_some());
+        assert_ne!(read.unwrap().read_at, None);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,226 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, QueryBuilder};
+
+use crate::models::SortDirection;
+
+/// Position of a row in a sorted list, the sort key is encoded so that the cursor stays valid
+/// when its row is deleted.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cursor {
+    /// the sort expression of the row as text
+    pub key: String,
+    pub id: i32,
+}
+
+/// A row together with its cursor.
+#[derive(sqlx::FromRow)]
+pub struct Keyed<T> {
+    #[sqlx(flatten)]
+    pub item: T,
+    pub sort_key: String,
+    pub cursor_id: i32,
+}
+
+/// Cursor arguments of a relay connection.
+#[derive(Clone, Debug, Default)]
+pub struct PageRequest {
+    pub after: Option<Cursor>,
+    pub before: Option<Cursor>,
+    pub first: Option<usize>,
+    pub last: Option<usize>,
+}
+
+impl PageRequest {
+    // pages requested with `last` are fetched in reverse order and turned around afterwards
+    fn backwards(&self) -> bool {
+        self.first.is_none() && self.last.is_some()
+    }
+
+    fn limit(&self) -> Option<usize> {
+        self.first.or(self.last)
+    }
+}
+
+pub struct Page<T> {
+    pub items: Vec<(Cursor, T)>,
+    pub has_previous_page: bool,
+    pub has_next_page: bool,
+}
+
+/// Describes how the rows of a table are ordered, the id column breaks ties.
+pub struct Keyset<'a> {
+    pub id_column: &'a str,
+    pub sort_expression: &'a str,
+    /// SQL type of the sort expression, the key of a cursor is cast back to it
+    pub sort_type: &'a str,
+    pub direction: SortDirection,
+}
+
+impl Keyset<'_> {
+    fn push_key(&self, query_builder: &mut QueryBuilder<Postgres>) {
+        query_builder
+            .push("(")
+            .push(self.sort_expression)
+            .push(", ")
+            .push(self.id_column)
+            .push(")");
+    }
+
+    fn push_cursor_key(&self, query_builder: &mut QueryBuilder<Postgres>, cursor: &Cursor) {
+        query_builder
+            .push("(CAST(")
+            .push_bind(cursor.key.clone())
+            .push(" AS ")
+            .push(self.sort_type)
+            .push("), ")
+            .push_bind(cursor.id)
+            .push(")");
+    }
+}
+
+/// Pushes the `sort_key` and `cursor_id` columns which `Keyed` reads, so the query is still in
+/// its column list.
+pub fn push_key_columns(query_builder: &mut QueryBuilder<Postgres>, keyset: &Keyset) {
+    query_builder
+        .push(", (")
+        .push(keyset.sort_expression)
+        .push(")::TEXT AS sort_key, ")
+        .push(keyset.id_column)
+        .push(" AS cursor_id");
+}
+
+/// Pushes ` AND ...` conditions for the `after` and `before` cursors, so the query needs a WHERE
+/// clause already.
+pub fn push_cursor_conditions(
+    query_builder: &mut QueryBuilder<Postgres>,
+    keyset: &Keyset,
+    page_request: &PageRequest,
+) {
+    for (cursor, is_after) in [(&page_request.after, true), (&page_request.before, false)] {
+        if let Some(cursor) = cursor {
+            let greater = (keyset.direction == SortDirection::Asc) == is_after;
+
+            query_builder.push(" AND ");
+            keyset.push_key(query_builder);
+            query_builder.push(if greater { " > " } else { " < " });
+            keyset.push_cursor_key(query_builder, cursor);
+        }
+    }
+}
+
+pub fn push_order_and_limit(
+    query_builder: &mut QueryBuilder<Postgres>,
+    keyset: &Keyset,
+    page_request: &PageRequest,
+) {
+    let direction = match (keyset.direction, page_request.backwards()) {
+        (SortDirection::Asc, false) | (SortDirection::Desc, true) => " ASC",
+        (SortDirection::Desc, false) | (SortDirection::Asc, true) => " DESC",
+    };
+
+    query_builder
+        .push(" ORDER BY ")
+        .push(keyset.sort_expression)
+        .push(direction)
+        .push(", ")
+        .push(keyset.id_column)
+        .push(direction);
+
+    // one more row than requested tells if there is another page
+    if let Some(limit) = page_request.limit() {
+        query_builder.push(" LIMIT ").push_bind(limit as i64 + 1);
+    }
+}
+
+pub fn into_page<T>(rows: Vec<Keyed<T>>, page_request: &PageRequest) -> Page<T> {
+    let mut rows: Vec<(Cursor, T)> = rows
+        .into_iter()
+        .map(|row| {
+            (
+                Cursor {
+                    key: row.sort_key,
+                    id: row.cursor_id,
+                },
+                row.item,
+            )
+        })
+        .collect();
+    let has_more = page_request.limit().is_some_and(|limit| rows.len() > limit);
+    if let Some(limit) = page_request.limit() {
+        rows.truncate(limit);
+    }
+
+    if page_request.backwards() {
+        rows.reverse();
+        Page {
+            items: rows,
+            has_previous_page: has_more,
+            has_next_page: page_request.before.is_some(),
+        }
+    } else {
+        Page {
+            items: rows,
+            has_previous_page: page_request.after.is_some(),
+            has_next_page: has_more,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyed(ids: &[i32]) -> Vec<Keyed<i32>> {
+        ids.iter()
+            .map(|&id| Keyed {
+                item: id,
+                sort_key: id.to_string(),
+                cursor_id: id,
+            })
+            .collect()
+    }
+
+    fn ids(page: &Page<i32>) -> Vec<i32> {
+        page.items.iter().map(|(_, id)| *id).collect()
+    }
+
+    #[test]
+    fn test_into_page_forward() {
+        let page_request = PageRequest {
+            first: Some(2),
+            ..Default::default()
+        };
+
+        let page = into_page(keyed(&[1, 2, 3]), &page_request);
+
+        assert_eq!(ids(&page), vec![1, 2]);
+        assert_eq!(
+            page.items[1].0,
+            Cursor {
+                key: String::from("2"),
+                id: 2
+            }
+        );
+        assert!(!page.has_previous_page);
+        assert!(page.has_next_page);
+    }
+
+    #[test]
+    fn test_into_page_backwards() {
+        let page_request = PageRequest {
+            last: Some(2),
+            before: Some(Cursor {
+                key: String::from("7"),
+                id: 7,
+            }),
+            ..Default::default()
+        };
+
+        // rows of a backwards page arrive in reverse order
+        let page = into_page(keyed(&[6, 5, 4]), &page_request);
+
+        assert_eq!(ids(&page), vec![5, 6]);
+        assert!(page.has_previous_page);
+        assert!(page.has_next_page);
+    }
+}
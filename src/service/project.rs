@@ -0,0 +1,220 @@
+use crate::models;
+
+pub(crate) async fn get_customer(
+    customer_id: i32,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Option<models::Customer>> {
+    sqlx::query_as!(
+        models::Customer,
+        r#"SELECT customer_id, name, status as "status: models::ArchiveStatus" FROM customer WHERE customer_id = $1"#,
+        customer_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+pub(crate) async fn get_customers_by_ids(
+    customer_ids: &[i32],
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Vec<models::Customer>> {
+    sqlx::query_as!(
+        models::Customer,
+        r#"SELECT customer_id, name, status as "status: models::ArchiveStatus" FROM customer WHERE customer_id = ANY($1)"#,
+        customer_ids
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub(crate) async fn get_customers(
+    include_archived: bool,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Vec<models::Customer>> {
+    sqlx::query_as!(
+        models::Customer,
+        r#"SELECT customer_id, name, status as "status: models::ArchiveStatus" FROM customer
+        WHERE $1 OR status = 'active' ORDER BY name, customer_id"#,
+        include_archived
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub(crate) async fn create_customer(
+    name: &str,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<models::Customer> {
+    sqlx::query_as!(
+        models::Customer,
+        r#"INSERT INTO customer (name) VALUES ($1) RETURNING customer_id, name, status as "status: models::ArchiveStatus""#,
+        name
+    )
+    .fetch_one(pool)
+    .await
+}
+
+pub(crate) async fn update_customer(
+    customer_id: i32,
+    name: &str,
+    status: models::ArchiveStatus,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Option<models::Customer>> {
+    sqlx::query_as!(
+        models::Customer,
+        r#"UPDATE customer SET name = $2, status = $3 WHERE customer_id = $1
+        RETURNING customer_id, name, status as "status: models::ArchiveStatus""#,
+        customer_id,
+        name,
+        status as models::ArchiveStatus
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+pub(crate) async fn get_project(
+    project_id: i32,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Option<models::Project>> {
+    sqlx::query_as!(
+        models::Project,
+        r#"SELECT project_id, customer_id, name, status as "status: models::ArchiveStatus", budget_hours, budget_cents
+        FROM project WHERE project_id = $1"#,
+        project_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+pub(crate) async fn get_projects_by_ids(
+    project_ids: &[i32],
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Vec<models::Project>> {
+    sqlx::query_as!(
+        models::Project,
+        r#"SELECT project_id, customer_id, name, status as "status: models::ArchiveStatus", budget_hours, budget_cents
+        FROM project WHERE project_id = ANY($1)"#,
+        project_ids
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// The projects of the customer or of all customers, archived ones only if they are asked for.
+pub(crate) async fn get_projects(
+    customer_id: Option<i32>,
+    include_archived: bool,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Vec<models::Project>> {
+    sqlx::query_as!(
+        models::Project,
+        r#"SELECT project_id, customer_id, name, status as "status: models::ArchiveStatus", budget_hours, budget_cents
+        FROM project WHERE ($1::INTEGER IS NULL OR customer_id = $1) AND ($2 OR status = 'active')
+        ORDER BY name, project_id"#,
+        customer_id,
+        include_archived
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub(crate) async fn create_project(
+    customer_id: i32,
+    name: &str,
+    budget_hours: Option<f64>,
+    budget_cents: Option<i64>,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<models::Project> {
+    sqlx::query_as!(
+        models::Project,
+        r#"INSERT INTO project (customer_id, name, budget_hours, budget_cents) VALUES ($1, $2, $3, $4)
+        RETURNING project_id, customer_id, name, status as "status: models::ArchiveStatus", budget_hours, budget_cents"#,
+        customer_id,
+        name,
+        budget_hours,
+        budget_cents
+    )
+    .fetch_one(pool)
+    .await
+}
+
+// replaces every field, the project can also be moved to another customer
+pub(crate) async fn update_project(
+    project: &models::Project,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Option<models::Project>> {
+    sqlx::query_as!(
+        models::Project,
+        r#"UPDATE project SET customer_id = $2, name = $3, status = $4, budget_hours = $5, budget_cents = $6
+        WHERE project_id = $1
+        RETURNING project_id, customer_id, name, status as "status: models::ArchiveStatus", budget_hours, budget_cents"#,
+        project.project_id,
+        project.customer_id,
+        project.name,
+        project.status as models::ArchiveStatus,
+        project.budget_hours,
+        project.budget_cents
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::task;
+
+    #[sqlx::test(fixtures("../../fixtures/truncate.sql", "../../fixtures/task.sql"))]
+    async fn test_customer_projects(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        // given is a customer with a project
+        let customer = create_customer("Cloud City", &pool).await?;
+        let project =
+            create_project(customer.customer_id, "Repairs", Some(40.0), None, &pool).await?;
+        assert_eq!(project.status, models::ArchiveStatus::Active);
+        assert_eq!(project.budget_hours, Some(40.0));
+
+        // when a task is moved into the project
+        task::set_task_project(3, Some(project.project_id), &pool).await?;
+
+        // then it is listed as task of the project
+        let tasks = task::get_tasks_of_project(project.project_id, false, &pool).await?;
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].task_id, 3);
+        assert_eq!(tasks[0].project_id, Some(project.project_id));
+
+        // when the project is archived
+        let archived = update_project(
+            &models::Project {
+                status: models::ArchiveStatus::Archived,
+                ..project.clone()
+            },
+            &pool,
+        )
+        .await?
+        .unwrap();
+        assert_eq!(archived.status, models::ArchiveStatus::Archived);
+
+        // then it is only listed on request
+        assert!(get_projects(Some(customer.customer_id), false, &pool)
+            .await?
+            .is_empty());
+        assert_eq!(
+            get_projects(Some(customer.customer_id), true, &pool)
+                .await?
+                .len(),
+            1
+        );
+
+        // archived customers are hidden the same way
+        update_customer(
+            customer.customer_id,
+            "Cloud City",
+            models::ArchiveStatus::Archived,
+            &pool,
+        )
+        .await?;
+        assert!(get_customers(false, &pool).await?.is_empty());
+        assert_eq!(get_customers(true, &pool).await?.len(), 1);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,656 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Datelike, NaiveDate, Utc, Weekday};
+use chrono_tz::Tz;
+use sqlx::postgres::types::PgInterval;
+
+use crate::{
+    models::{self, WorktimeType},
+    service::{absence, employee, task, worktime},
+    time_utils,
+};
+
+// microseconds in the interval, months are taken as 30 days
+fn interval_minutes(interval: &PgInterval) -> i64 {
+    let days = interval.months as i64 * 30 + interval.days as i64;
+    days * 24 * 60 + interval.microseconds / 60_000_000
+}
+
+// the weekly time spread over monday to friday of the period
+pub(crate) fn target_minutes(
+    weekly_time: Option<&PgInterval>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> i64 {
+    let Some(weekly_time) = weekly_time else {
+        return 0;
+    };
+    let weekdays = from
+        .iter_days()
+        .take_while(|day| *day <= to)
+        .filter(|day| !matches!(day.weekday(), Weekday::Sat | Weekday::Sun))
+        .count() as i64;
+
+    interval_minutes(weekly_time) * weekdays / 5
+}
+
+fn task_minutes(
+    minutes_per_task: BTreeMap<i32, i64>,
+    task_descriptions: &HashMap<i32, Option<String>>,
+) -> Vec<models::TaskMinutes> {
+    minutes_per_task
+        .into_iter()
+        .map(|(task_id, minutes)| models::TaskMinutes {
+            task_id,
+            task_description: task_descriptions.get(&task_id).cloned().flatten(),
+            minutes,
+        })
+        .collect()
+}
+
+// descriptions of the tasks the worktimes were booked on, loaded at once
+async fn get_task_descriptions<'a>(
+    worktimes: impl Iterator<Item = &'a models::Worktime>,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<HashMap<i32, Option<String>>> {
+    let mut task_ids: Vec<i32> = worktimes.map(|w| w.task_id).collect();
+    task_ids.sort_unstable();
+    task_ids.dedup();
+
+    Ok(task::get_tasks_by_ids(&task_ids, pool)
+        .await?
+        .into_iter()
+        .map(|task| (task.task_id, task.task_description))
+        .collect())
+}
+
+// minutes of the worktime on each of its days between `from` and `to` (both inclusive), running
+// timers are counted up to `now`
+fn minutes_in_period(
+    worktime: &models::Worktime,
+    from: NaiveDate,
+    to: NaiveDate,
+    timezone: &Tz,
+    now: DateTime<Utc>,
+) -> Vec<(NaiveDate, i64)> {
+    let end_time = worktime.end_time.unwrap_or(now);
+
+    time_utils::minutes_per_day(worktime.start_time, end_time, timezone)
+        .into_iter()
+        .filter(|(date, _)| (from..=to).contains(date))
+        .collect()
+}
+
+// totals of the month report of the employee, which only contains the part of the worktimes
+// within its period
+fn employee_totals(
+    employee: models::Employee,
+    report: &models::MonthReport,
+    absences: &[models::Absence],
+    task_descriptions: &HashMap<i32, Option<String>>,
+) -> models::EmployeeTotals {
+    let (from, to) = (report.from, report.to);
+    let mut minutes_per_task: BTreeMap<i32, i64> = BTreeMap::new();
+    for task in report.days.iter().flat_map(|day| &day.tasks) {
+        *minutes_per_task.entry(task.task_id).or_default() += task.work_minutes + task.ride_minutes;
+    }
+
+    let target_minutes = target_minutes(employee.weekly_time.as_ref(), from, to);
+    let absence_minutes = absences
+        .iter()
+        .filter(|absence| absence.employee_id == employee.employee_id)
+        .map(|absence| absence::absence_minutes(absence, employee.weekly_time.as_ref(), from, to))
+        .sum();
+
+    models::EmployeeTotals {
+        employee_id: employee.employee_id,
+        firstname: employee.firstname,
+        lastname: employee.lastname,
+        work_minutes: report.work_minutes,
+        ride_minutes: report.ride_minutes,
+        break_minutes: report.break_minutes,
+        absence_minutes,
+        target_minutes,
+        overtime_minutes: report.net() + absence_minutes - target_minutes,
+        tasks: task_minutes(minutes_per_task, task_descriptions),
+    }
+}
+
+/// Totals of the employees between `from` and `to` (both inclusive), the days are taken in the
+/// timezone of each employee. Approved absences count with the target time of their weekdays.
+pub(crate) async fn get_team_report(
+    employee_ids: &[i32],
+    from: NaiveDate,
+    to: NaiveDate,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<models::TeamReport> {
+    let mut employees = employee::get_employees_by_ids(employee_ids, pool).await?;
+    employees.sort_by(|a, b| {
+        (&a.lastname, &a.firstname, a.employee_id).cmp(&(&b.lastname, &b.firstname, b.employee_id))
+    });
+
+    // running timers are counted up to now
+    let now = Utc::now();
+    let mut worktimes_per_employee = Vec::with_capacity(employees.len());
+    for employee in employees {
+        let timezone = time_utils::resolve_timezone(employee.timezone.as_deref());
+        let (start, _) = time_utils::day_bounds(from, &timezone);
+        let (_, end) = time_utils::day_bounds(to, &timezone);
+        let worktimes =
+            worktime::get_timers_overlapping(&employee.employee_id, start, end, pool).await?;
+        worktimes_per_employee.push((employee, timezone, worktimes));
+    }
+
+    let absences = absence::get_absences(
+        employee_ids,
+        from,
+        to,
+        Some(models::AbsenceStatus::Approved),
+        pool,
+    )
+    .await?;
+    let task_descriptions = get_task_descriptions(
+        worktimes_per_employee
+            .iter()
+            .flat_map(|(_, _, worktimes)| worktimes.iter()),
+        pool,
+    )
+    .await?;
+
+    let employees: Vec<models::EmployeeTotals> = worktimes_per_employee
+        .into_iter()
+        .map(|(employee, timezone, worktimes)| {
+            let report = build_month_report(
+                employee.employee_id,
+                &worktimes,
+                from,
+                to,
+                &timezone,
+                now,
+                &task_descriptions,
+            );
+            employee_totals(employee, &report, &absences, &task_descriptions)
+        })
+        .collect();
+
+    let mut minutes_per_task: BTreeMap<i32, i64> = BTreeMap::new();
+    for task in employees.iter().flat_map(|employee| &employee.tasks) {
+        *minutes_per_task.entry(task.task_id).or_default() += task.minutes;
+    }
+
+    Ok(models::TeamReport {
+        from,
+        to,
+        work_minutes: employees.iter().map(|e| e.work_minutes).sum(),
+        ride_minutes: employees.iter().map(|e| e.ride_minutes).sum(),
+        break_minutes: employees.iter().map(|e| e.break_minutes).sum(),
+        absence_minutes: employees.iter().map(|e| e.absence_minutes).sum(),
+        overtime_minutes: employees.iter().map(|e| e.overtime_minutes).sum(),
+        tasks: task_minutes(minutes_per_task, &task_descriptions),
+        employees,
+    })
+}
+
+/// Splits the worktimes into the days between `from` and `to` (both inclusive) in the timezone,
+/// every day of the period is part of the report. Running timers are counted up to `now`.
+pub(crate) fn build_month_report(
+    employee_id: i32,
+    worktimes: &[models::Worktime],
+    from: NaiveDate,
+    to: NaiveDate,
+    timezone: &Tz,
+    now: DateTime<Utc>,
+    task_descriptions: &HashMap<i32, Option<String>>,
+) -> models::MonthReport {
+    let mut days: Vec<models::DayReport> = from
+        .iter_days()
+        .take_while(|date| *date <= to)
+        .map(|date| models::DayReport {
+            date,
+            work_minutes: 0,
+            ride_minutes: 0,
+            break_minutes: 0,
+            tasks: Vec::new(),
+            breaks: Vec::new(),
+            in_progress: false,
+        })
+        .collect();
+    // work minutes, ride minutes and whether a timer is running per task of each day
+    let mut minutes_per_task: Vec<BTreeMap<i32, (i64, i64, bool)>> =
+        vec![BTreeMap::new(); days.len()];
+
+    let mut worktimes: Vec<&models::Worktime> = worktimes.iter().collect();
+    worktimes.sort_by_key(|worktime| (worktime.start_time, worktime.worktime_id));
+
+    // worktimes over midnight are split into the days they lasted
+    for worktime in worktimes {
+        let in_progress = worktime.end_time.is_none();
+        for (date, minutes) in minutes_in_period(worktime, from, to, timezone, now) {
+            let index = (date - from).num_days() as usize;
+            let day = &mut days[index];
+            day.in_progress |= in_progress;
+            match worktime.work_type {
+                WorktimeType::Work | WorktimeType::Ride => {
+                    let task = minutes_per_task[index].entry(worktime.task_id).or_default();
+                    if worktime.work_type == WorktimeType::Work {
+                        day.work_minutes += minutes;
+                        task.0 += minutes;
+                    } else {
+                        day.ride_minutes += minutes;
+                        task.1 += minutes;
+                    }
+                    task.2 |= in_progress;
+                }
+                WorktimeType::Break => {
+                    day.break_minutes += minutes;
+                    day.breaks.push(models::DayBreak {
+                        worktime_id: worktime.worktime_id,
+                        start_time: worktime.start_time,
+                        end_time: worktime.end_time,
+                        minutes,
+                    });
+                }
+            }
+        }
+    }
+
+    for (day, minutes_per_task) in days.iter_mut().zip(minutes_per_task) {
+        day.tasks = minutes_per_task
+            .into_iter()
+            .map(
+                |(task_id, (work_minutes, ride_minutes, in_progress))| models::DayTaskMinutes {
+                    task_id,
+                    task_description: task_descriptions.get(&task_id).cloned().flatten(),
+                    work_minutes,
+                    ride_minutes,
+                    in_progress,
+                },
+            )
+            .collect();
+    }
+
+    models::MonthReport {
+        employee_id,
+        from,
+        to,
+        work_minutes: days.iter().map(|day| day.work_minutes).sum(),
+        ride_minutes: days.iter().map(|day| day.ride_minutes).sum(),
+        break_minutes: days.iter().map(|day| day.break_minutes).sum(),
+        generated_at: now,
+        in_progress: days.iter().any(|day| day.in_progress),
+        days,
+    }
+}
+
+/// Report of the already loaded worktimes of the employee, see `build_month_report`.
+pub(crate) async fn get_month_report_of_worktimes(
+    employee_id: i32,
+    worktimes: &[models::Worktime],
+    from: NaiveDate,
+    to: NaiveDate,
+    timezone: &Tz,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<models::MonthReport> {
+    let task_descriptions = get_task_descriptions(worktimes.iter(), pool).await?;
+
+    Ok(build_month_report(
+        employee_id,
+        worktimes,
+        from,
+        to,
+        timezone,
+        Utc::now(),
+        &task_descriptions,
+    ))
+}
+
+/// Times of the employee per day between `from` and `to` (both inclusive), the days are taken in
+/// the timezone of the employee.
+pub(crate) async fn get_month_report(
+    employee_id: i32,
+    from: NaiveDate,
+    to: NaiveDate,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<models::MonthReport> {
+    let timezone = employee::get_timezone(&employee_id, pool).await?;
+    let (start, _) = time_utils::day_bounds(from, &timezone);
+    let (_, end) = time_utils::day_bounds(to, &timezone);
+    let worktimes = worktime::get_timers_overlapping(&employee_id, start, end, pool).await?;
+
+    get_month_report_of_worktimes(employee_id, &worktimes, from, to, &timezone, pool).await
+}
+
+/// Durations booked on the task per employee and work type between `from` and `to` (both
+/// inclusive), the days are taken in the timezone of each employee like in the other reports.
+pub(crate) async fn get_task_report(
+    task_id: i32,
+    from: NaiveDate,
+    to: NaiveDate,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<models::TaskReport> {
+    let task = task::get_task_by_id(task_id, pool)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?;
+    let company_timezone = time_utils::company_timezone();
+
+    // running timers count up to now, worktimes over the bounds with their part within
+    let entries = sqlx::query_as!(
+        models::TaskReportEntry,
+        r#"SELECT w.employee_id, e.firstname, e.lastname, w.work_type as "work_type: WorktimeType", (EXTRACT(EPOCH FROM SUM(LEAST(COALESCE(w.end_time, NOW()), b.end_bound) - GREATEST(w.start_time, b.start_bound))) / 60)::BIGINT as "minutes!"
+        FROM worktime w
+        JOIN employee e ON e.employee_id = w.employee_id
+        CROSS JOIN LATERAL (
+            SELECT $2::DATE::TIMESTAMP AT TIME ZONE COALESCE(e.timezone, $4) as start_bound,
+            ($3::DATE + 1)::TIMESTAMP AT TIME ZONE COALESCE(e.timezone, $4) as end_bound
+        ) b
+        WHERE w.task_id = $1 AND w.start_time < b.end_bound AND COALESCE(w.end_time, NOW()) > b.start_bound
+        GROUP BY w.employee_id, e.firstname, e.lastname, w.work_type
+        ORDER BY e.lastname, e.firstname, w.employee_id, w.work_type"#,
+        task_id,
+        from,
+        to,
+        company_timezone.name(),
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(models::TaskReport {
+        task_id,
+        task_description: task.task_description,
+        from,
+        to,
+        total_minutes: entries.iter().map(|entry| entry.minutes).sum(),
+        entries,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_minutes() {
+        let weekly_time = PgInterval {
+            months: 0,
+            days: 0,
+            microseconds: 40 * 60 * 60 * 1_000_000,
+        };
+        // monday 2024-01-01 to sunday 2024-01-07
+        let monday = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let sunday = NaiveDate::from_ymd_opt(2024, 1, 7).unwrap();
+
+        assert_eq!(target_minutes(Some(&weekly_time), monday, sunday), 40 * 60);
+        assert_eq!(target_minutes(Some(&weekly_time), monday, monday), 8 * 60);
+        assert_eq!(target_minutes(Some(&weekly_time), sunday, sunday), 0);
+        assert_eq!(target_minutes(None, monday, sunday), 0);
+    }
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/task.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+        "../../fixtures/worktime.sql"
+    ))]
+    async fn test_get_task_report(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        // given is the task 2 in january which was worked on by both employees
+        let from = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+
+        // when the report is created
+        let report = get_task_report(2, from, to, &pool).await?;
+
+        // then the durations are summed up per employee and work type
+        assert_eq!(report.task_description.as_deref(), Some("second task"));
+        let entries: Vec<(i32, WorktimeType, i64)> = report
+            .entries
+            .iter()
+            .map(|entry| (entry.employee_id, entry.work_type, entry.minutes))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                (1, WorktimeType::Work, 3 * 3 * 60),
+                (
+                    1,
+                    WorktimeType::Ride,
+                    120 + 150 + 120 + 150 + 120 + 180 + 150 + 120
+                ),
+                (2, WorktimeType::Break, 8 * 60),
+            ]
+        );
+        assert_eq!(
+            report.total_minutes,
+            entries.iter().map(|entry| entry.2).sum::<i64>()
+        );
+
+        // when bob works in new york on the evening of the last day of january
+        employee::update_timezone(&1, Some(String::from("America/New_York")), &pool).await?;
+        sqlx::query!(
+            "INSERT INTO worktime (worktime_id, employee_id, task_id, start_time, end_time, work_type) VALUES (100, 1, 2, '2024-02-01T02:00:00+00:00', '2024-02-01T03:00:00+00:00', 'work')"
+        )
+        .execute(&pool)
+        .await?;
+
+        // then the hour counts in january of his timezone, although it is february in berlin
+        let report = get_task_report(2, from, to, &pool).await?;
+        assert_eq!(report.entries[0].minutes, 3 * 3 * 60 + 60);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_month_report_with_running_timer() {
+        use chrono::TimeZone;
+
+        let worktime = |worktime_id, work_type, start_hour, end_hour: Option<u32>| {
+            let time = |hour, minute| Utc.with_ymd_and_hms(2024, 1, 2, hour, minute, 0).unwrap();
+            models::Worktime {
+                worktime_id,
+                employee_id: 1,
+                task_id: 1,
+                start_time: time(start_hour, 0),
+                end_time: end_hour.map(|hour| time(hour, 30)),
+                timeduration: None,
+                work_type,
+                needs_review: false,
+                comment: None,
+                billable: true,
+            }
+        };
+        // given is a work timer which is still running and a finished break
+        let worktimes = vec![
+            worktime(1, WorktimeType::Work, 8, None),
+            worktime(2, WorktimeType::Break, 10, Some(10)),
+        ];
+        let date = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        let now = Utc.with_ymd_and_hms(2024, 1, 2, 12, 0, 0).unwrap();
+
+        // when the report is created at noon
+        let report = build_month_report(
+            1,
+            &worktimes,
+            date,
+            date,
+            &chrono_tz::UTC,
+            now,
+            &HashMap::new(),
+        );
+
+        // then the timer is counted up to noon and marked as in progress
+        let day = &report.days[0];
+        assert_eq!(day.work_minutes, 4 * 60);
+        assert!(day.in_progress && day.tasks[0].in_progress && report.in_progress);
+        // and the break is listed and taken off the net time
+        assert_eq!(day.breaks.len(), 1);
+        assert_eq!(day.breaks[0].minutes, 30);
+        assert_eq!(day.net(), 4 * 60 - 30);
+        assert_eq!(report.net(), 4 * 60 - 30);
+        assert_eq!(report.generated_at, now);
+    }
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/task.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+        "../../fixtures/worktime.sql"
+    ))]
+    async fn test_get_month_report(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        // given is the first week of january
+        let from = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2024, 1, 7).unwrap();
+
+        // when the report of bob is created
+        let report = get_month_report(1, from, to, &pool).await?;
+
+        // then every day is part of it, also the ones without worktimes
+        assert_eq!(report.days.len(), 7);
+        let monday = &report.days[0];
+        assert_eq!(
+            (
+                monday.work_minutes,
+                monday.ride_minutes,
+                monday.break_minutes
+            ),
+            (0, 8 * 60, 8 * 60)
+        );
+        assert_eq!(monday.tasks.len(), 1);
+        assert_eq!(monday.breaks.len(), 1);
+        assert_eq!(monday.net(), 0);
+        assert!(report.days[1].tasks.is_empty());
+
+        // and the times of a day are split by task
+        let friday = &report.days[4];
+        assert_eq!(friday.date, NaiveDate::from_ymd_opt(2024, 1, 5).unwrap());
+        assert_eq!(friday.total(), 7 * 60);
+        let tasks: Vec<(i32, i64, i64)> = friday
+            .tasks
+            .iter()
+            .map(|task| (task.task_id, task.work_minutes, task.ride_minutes))
+            .collect();
+        assert_eq!(tasks, vec![(1, 120, 0), (2, 0, 120), (3, 180, 0)]);
+        assert_eq!(
+            friday.tasks[0].task_description.as_deref(),
+            Some("first task")
+        );
+
+        assert_eq!(report.work_minutes, (3 + 4 + 2 + 3) * 60);
+        assert_eq!(report.ride_minutes, (8 + 2) * 60 + 150 + 120);
+        assert_eq!(report.break_minutes, 8 * 60);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/task.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+        "../../fixtures/worktime.sql"
+    ))]
+    async fn test_split_at_midnight(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        // given is a night shift of mia from 22:00 to 06:30 in Berlin over the end of january
+        sqlx::query!(
+            "INSERT INTO worktime (worktime_id, employee_id, task_id, start_time, end_time, work_type) VALUES (1000, 3, 1, '2024-01-31 21:00:00+00', '2024-02-01 05:30:00+00', 'work')"
+        )
+        .execute(&pool)
+        .await?;
+        let january = (
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+        );
+
+        // then the days only get their part of it
+        let report = get_month_report(
+            3,
+            NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 2, 1).unwrap(),
+            &pool,
+        )
+        .await?;
+        let minutes: Vec<i64> = report.days.iter().map(|day| day.work_minutes).collect();
+        assert_eq!(minutes, vec![2 * 60, 6 * 60 + 30]);
+        assert_eq!(report.days[1].tasks[0].work_minutes, 6 * 60 + 30);
+
+        // and so does the month
+        let report = get_month_report(3, january.0, january.1, &pool).await?;
+        assert_eq!(report.work_minutes, 2 * 60);
+        let report = get_team_report(&[3], january.0, january.1, &pool).await?;
+        assert_eq!(report.employees[0].work_minutes, 2 * 60);
+        let report = get_task_report(1, january.0, january.1, &pool).await?;
+        let mia = report
+            .entries
+            .iter()
+            .find(|entry| entry.employee_id == 3)
+            .unwrap();
+        assert_eq!(mia.minutes, 2 * 60);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/task.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+        "../../fixtures/worktime.sql"
+    ))]
+    async fn test_get_team_report(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        // given is the first week of january
+        let from = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2024, 1, 5).unwrap();
+        // and leck was on approved vacation from friday until the next week
+        let vacation = absence::request_absence(
+            &2,
+            models::AbsenceType::Vacation,
+            to,
+            NaiveDate::from_ymd_opt(2024, 1, 9).unwrap(),
+            None,
+            &pool,
+        )
+        .await?;
+        absence::review_absence(vacation.absence_id, models::AbsenceStatus::Approved, &pool)
+            .await?;
+
+        // when the report of both employees is created
+        let report = get_team_report(&[1, 2], from, to, &pool).await?;
+
+        // then the employees are sorted by name and their times are summed up
+        assert_eq!(report.employees.len(), 2);
+        let bob = &report.employees[0];
+        assert_eq!(bob.employee_id, 1);
+        assert_eq!(bob.work_minutes, (3 + 4 + 2 + 3) * 60);
+        assert_eq!(bob.ride_minutes, (8 + 2) * 60 + 150 + 120);
+        assert_eq!(bob.break_minutes, 8 * 60);
+        assert_eq!(bob.target_minutes, 38 * 60);
+        assert_eq!(
+            bob.overtime_minutes,
+            bob.work_minutes + bob.ride_minutes - bob.break_minutes - 38 * 60
+        );
+
+        let leck = &report.employees[1];
+        assert_eq!(leck.employee_id, 2);
+        assert_eq!(leck.work_minutes, 0);
+        assert_eq!(leck.break_minutes, 8 * 60);
+        assert!(leck.tasks.is_empty());
+        // only friday is within the period and counts as worked
+        assert_eq!(bob.absence_minutes, 0);
+        assert_eq!(leck.absence_minutes, 18 * 60 / 5);
+        // and the breaks without any work do not count against the target time
+        assert_eq!(leck.overtime_minutes, 18 * 60 / 5 - 18 * 60);
+        assert_eq!(report.absence_minutes, 18 * 60 / 5);
+
+        assert_eq!(report.break_minutes, 16 * 60);
+        assert_eq!(
+            report.tasks.iter().map(|t| t.minutes).sum::<i64>(),
+            bob.work_minutes + bob.ride_minutes
+        );
+        assert_eq!(
+            report.tasks[0].task_description.as_deref(),
+            Some("first task")
+        );
+
+        Ok(())
+    }
+}
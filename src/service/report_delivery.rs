@@ -0,0 +1,104 @@
+use crate::models;
+
+pub(crate) async fn record_delivery(
+    employee_id: i32,
+    period: &str,
+    recipient: &str,
+    error: Option<&str>,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<models::ReportDelivery> {
+    let status = match error {
+        None => models::DeliveryStatus::Sent,
+        Some(_) => models::DeliveryStatus::Failed,
+    };
+
+    sqlx::query_as!(
+        models::ReportDelivery,
+        r#"INSERT INTO report_delivery (employee_id, period, recipient, status, error) VALUES ($1, $2, $3, $4, $5)
+        RETURNING delivery_id, employee_id, period, recipient, status as "status: models::DeliveryStatus", error, attempted_at"#,
+        employee_id,
+        period,
+        recipient,
+        status as models::DeliveryStatus,
+        error
+    )
+    .fetch_one(pool)
+    .await
+}
+
+// failed attempts do not count, their report is sent again
+pub(crate) async fn is_delivered(
+    employee_id: i32,
+    period: &str,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<bool> {
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM report_delivery WHERE employee_id = $1 AND period = $2 AND status = 'sent') as "delivered!""#,
+        employee_id,
+        period
+    )
+    .fetch_one(pool)
+    .await
+}
+
+pub(crate) async fn count_failed(
+    employee_id: i32,
+    period: &str,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<i64> {
+    sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM report_delivery WHERE employee_id = $1 AND period = $2 AND status = 'failed'"#,
+        employee_id,
+        period
+    )
+    .fetch_one(pool)
+    .await
+}
+
+pub(crate) async fn get_deliveries(
+    period: &str,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Vec<models::ReportDelivery>> {
+    sqlx::query_as!(
+        models::ReportDelivery,
+        r#"SELECT delivery_id, employee_id, period, recipient, status as "status: models::DeliveryStatus", error, attempted_at
+        FROM report_delivery WHERE period = $1 ORDER BY employee_id, attempted_at"#,
+        period
+    )
+    .fetch_all(pool)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql"
+    ))]
+    async fn test_record_delivery(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        // when the first attempt fails
+        let failed = record_delivery(1, "2024-01", "test@test.de", Some("timeout"), &pool).await?;
+        assert_eq!(failed.status, models::DeliveryStatus::Failed);
+
+        // then the report is not delivered yet
+        assert!(!is_delivered(1, "2024-01", &pool).await?);
+        assert_eq!(count_failed(1, "2024-01", &pool).await?, 1);
+        assert_eq!(count_failed(1, "2024-02", &pool).await?, 0);
+
+        // when the second one succeeds
+        record_delivery(1, "2024-01", "test@test.de", None, &pool).await?;
+
+        // then it is, both attempts are listed
+        assert!(is_delivered(1, "2024-01", &pool).await?);
+        assert!(!is_delivered(1, "2024-02", &pool).await?);
+        let deliveries = get_deliveries("2024-01", &pool).await?;
+        assert_eq!(deliveries.len(), 2);
+        assert_eq!(deliveries[0].error.as_deref(), Some("timeout"));
+        assert_eq!(deliveries[1].status, models::DeliveryStatus::Sent);
+
+        Ok(())
+    }
+}
@@ -1,4 +1,9 @@
-use crate::models;
+use sqlx::query_builder;
+
+use crate::{
+    models,
+    service::pagination::{self, Keyed, Keyset, Page, PageRequest},
+};
 
 pub(crate) async fn get_task_by_id(
     task_id: i32,
@@ -6,27 +11,128 @@ pub(crate) async fn get_task_by_id(
 ) -> sqlx::Result<Option<models::Task>> {
     sqlx::query_as!(
         models::Task,
-        "SELECT task_id, task_description FROM task WHERE task_id = $1",
+        r#"SELECT task_id, task_description, project_id, status as "status: models::ArchiveStatus", estimated_hours, budget_cents FROM task WHERE task_id = $1"#,
         task_id
     )
     .fetch_optional(pool)
     .await
 }
 
-pub(crate) async fn get_tasks(pool: &sqlx::PgPool) -> sqlx::Result<Vec<models::Task>> {
-    sqlx::query_as!(models::Task, "SELECT task_id, task_description FROM task")
+// archived tasks are included, old worktimes may still reference them
+pub(crate) async fn get_tasks_by_ids(
+    task_ids: &[i32],
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Vec<models::Task>> {
+    sqlx::query_as!(
+        models::Task,
+        r#"SELECT task_id, task_description, project_id, status as "status: models::ArchiveStatus", estimated_hours, budget_cents FROM task WHERE task_id = ANY($1)"#,
+        task_ids
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub(crate) async fn get_tasks(
+    include_archived: bool,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Vec<models::Task>> {
+    sqlx::query_as!(
+        models::Task,
+        r#"SELECT task_id, task_description, project_id, status as "status: models::ArchiveStatus", estimated_hours, budget_cents FROM task
+        WHERE $1 OR status = 'active' ORDER BY task_id"#,
+        include_archived
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub(crate) async fn get_tasks_of_project(
+    project_id: i32,
+    include_archived: bool,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Vec<models::Task>> {
+    sqlx::query_as!(
+        models::Task,
+        r#"SELECT task_id, task_description, project_id, status as "status: models::ArchiveStatus", estimated_hours, budget_cents FROM task
+        WHERE project_id = $1 AND ($2 OR status = 'active') ORDER BY task_id"#,
+        project_id,
+        include_archived
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub(crate) async fn get_tasks_page(
+    filter: &models::TaskFilter,
+    sort_field: models::TaskSortField,
+    direction: models::SortDirection,
+    page_request: &PageRequest,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Page<models::Task>> {
+    let keyset = match sort_field {
+        models::TaskSortField::Id => Keyset {
+            id_column: "task_id",
+            sort_expression: "task_id",
+            sort_type: "INTEGER",
+            direction,
+        },
+        models::TaskSortField::Description => Keyset {
+            id_column: "task_id",
+            sort_expression: "COALESCE(task_description, '')",
+            sort_type: "TEXT",
+            direction,
+        },
+    };
+
+    let mut query_builder = query_builder::QueryBuilder::<sqlx::Postgres>::new(
+        "SELECT task_id, task_description, project_id, status, estimated_hours, budget_cents",
+    );
+    pagination::push_key_columns(&mut query_builder, &keyset);
+    query_builder.push(" FROM task WHERE TRUE");
+
+    if let Some(description) = &filter.description_contains {
+        // wildcards typed by the user are searched literally
+        let escaped = description
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        query_builder
+            .push(" AND task_description ILIKE ")
+            .push_bind(format!("%{}%", escaped));
+    }
+
+    if let Some(project_id) = filter.project_id {
+        query_builder
+            .push(" AND project_id = ")
+            .push_bind(project_id);
+    }
+
+    if !filter.include_archived {
+        query_builder.push(" AND status = 'active'");
+    }
+
+    pagination::push_cursor_conditions(&mut query_builder, &keyset, page_request);
+    pagination::push_order_and_limit(&mut query_builder, &keyset, page_request);
+
+    let rows = query_builder
+        .build_query_as::<Keyed<models::Task>>()
         .fetch_all(pool)
-        .await
+        .await?;
+
+    Ok(pagination::into_page(rows, page_request))
 }
 
 pub(crate) async fn create_task(
     task_description: &str,
+    project_id: Option<i32>,
     pool: &sqlx::PgPool,
 ) -> sqlx::Result<models::Task> {
     sqlx::query_as!(
         models::Task,
-        "INSERT INTO task (task_description) VALUES($1) RETURNING task_id, task_description",
-        task_description
+        r#"INSERT INTO task (task_description, project_id) VALUES($1, $2)
+        RETURNING task_id, task_description, project_id, status as "status: models::ArchiveStatus", estimated_hours, budget_cents"#,
+        task_description,
+        project_id
     )
     .fetch_one(pool)
     .await
@@ -39,7 +145,8 @@ pub(crate) async fn update_task(
 ) -> sqlx::Result<Option<models::Task>> {
     sqlx::query_as!(
         models::Task,
-        "UPDATE task SET task_description = $2 WHERE task_id = $1 RETURNING task_id, task_description",
+        r#"UPDATE task SET task_description = $2 WHERE task_id = $1
+        RETURNING task_id, task_description, project_id, status as "status: models::ArchiveStatus", estimated_hours, budget_cents"#,
         task_id,
         task_description
     )
@@ -47,13 +154,58 @@ pub(crate) async fn update_task(
     .await
 }
 
+// None takes the task out of its project
+pub(crate) async fn set_task_project(
+    task_id: i32,
+    project_id: Option<i32>,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Option<models::Task>> {
+    sqlx::query_as!(
+        models::Task,
+        r#"UPDATE task SET project_id = $2 WHERE task_id = $1
+        RETURNING task_id, task_description, project_id, status as "status: models::ArchiveStatus", estimated_hours, budget_cents"#,
+        task_id,
+        project_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+// archived tasks keep their worktimes but no new timers can be started on them
+pub(crate) async fn set_task_status(
+    task_id: i32,
+    status: models::ArchiveStatus,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Option<models::Task>> {
+    sqlx::query_as!(
+        models::Task,
+        r#"UPDATE task SET status = $2 WHERE task_id = $1
+        RETURNING task_id, task_description, project_id, status as "status: models::ArchiveStatus", estimated_hours, budget_cents"#,
+        task_id,
+        status as models::ArchiveStatus
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+pub(crate) async fn has_worktimes(task_id: i32, pool: &sqlx::PgPool) -> sqlx::Result<bool> {
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM worktime WHERE task_id = $1) as "exists!""#,
+        task_id
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Deletes a task without worktimes, None if it does not exist or time was booked on it.
 pub(crate) async fn delete_task(
     task_id: i32,
     pool: &sqlx::PgPool,
 ) -> sqlx::Result<Option<models::Task>> {
     sqlx::query_as!(
         models::Task,
-        "DELETE FROM task WHERE task_id = $1 RETURNING task_id, task_description",
+        r#"DELETE FROM task WHERE task_id = $1 AND NOT EXISTS (SELECT 1 FROM worktime WHERE task_id = $1)
+        RETURNING task_id, task_description, project_id, status as "status: models::ArchiveStatus", estimated_hours, budget_cents"#,
         task_id
     )
     .fetch_optional(pool)
@@ -86,9 +238,20 @@ mod tests {
         Ok(())
     }
 
+    #[sqlx::test(fixtures("../../fixtures/truncate.sql", "../../fixtures/task.sql",))]
+    async fn test_get_tasks_by_ids(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        let mut tasks = get_tasks_by_ids(&[2, 1, 100], &pool).await?;
+        tasks.sort_by_key(|task| task.task_id);
+
+        let ids: Vec<i32> = tasks.iter().map(|task| task.task_id).collect();
+        assert_eq!(ids, vec![1, 2]);
+
+        Ok(())
+    }
+
     #[sqlx::test(fixtures("../../fixtures/truncate.sql", "../../fixtures/task.sql",))]
     async fn test_get_tasks(pool: sqlx::PgPool) -> sqlx::Result<()> {
-        let task = &get_tasks(&pool).await?;
+        let task = &get_tasks(false, &pool).await?;
 
         let task = &task[0];
 
@@ -99,9 +262,48 @@ mod tests {
         Ok(())
     }
 
+    #[sqlx::test(fixtures("../../fixtures/truncate.sql", "../../fixtures/task.sql",))]
+    async fn test_get_tasks_page(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        let filter = models::TaskFilter {
+            description_contains: Some(String::from("imperial")),
+            ..Default::default()
+        };
+        let page = get_tasks_page(
+            &filter,
+            models::TaskSortField::Description,
+            models::SortDirection::Asc,
+            &PageRequest::default(),
+            &pool,
+        )
+        .await?;
+
+        let ids: Vec<i32> = page.items.iter().map(|(_, task)| task.task_id).collect();
+        assert_eq!(ids, vec![21, 11]);
+
+        let page_request = PageRequest {
+            last: Some(1),
+            ..Default::default()
+        };
+        let page = get_tasks_page(
+            &filter,
+            models::TaskSortField::Description,
+            models::SortDirection::Asc,
+            &page_request,
+            &pool,
+        )
+        .await?;
+
+        let ids: Vec<i32> = page.items.iter().map(|(_, task)| task.task_id).collect();
+        assert_eq!(ids, vec![11]);
+        assert!(page.has_previous_page);
+        assert!(!page.has_next_page);
+
+        Ok(())
+    }
+
     #[sqlx::test(fixtures("../../fixtures/truncate.sql",))]
     async fn test_create_task(pool: sqlx::PgPool) -> sqlx::Result<()> {
-        let task = &create_task("test", &pool).await?;
+        let task = &create_task("test", None, &pool).await?;
 
         assert_eq!(task.task_id, 1);
         assert_ne!(task.task_description, None);
@@ -137,4 +339,50 @@ mod tests {
 
         Ok(())
     }
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/task.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+        "../../fixtures/worktime.sql"
+    ))]
+    async fn test_can_not_delete_task_with_worktimes(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        assert!(has_worktimes(1, &pool).await?);
+        assert!(delete_task(1, &pool).await?.is_none());
+        assert!(get_task_by_id(1, &pool).await?.is_some());
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("../../fixtures/truncate.sql", "../../fixtures/task.sql",))]
+    async fn test_archive_task(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        let task = set_task_status(1, models::ArchiveStatus::Archived, &pool)
+            .await?
+            .unwrap();
+        assert_eq!(task.status, models::ArchiveStatus::Archived);
+
+        // archived tasks are only listed on request
+        assert!(!get_tasks(false, &pool)
+            .await?
+            .iter()
+            .any(|t| t.task_id == 1));
+        assert!(get_tasks(true, &pool).await?.iter().any(|t| t.task_id == 1));
+
+        let filter = models::TaskFilter {
+            description_contains: Some(String::from("first")),
+            ..Default::default()
+        };
+        let page = get_tasks_page(
+            &filter,
+            models::TaskSortField::Id,
+            models::SortDirection::Asc,
+            &PageRequest::default(),
+            &pool,
+        )
+        .await?;
+        assert!(page.items.is_empty());
+
+        Ok(())
+    }
 }
@@ -0,0 +1,193 @@
+use crate::models;
+
+// UNASSIGNED_TASKS_BOOKABLE=false restricts timers to assigned tasks, by default tasks without any
+// assignment are open to everyone
+pub(crate) fn unassigned_tasks_bookable() -> bool {
+    dotenvy::var("UNASSIGNED_TASKS_BOOKABLE").map_or(true, |bookable| bookable != "false")
+}
+
+pub(crate) async fn get_assignments(
+    task_id: i32,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Vec<models::TaskAssignment>> {
+    sqlx::query_as!(
+        models::TaskAssignment,
+        "SELECT assignment_id, task_id, employee_id, team_id FROM task_assignment WHERE task_id = $1 ORDER BY assignment_id",
+        task_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub(crate) async fn get_assignments_of_tasks(
+    task_ids: &[i32],
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Vec<models::TaskAssignment>> {
+    sqlx::query_as!(
+        models::TaskAssignment,
+        "SELECT assignment_id, task_id, employee_id, team_id FROM task_assignment WHERE task_id = ANY($1) ORDER BY assignment_id",
+        task_ids
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Whether the employee may book on the task, the same rules as for starting a timer.
+pub(crate) async fn may_book_task(
+    employee_id: i32,
+    task_id: i32,
+    unassigned_bookable: bool,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<bool> {
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS (
+            SELECT 1 FROM task t WHERE task_id = $2 AND status = 'active'
+            AND (
+                ($3 AND NOT EXISTS (SELECT 1 FROM task_assignment a WHERE a.task_id = t.task_id))
+                OR EXISTS (
+                    SELECT 1 FROM task_assignment a LEFT JOIN employee e ON e.employee_id = $1
+                    WHERE a.task_id = t.task_id AND (a.employee_id = $1 OR a.team_id = e.team_id)
+                )
+            )
+        ) as "bookable!""#,
+        employee_id,
+        task_id,
+        unassigned_bookable
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Assigns the task to the employee or the team, exactly one of them has to be given. Assigning
+/// it twice keeps the first assignment.
+pub(crate) async fn assign_task(
+    task_id: i32,
+    employee_id: Option<i32>,
+    team_id: Option<i32>,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Vec<models::TaskAssignment>> {
+    sqlx::query!(
+        "INSERT INTO task_assignment (task_id, employee_id, team_id) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING",
+        task_id,
+        employee_id,
+        team_id
+    )
+    .execute(pool)
+    .await?;
+
+    get_assignments(task_id, pool).await
+}
+
+pub(crate) async fn unassign_task(
+    assignment_id: i32,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Option<models::TaskAssignment>> {
+    sqlx::query_as!(
+        models::TaskAssignment,
+        "DELETE FROM task_assignment WHERE assignment_id = $1 RETURNING assignment_id, task_id, employee_id, team_id",
+        assignment_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// The active tasks the employee may book: the ones assigned to the employee or its team and,
+/// unless `assigned_only` is set, the ones without any assignment.
+pub(crate) async fn get_tasks_of_employee(
+    employee_id: i32,
+    assigned_only: bool,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Vec<models::Task>> {
+    sqlx::query_as!(
+        models::Task,
+        r#"SELECT task_id, task_description, project_id, status as "status: models::ArchiveStatus", estimated_hours, budget_cents FROM task t
+        WHERE t.status = 'active'
+        AND (
+            EXISTS (
+                SELECT 1 FROM task_assignment a LEFT JOIN employee e ON e.employee_id = $1
+                WHERE a.task_id = t.task_id AND (a.employee_id = $1 OR a.team_id = e.team_id)
+            )
+            OR (NOT $2 AND NOT EXISTS (SELECT 1 FROM task_assignment a WHERE a.task_id = t.task_id))
+        )
+        ORDER BY task_id"#,
+        employee_id,
+        assigned_only
+    )
+    .fetch_all(pool)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::{team, worktime};
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/task.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql"
+    ))]
+    async fn test_task_assignment(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        // given is task 1 assigned to bob and task 2 to the team of leck
+        let crew = team::create_team("Crew", None, &pool).await?;
+        team::set_employee_team(&2, Some(crew.team_id), &pool).await?;
+        assign_task(1, Some(1), None, &pool).await?;
+        let assignments = assign_task(2, None, Some(crew.team_id), &pool).await?;
+        assert_eq!(assignments.len(), 1);
+        // assigning twice changes nothing
+        assert_eq!(assign_task(1, Some(1), None, &pool).await?.len(), 1);
+
+        // then only they see and book their task
+        let ids = |tasks: Vec<models::Task>| tasks.iter().map(|t| t.task_id).collect::<Vec<_>>();
+        assert_eq!(ids(get_tasks_of_employee(1, true, &pool).await?), vec![1]);
+        assert_eq!(ids(get_tasks_of_employee(2, true, &pool).await?), vec![2]);
+        assert!(get_tasks_of_employee(3, true, &pool).await?.is_empty());
+        // unassigned tasks are open to everyone
+        let open = ids(get_tasks_of_employee(3, false, &pool).await?);
+        assert!(open.contains(&3) && !open.contains(&1) && !open.contains(&2));
+
+        assert!(
+            worktime::start_timer(&1, 1, models::WorktimeType::Work, true, &pool)
+                .await?
+                .is_some()
+        );
+        assert!(
+            worktime::start_timer(&3, 1, models::WorktimeType::Work, true, &pool)
+                .await?
+                .is_none()
+        );
+        assert!(
+            worktime::start_timer(&2, 2, models::WorktimeType::Work, true, &pool)
+                .await?
+                .is_some()
+        );
+        assert!(
+            worktime::start_timer(&3, 3, models::WorktimeType::Work, true, &pool)
+                .await?
+                .is_some()
+        );
+
+        assert!(may_book_task(2, 2, true, &pool).await?);
+        assert!(!may_book_task(3, 2, true, &pool).await?);
+        // unassigned tasks can be closed for everyone
+        assert!(may_book_task(3, 3, true, &pool).await?);
+        assert!(!may_book_task(3, 3, false, &pool).await?);
+        assert!(
+            worktime::start_timer(&3, 3, models::WorktimeType::Work, false, &pool)
+                .await?
+                .is_none()
+        );
+        assert_eq!(get_assignments_of_tasks(&[1, 2, 3], &pool).await?.len(), 2);
+
+        // when the assignment is removed, the task is open again
+        unassign_task(assignments[0].assignment_id, &pool).await?;
+        assert!(
+            worktime::start_timer(&3, 2, models::WorktimeType::Work, true, &pool)
+                .await?
+                .is_some()
+        );
+
+        Ok(())
+    }
+}
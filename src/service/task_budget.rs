@@ -0,0 +1,205 @@
+use crate::models;
+
+/// Changes the estimate and the budget of the task, the thresholds are only reported again if the
+/// estimate changed.
+pub(crate) async fn set_task_estimate(
+    task_id: i32,
+    estimated_hours: Option<f64>,
+    budget_cents: Option<i64>,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Option<models::Task>> {
+    let mut transaction = pool.begin().await?;
+
+    let previous_hours = sqlx::query_scalar!(
+        "SELECT estimated_hours FROM task WHERE task_id = $1 FOR UPDATE",
+        task_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await?
+    .flatten();
+    let task = sqlx::query_as!(
+        models::Task,
+        r#"UPDATE task SET estimated_hours = $2, budget_cents = $3 WHERE task_id = $1
+        RETURNING task_id, task_description, project_id, status as "status: models::ArchiveStatus", estimated_hours, budget_cents"#,
+        task_id,
+        estimated_hours,
+        budget_cents
+    )
+    .fetch_optional(&mut *transaction)
+    .await?;
+    if previous_hours != estimated_hours {
+        sqlx::query!("DELETE FROM task_budget_alert WHERE task_id = $1", task_id)
+            .execute(&mut *transaction)
+            .await?;
+    }
+
+    transaction.commit().await?;
+    Ok(task)
+}
+
+fn percent_used(booked_minutes: i64, estimated_hours: f64) -> Option<f64> {
+    (estimated_hours > 0.0).then(|| booked_minutes as f64 / (estimated_hours * 60.0) * 100.0)
+}
+
+fn percent_of_budget(booked_cents: i64, budget_cents: Option<i64>) -> Option<f64> {
+    budget_cents
+        .filter(|&budget_cents| budget_cents > 0)
+        .map(|budget_cents| booked_cents as f64 / budget_cents as f64 * 100.0)
+}
+
+/// Booked work and ride time of the tasks with an estimate and the amount of its billable part
+/// with the applying billing rates, only the given task or the tasks of the given project if they
+/// are set. Running timers are not counted yet.
+pub(crate) async fn get_task_budgets(
+    task_id: Option<i32>,
+    project_id: Option<i32>,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Vec<models::TaskBudget>> {
+    let rows = sqlx::query!(
+        r#"SELECT t.task_id, t.task_description, t.estimated_hours as "estimated_hours!", t.budget_cents,
+        COALESCE((EXTRACT(EPOCH FROM SUM(w.timeduration)) / 60)::BIGINT, 0) as "booked_minutes!",
+        COALESCE(ROUND(SUM(EXTRACT(EPOCH FROM w.timeduration) / 3600 * r.rate_cents) FILTER (WHERE w.billable))::BIGINT, 0) as "booked_cents!"
+        FROM task t LEFT JOIN worktime w ON w.task_id = t.task_id AND w.work_type <> 'break'
+        LEFT JOIN LATERAL (
+            SELECT rate_cents FROM billing_rate r
+            WHERE (r.employee_id IS NULL OR r.employee_id = w.employee_id)
+            AND (r.task_id IS NULL OR r.task_id = w.task_id)
+            AND (r.work_type IS NULL OR r.work_type = w.work_type)
+            ORDER BY r.task_id IS NOT NULL DESC, r.employee_id IS NOT NULL DESC, r.work_type IS NOT NULL DESC
+            LIMIT 1
+        ) r ON TRUE
+        WHERE t.estimated_hours IS NOT NULL
+        AND ($1::INTEGER IS NULL OR t.task_id = $1) AND ($2::INTEGER IS NULL OR t.project_id = $2)
+        GROUP BY t.task_id ORDER BY t.task_id"#,
+        task_id,
+        project_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| models::TaskBudget {
+            task_id: row.task_id,
+            task_description: row.task_description,
+            estimated_hours: row.estimated_hours,
+            budget_cents: row.budget_cents,
+            booked_minutes: row.booked_minutes,
+            percent_used: percent_used(row.booked_minutes, row.estimated_hours),
+            booked_cents: row.booked_cents,
+            percent_of_budget: percent_of_budget(row.booked_cents, row.budget_cents),
+        })
+        .collect())
+}
+
+/// Records every threshold (in percent of the estimate) the booked time of the task passed and
+/// returns the ones which were not passed before.
+pub(crate) async fn record_crossed_thresholds(
+    task_id: i32,
+    thresholds: &[i32],
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Vec<models::TaskBudgetAlert>> {
+    let Some(budget) = get_task_budgets(Some(task_id), None, pool).await?.pop() else {
+        return Ok(Vec::new());
+    };
+    let Some(percent_used) = budget.percent_used else {
+        return Ok(Vec::new());
+    };
+
+    let mut alerts = Vec::new();
+    for &threshold in thresholds {
+        if percent_used < threshold as f64 {
+            continue;
+        }
+        let crossed_at = sqlx::query_scalar!(
+            "INSERT INTO task_budget_alert (task_id, threshold) VALUES ($1, $2) ON CONFLICT DO NOTHING RETURNING crossed_at",
+            task_id,
+            threshold
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        if let Some(crossed_at) = crossed_at {
+            alerts.push(models::TaskBudgetAlert {
+                task_id,
+                task_description: budget.task_description.clone(),
+                threshold,
+                estimated_hours: budget.estimated_hours,
+                booked_minutes: budget.booked_minutes,
+                crossed_at,
+            });
+        }
+    }
+
+    Ok(alerts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percent_used() {
+        assert_eq!(percent_used(480, 10.0), Some(80.0));
+        assert_eq!(percent_used(60, 0.0), None);
+        assert_eq!(percent_of_budget(4500, Some(6000)), Some(75.0));
+        assert_eq!(percent_of_budget(4500, Some(0)), None);
+        assert_eq!(percent_of_budget(4500, None), None);
+    }
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/task.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+        "../../fixtures/worktime.sql"
+    ))]
+    async fn test_record_crossed_thresholds(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        // given is a task without an estimate
+        assert!(get_task_budgets(Some(4), None, &pool).await?.is_empty());
+
+        // when the booked time is 90% of the estimate
+        set_task_estimate(4, Some(1.0), Some(50_000), &pool).await?;
+        let booked_minutes = get_task_budgets(Some(4), None, &pool).await?[0].booked_minutes;
+        assert!(booked_minutes > 0);
+        set_task_estimate(4, Some(booked_minutes as f64 / 54.0), None, &pool).await?;
+
+        // then only the first threshold is passed, and only once
+        let alerts = record_crossed_thresholds(4, &[80, 100], &pool).await?;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].threshold, 80);
+        assert!(record_crossed_thresholds(4, &[80, 100], &pool)
+            .await?
+            .is_empty());
+
+        // when the estimate is lowered, both are reported against the new estimate
+        set_task_estimate(4, Some(booked_minutes as f64 / 120.0), None, &pool).await?;
+        let alerts = record_crossed_thresholds(4, &[80, 100], &pool).await?;
+        assert_eq!(
+            alerts.iter().map(|a| a.threshold).collect::<Vec<_>>(),
+            vec![80, 100]
+        );
+        let percent_used = get_task_budgets(None, None, &pool).await?[0]
+            .percent_used
+            .unwrap();
+        assert!((percent_used - 200.0).abs() < 1e-9);
+
+        // when only the budget changes, then nothing is reported again
+        let estimated_hours = get_task_budgets(Some(4), None, &pool).await?[0].estimated_hours;
+        set_task_estimate(4, Some(estimated_hours), Some(10_000), &pool).await?;
+        assert!(record_crossed_thresholds(4, &[80, 100], &pool)
+            .await?
+            .is_empty());
+
+        // and the booked amount is compared with the budget
+        crate::service::billing::set_rate(None, None, None, 6000, &pool).await?;
+        let budget = get_task_budgets(Some(4), None, &pool).await?.remove(0);
+        assert_eq!(budget.booked_cents, booked_minutes * 100);
+        assert_eq!(
+            budget.percent_of_budget,
+            Some(booked_minutes as f64 * 100.0 / 10_000.0 * 100.0)
+        );
+
+        Ok(())
+    }
+}
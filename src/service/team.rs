@@ -0,0 +1,131 @@
+use crate::{
+    models::{self, EmployeeRole},
+    service::employee,
+};
+
+pub(crate) async fn get_teams(pool: &sqlx::PgPool) -> sqlx::Result<Vec<models::Team>> {
+    sqlx::query_as!(
+        models::Team,
+        "SELECT team_id, name, manager_id FROM team ORDER BY name"
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub(crate) async fn get_team(team_id: &i32, pool: &sqlx::PgPool) -> sqlx::Result<models::Team> {
+    sqlx::query_as!(
+        models::Team,
+        "SELECT team_id, name, manager_id FROM team WHERE team_id = $1",
+        team_id
+    )
+    .fetch_one(pool)
+    .await
+}
+
+pub(crate) async fn create_team(
+    name: &str,
+    manager_id: Option<i32>,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<models::Team> {
+    sqlx::query_as!(
+        models::Team,
+        "INSERT INTO team (name, manager_id) VALUES ($1, $2) RETURNING team_id, name, manager_id",
+        name,
+        manager_id
+    )
+    .fetch_one(pool)
+    .await
+}
+
+// None removes the employee from its team
+pub(crate) async fn set_employee_team(
+    employee_id: &i32,
+    team_id: Option<i32>,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<()> {
+    sqlx::query!(
+        "UPDATE employee SET team_id = $2 WHERE employee_id = $1",
+        employee_id,
+        team_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// the members of the team or every employee of the company without a team
+pub(crate) async fn get_member_ids(
+    team_id: Option<i32>,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Vec<i32>> {
+    sqlx::query_scalar!(
+        "SELECT employee_id FROM employee WHERE $1::INTEGER IS NULL OR team_id = $1 ORDER BY employee_id",
+        team_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+// the members of every team the employee manages
+pub(crate) async fn get_managed_ids(
+    manager_id: &i32,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Vec<i32>> {
+    sqlx::query_scalar!(
+        "SELECT employee.employee_id FROM employee JOIN team USING (team_id) WHERE team.manager_id = $1 ORDER BY employee.employee_id",
+        manager_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Admins see the reports of every team and of the whole company (no team), managers only the
+/// ones of the teams they lead.
+pub(crate) async fn may_see_report(
+    employee_id: &i32,
+    team: Option<&models::Team>,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<bool> {
+    Ok(match employee::get_role(employee_id, pool).await? {
+        EmployeeRole::Admin => true,
+        EmployeeRole::Manager => team.is_some_and(|team| team.manager_id == Some(*employee_id)),
+        EmployeeRole::Employee => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql"
+    ))]
+    async fn test_team_members(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        // given is a team of the manager with one employee
+        let team = create_team("Montage", Some(3), &pool).await?;
+        set_employee_team(&1, Some(team.team_id), &pool).await?;
+
+        // then only the employee is a member while the company has everyone
+        assert_eq!(get_member_ids(Some(team.team_id), &pool).await?, vec![1]);
+        assert_eq!(get_member_ids(None, &pool).await?, vec![1, 2, 3]);
+        assert_eq!(get_teams(&pool).await?.len(), 1);
+        assert_eq!(get_team(&team.team_id, &pool).await?.manager_id, Some(3));
+
+        // only the manager of the team sees its report, but not the one of the company
+        assert!(may_see_report(&3, Some(&team), &pool).await?);
+        assert!(!may_see_report(&3, None, &pool).await?);
+        assert!(!may_see_report(&1, Some(&team), &pool).await?);
+        let other_team = create_team("Vertrieb", None, &pool).await?;
+        assert!(!may_see_report(&3, Some(&other_team), &pool).await?);
+        assert_eq!(get_managed_ids(&3, &pool).await?, vec![1]);
+        assert!(get_managed_ids(&1, &pool).await?.is_empty());
+
+        set_employee_team(&1, None, &pool).await?;
+        assert!(get_member_ids(Some(team.team_id), &pool).await?.is_empty());
+
+        Ok(())
+    }
+}
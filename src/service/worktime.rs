@@ -1,6 +1,25 @@
 use sqlx::query_builder;
 
-use crate::models;
+use crate::{
+    models,
+    service::{
+        employee, notification,
+        pagination::{self, Keyed, Keyset, Page, PageRequest},
+    },
+    time_utils,
+};
+
+/// Decides which end time a forgotten timer gets when it is stopped automatically.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StopPolicy {
+    /// close the timer at start time plus the allowed maximum
+    AfterLimit,
+    /// close the timer at its start time, leaving a zero duration to be corrected
+    AtStart,
+    /// close the timer at the given time of the day it was started in the timezone of the
+    /// employee, but never before its start time
+    AtTimeOfDay(chrono::NaiveTime),
+}
 
 pub async fn get_timers(
     employee_id: &i32,
@@ -8,7 +27,7 @@ pub async fn get_timers(
 ) -> sqlx::Result<Vec<models::Worktime>> {
     sqlx::query_as!(
         models::Worktime,
-        r#"SELECT worktime_id, employee_id, task_id, start_time, end_time, timeduration, work_type as "work_type: models::WorktimeType" FROM worktime WHERE employee_id = $1"#,
+        r#"SELECT worktime_id, employee_id, task_id, start_time, end_time, timeduration, work_type as "work_type: models::WorktimeType", needs_review, comment, billable FROM worktime WHERE employee_id = $1"#,
         employee_id
     )
     .fetch_all(pool)
@@ -23,7 +42,28 @@ pub async fn get_timers_in_boundary(
 ) -> sqlx::Result<Vec<models::Worktime>> {
     sqlx::query_as!(
         models::Worktime,
-        r#"SELECT worktime_id, employee_id, task_id, start_time, end_time, timeduration, work_type as "work_type: models::WorktimeType" FROM worktime WHERE employee_id = $1 AND start_time >= $2 AND start_time < $3"#,
+        r#"SELECT worktime_id, employee_id, task_id, start_time, end_time, timeduration, work_type as "work_type: models::WorktimeType", needs_review, comment, billable FROM worktime WHERE employee_id = $1 AND start_time >= $2 AND start_time < $3"#,
+        employee_id,
+        lower_bound,
+        upper_bound,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Worktimes which overlap the bounds, unlike `get_timers_in_boundary` this includes the ones which
+/// started before the lower bound and ended or are still running after it.
+pub async fn get_timers_overlapping(
+    employee_id: &i32,
+    lower_bound: chrono::DateTime<chrono::FixedOffset>,
+    upper_bound: chrono::DateTime<chrono::FixedOffset>,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Vec<models::Worktime>> {
+    sqlx::query_as!(
+        models::Worktime,
+        r#"SELECT worktime_id, employee_id, task_id, start_time, end_time, timeduration, work_type as "work_type: models::WorktimeType", needs_review, comment, billable FROM worktime
+        WHERE employee_id = $1 AND start_time < $3 AND (end_time > $2 OR end_time IS NULL)
+        ORDER BY start_time"#,
         employee_id,
         lower_bound,
         upper_bound,
@@ -32,24 +72,121 @@ pub async fn get_timers_in_boundary(
     .await
 }
 
+pub(crate) async fn get_timers_page(
+    employee_id: &i32,
+    filter: &models::WorktimeFilter,
+    sort_field: models::WorktimeSortField,
+    direction: models::SortDirection,
+    page_request: &PageRequest,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Page<models::Worktime>> {
+    let keyset = match sort_field {
+        models::WorktimeSortField::StartTime => Keyset {
+            id_column: "worktime_id",
+            sort_expression: "start_time",
+            sort_type: "TIMESTAMPTZ",
+            direction,
+        },
+        models::WorktimeSortField::Duration => Keyset {
+            id_column: "worktime_id",
+            sort_expression: "COALESCE(timeduration, INTERVAL '0')",
+            sort_type: "INTERVAL",
+            direction,
+        },
+    };
+
+    let mut query_builder = query_builder::QueryBuilder::<sqlx::Postgres>::new(
+        "SELECT worktime_id, employee_id, task_id, start_time, end_time, timeduration, work_type, needs_review, comment, billable",
+    );
+    pagination::push_key_columns(&mut query_builder, &keyset);
+    query_builder
+        .push(" FROM worktime WHERE employee_id = ")
+        .push_bind(*employee_id);
+
+    if let Some(task_id) = filter.task_id {
+        query_builder.push(" AND task_id = ").push_bind(task_id);
+    }
+    if let Some(work_type) = filter.work_type {
+        query_builder.push(" AND work_type = ").push_bind(work_type);
+    }
+    if let Some(from) = filter.from {
+        query_builder.push(" AND start_time >= ").push_bind(from);
+    }
+    if let Some(to) = filter.to {
+        query_builder.push(" AND start_time < ").push_bind(to);
+    }
+    match filter.open {
+        Some(true) => {
+            query_builder.push(" AND end_time IS NULL");
+        }
+        Some(false) => {
+            query_builder.push(" AND end_time IS NOT NULL");
+        }
+        None => {}
+    }
+
+    pagination::push_cursor_conditions(&mut query_builder, &keyset, page_request);
+    pagination::push_order_and_limit(&mut query_builder, &keyset, page_request);
+
+    let rows = query_builder
+        .build_query_as::<Keyed<models::Worktime>>()
+        .fetch_all(pool)
+        .await?;
+
+    Ok(pagination::into_page(rows, page_request))
+}
+
+pub(crate) async fn get_running_timers(pool: &sqlx::PgPool) -> sqlx::Result<Vec<models::Worktime>> {
+    sqlx::query_as!(
+        models::Worktime,
+        r#"SELECT worktime_id, employee_id, task_id, start_time, end_time, timeduration, work_type as "work_type: models::WorktimeType", needs_review, comment, billable FROM worktime WHERE end_time IS NULL ORDER BY start_time"#
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub(crate) async fn get_timer(
+    worktime_id: i32,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Option<models::Worktime>> {
+    sqlx::query_as!(
+        models::Worktime,
+        r#"SELECT worktime_id, employee_id, task_id, start_time, end_time, timeduration, work_type as "work_type: models::WorktimeType", needs_review, comment, billable FROM worktime WHERE worktime_id = $1"#,
+        worktime_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Starts a timer on an active task, None if the task is archived, assigned to others only,
+/// not assigned at all while `unassigned_bookable` is not set or does not exist.
 pub(crate) async fn start_timer(
     employee_id: &i32,
     task_id: i32,
     worktype: models::WorktimeType,
+    unassigned_bookable: bool,
     pool: &sqlx::PgPool,
-) -> sqlx::Result<models::Worktime> {
+) -> sqlx::Result<Option<models::Worktime>> {
     sqlx::query_as!(
         models::Worktime,
         r#"
         INSERT INTO worktime(employee_id, task_id, work_type)
-        VALUES($1, $2, $3)
-        RETURNING worktime_id, employee_id, task_id, start_time, end_time, timeduration, work_type as "work_type: models::WorktimeType"
+        SELECT $1, task_id, $3 FROM task t WHERE task_id = $2 AND status = 'active'
+        AND (
+            ($4 AND NOT EXISTS (SELECT 1 FROM task_assignment a WHERE a.task_id = t.task_id))
+            OR EXISTS (
+                SELECT 1 FROM task_assignment a LEFT JOIN employee e ON e.employee_id = $1
+                WHERE a.task_id = t.task_id AND (a.employee_id = $1 OR a.team_id = e.team_id)
+            )
+        )
+        RETURNING worktime_id, employee_id, task_id, start_time, end_time, timeduration, work_type as "work_type: models::WorktimeType", needs_review, comment, billable
         "#,
         employee_id,
         task_id,
-        worktype as models::WorktimeType
+        worktype as models::WorktimeType,
+        unassigned_bookable
     )
-    .fetch_one(pool)
+    .fetch_optional(pool)
     .await
 }
 
@@ -63,7 +200,7 @@ pub(crate) async fn stop_timer(
         UPDATE worktime
         SET end_time = NOW()
         WHERE worktime_id = $1
-        RETURNING worktime_id, employee_id, task_id, start_time, end_time, timeduration, work_type as "work_type: models::WorktimeType"
+        RETURNING worktime_id, employee_id, task_id, start_time, end_time, timeduration, work_type as "work_type: models::WorktimeType", needs_review, comment, billable
         "#,
         worktime_id,
     )
@@ -71,12 +208,15 @@ pub(crate) async fn stop_timer(
     .await
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn update_timer(
     worktime_id: i32,
     task_id: Option<i32>,
     start_time: Option<chrono::DateTime<chrono::FixedOffset>>,
     end_time: Option<chrono::DateTime<chrono::FixedOffset>>,
     worktype: Option<models::WorktimeType>,
+    comment: Option<String>,
+    billable: Option<bool>,
     pool: &sqlx::PgPool,
 ) -> sqlx::Result<models::Worktime> {
     let mut query_builder =
@@ -104,7 +244,11 @@ pub(crate) async fn update_timer(
         if needs_comma {
             query_builder.push(", ");
         }
-        query_builder.push("end_time = ").push_bind(end_time);
+        // a corrected end time resolves a review of an automatically stopped timer
+        query_builder
+            .push("end_time = ")
+            .push_bind(end_time)
+            .push(", needs_review = FALSE");
         needs_comma = true;
     }
 
@@ -118,6 +262,22 @@ pub(crate) async fn update_timer(
         needs_comma = true;
     }
 
+    if let Some(comment) = comment {
+        if needs_comma {
+            query_builder.push(", ");
+        }
+        query_builder.push("comment = ").push_bind(comment);
+        needs_comma = true;
+    }
+
+    if let Some(billable) = billable {
+        if needs_comma {
+            query_builder.push(", ");
+        }
+        query_builder.push("billable = ").push_bind(billable);
+        needs_comma = true;
+    }
+
     if !needs_comma {
         return Err(sqlx::Error::RowNotFound); // No fields were provided to update
     }
@@ -125,12 +285,76 @@ pub(crate) async fn update_timer(
     let query = query_builder
         .push(" WHERE worktime_id = ")
         .push_bind(worktime_id)
-        .push(" RETURNING worktime_id, employee_id, task_id, start_time, end_time, timeduration, work_type")
+        .push(" RETURNING worktime_id, employee_id, task_id, start_time, end_time, timeduration, work_type, needs_review, comment, billable")
         .build_query_as::<models::Worktime>();
 
     query.fetch_one(pool).await
 }
 
+/// Stops every timer that has been running for longer than `max_open`, flags it for review
+/// and notifies the owning employee.
+pub(crate) async fn stop_forgotten_timers(
+    max_open: chrono::Duration,
+    policy: StopPolicy,
+    pool: &sqlx::PgPool,
+) -> sqlx::Result<Vec<models::Worktime>> {
+    let mut transaction = pool.begin().await?;
+
+    let mut query_builder =
+        query_builder::QueryBuilder::<sqlx::Postgres>::new("UPDATE worktime SET end_time = ");
+
+    match policy {
+        StopPolicy::AfterLimit => {
+            query_builder.push("start_time + ").push_bind(max_open);
+        }
+        StopPolicy::AtStart => {
+            query_builder.push("start_time");
+        }
+        StopPolicy::AtTimeOfDay(time) => {
+            // employees without an own timezone use the one of the company
+            let company_timezone = time_utils::company_timezone().name();
+            let timezone = "(SELECT COALESCE(e.timezone, ";
+            let timezone_end = ") FROM employee e WHERE e.employee_id = worktime.employee_id)";
+            query_builder
+                .push("GREATEST(start_time, ((start_time AT TIME ZONE ")
+                .push(timezone)
+                .push_bind(company_timezone)
+                .push(timezone_end)
+                .push(")::date + ")
+                .push_bind(time)
+                .push(") AT TIME ZONE ")
+                .push(timezone)
+                .push_bind(company_timezone)
+                .push(timezone_end)
+                .push(")");
+        }
+    }
+
+    let stopped = query_builder
+        .push(", needs_review = TRUE WHERE end_time IS NULL AND start_time < NOW() - ")
+        .push_bind(max_open)
+        .push(" RETURNING worktime_id, employee_id, task_id, start_time, end_time, timeduration, work_type, needs_review, comment, billable")
+        .build_query_as::<models::Worktime>()
+        .fetch_all(&mut *transaction)
+        .await?;
+
+    for worktime in &stopped {
+        // in the timezone the cutoff was computed in
+        let timezone = employee::get_timezone(&worktime.employee_id, pool).await?;
+        let message = format!(
+            "Your timer started at {} {} was still running and has been stopped automatically. Please review it.",
+            worktime.start_time.with_timezone(&timezone).format("%Y-%m-%d %H:%M"),
+            timezone.name()
+        );
+        notification::create_notification(&worktime.employee_id, &message, &mut *transaction)
+            .await?;
+    }
+
+    transaction.commit().await?;
+
+    Ok(stopped)
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
@@ -171,6 +395,111 @@ mod tests {
         Ok(())
     }
 
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/task.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+        "../../fixtures/worktime.sql"
+    ))]
+    async fn test_get_timers_page(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        let filter = models::WorktimeFilter {
+            work_type: Some(models::WorktimeType::Ride),
+            from: chrono::DateTime::parse_from_rfc3339("2024-01-01T00:00:00+00:00").ok(),
+            to: chrono::DateTime::parse_from_rfc3339("2024-01-05T00:00:00+00:00").ok(),
+            open: Some(false),
+            ..Default::default()
+        };
+        let mut page_request = PageRequest {
+            first: Some(2),
+            ..Default::default()
+        };
+
+        let page = get_timers_page(
+            &1,
+            &filter,
+            models::WorktimeSortField::StartTime,
+            models::SortDirection::Asc,
+            &page_request,
+            &pool,
+        )
+        .await?;
+
+        let ids: Vec<i32> = page.items.iter().map(|(_, w)| w.worktime_id).collect();
+        assert_eq!(ids, vec![3, 4]);
+        assert!(!page.has_previous_page);
+        assert!(page.has_next_page);
+
+        // the cursor keeps working after its row was deleted
+        let (cursor, _) = page.items.into_iter().last().unwrap();
+        sqlx::query!("DELETE FROM worktime WHERE worktime_id = 4")
+            .execute(&pool)
+            .await?;
+        page_request.after = Some(cursor);
+        let page = get_timers_page(
+            &1,
+            &filter,
+            models::WorktimeSortField::StartTime,
+            models::SortDirection::Asc,
+            &page_request,
+            &pool,
+        )
+        .await?;
+
+        let ids: Vec<i32> = page.items.iter().map(|(_, w)| w.worktime_id).collect();
+        assert_eq!(ids, vec![7]);
+        assert!(page.has_previous_page);
+        assert!(!page.has_next_page);
+
+        // durations are cast back from the text of the cursor
+        let first_request = PageRequest {
+            first: Some(1),
+            ..Default::default()
+        };
+        let page = get_timers_page(
+            &1,
+            &filter,
+            models::WorktimeSortField::Duration,
+            models::SortDirection::Desc,
+            &first_request,
+            &pool,
+        )
+        .await?;
+        let (cursor, longest) = page.items.into_iter().next().unwrap();
+        let page = get_timers_page(
+            &1,
+            &filter,
+            models::WorktimeSortField::Duration,
+            models::SortDirection::Desc,
+            &PageRequest {
+                after: Some(cursor),
+                ..first_request
+            },
+            &pool,
+        )
+        .await?;
+        assert_ne!(page.items[0].1.worktime_id, longest.worktime_id);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/task.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+        "../../fixtures/worktime.sql"
+    ))]
+    async fn test_get_running_timers(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        let running = get_running_timers(&pool).await?;
+
+        assert_eq!(running.len(), 1);
+        assert_eq!(running[0].worktime_id, 2);
+        assert_eq!(running[0].end_time, None);
+
+        Ok(())
+    }
+
     #[sqlx::test(fixtures(
         "../../fixtures/truncate.sql",
         "../../fixtures/task.sql",
@@ -178,7 +507,9 @@ mod tests {
         "../../fixtures/employee.sql"
     ))]
     async fn test_start_timer(pool: sqlx::PgPool) -> sqlx::Result<()> {
-        let worktime = &start_timer(&1, 1, models::WorktimeType::Break, &pool).await?;
+        let worktime = &start_timer(&1, 1, models::WorktimeType::Break, true, &pool)
+            .await?
+            .unwrap();
 
         assert_eq!(worktime.employee_id, 1);
         assert_eq!(worktime.task_id, 1);
@@ -189,6 +520,27 @@ mod tests {
         Ok(())
     }
 
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/task.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql"
+    ))]
+    async fn test_can_not_start_timer_on_archived_task(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        crate::service::task::set_task_status(1, models::ArchiveStatus::Archived, &pool).await?;
+
+        assert!(start_timer(&1, 1, models::WorktimeType::Work, true, &pool)
+            .await?
+            .is_none());
+        assert!(
+            start_timer(&1, 999, models::WorktimeType::Work, true, &pool)
+                .await?
+                .is_none()
+        );
+
+        Ok(())
+    }
+
     #[sqlx::test(fixtures(
         "../../fixtures/truncate.sql",
         "../../fixtures/task.sql",
@@ -216,7 +568,7 @@ mod tests {
         "../../fixtures/worktime.sql"
     ))]
     async fn test_update_timer_task(pool: sqlx::PgPool) -> sqlx::Result<()> {
-        let worktime = &update_timer(1, Some(2), None, None, None, &pool).await?;
+        let worktime = &update_timer(1, Some(2), None, None, None, None, None, &pool).await?;
 
         assert_eq!(worktime.employee_id, 1);
         assert_eq!(worktime.task_id, 2);
@@ -254,6 +606,8 @@ mod tests {
             chrono::DateTime::parse_from_rfc3339("2024-01-01T09:00:00+00:00").ok(),
             None,
             None,
+            None,
+            None,
             &pool,
         )
         .await?;
@@ -294,6 +648,8 @@ mod tests {
             None,
             chrono::DateTime::parse_from_rfc3339("2024-01-01T15:00:00+00:00").ok(),
             None,
+            None,
+            None,
             &pool,
         )
         .await?;
@@ -328,8 +684,17 @@ mod tests {
         "../../fixtures/worktime.sql"
     ))]
     async fn test_update_timer_worktype(pool: sqlx::PgPool) -> sqlx::Result<()> {
-        let worktime =
-            &update_timer(1, None, None, None, Some(models::WorktimeType::Ride), &pool).await?;
+        let worktime = &update_timer(
+            1,
+            None,
+            None,
+            None,
+            Some(models::WorktimeType::Ride),
+            None,
+            None,
+            &pool,
+        )
+        .await?;
 
         assert_eq!(worktime.employee_id, 1);
         assert_eq!(worktime.task_id, 1);
@@ -353,6 +718,35 @@ mod tests {
         Ok(())
     }
 
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/task.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+        "../../fixtures/worktime.sql"
+    ))]
+    async fn test_update_timer_comment(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        let worktime = update_timer(
+            1,
+            None,
+            None,
+            None,
+            None,
+            Some(String::from("customer was not at home")),
+            None,
+            &pool,
+        )
+        .await?;
+
+        assert_eq!(
+            worktime.comment.as_deref(),
+            Some("customer was not at home")
+        );
+        assert_eq!(worktime.work_type, models::WorktimeType::Break);
+
+        Ok(())
+    }
+
     #[sqlx::test(fixtures(
         "../../fixtures/truncate.sql",
         "../../fixtures/task.sql",
@@ -394,4 +788,210 @@ mod tests {
 
         Ok(())
     }
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/task.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+        "../../fixtures/worktime.sql"
+    ))]
+    async fn test_get_timers_overlapping(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        // given are the afternoon of the first of january in which no worktime started
+        let lower_bound = chrono::DateTime::from_str("2024-01-01T12:00:00Z").unwrap();
+        let upper_bound = chrono::DateTime::from_str("2024-01-02T00:00:00Z").unwrap();
+        assert!(get_timers_in_boundary(&1, lower_bound, upper_bound, &pool)
+            .await?
+            .is_empty());
+
+        // when the overlapping worktimes are loaded
+        let worktimes = get_timers_overlapping(&1, lower_bound, upper_bound, &pool).await?;
+
+        // then the ones of the morning which lasted until the afternoon are found
+        let ids: Vec<i32> = worktimes.iter().map(|w| w.worktime_id).collect();
+        assert_eq!(ids, vec![1, 3]);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/task.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+        "../../fixtures/worktime.sql"
+    ))]
+    async fn test_get_running_timers_overlapping(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        // given is a timer which was started before february and is still running
+        sqlx::query!(
+            "INSERT INTO worktime (worktime_id, employee_id, task_id, start_time, work_type) VALUES (100, 2, 1, '2024-01-31T20:00:00+00:00', 'work')"
+        )
+        .execute(&pool)
+        .await?;
+        let february = |day| {
+            chrono::DateTime::parse_from_rfc3339(&format!("2024-02-{:02}T00:00:00+00:00", day))
+                .unwrap()
+        };
+
+        // then it overlaps february, but not the days before it started
+        let worktimes = get_timers_overlapping(&2, february(1), february(29), &pool).await?;
+        assert!(worktimes.iter().any(|w| w.worktime_id == 100));
+        let before = get_timers_overlapping(
+            &2,
+            chrono::DateTime::parse_from_rfc3339("2024-01-01T00:00:00+00:00").unwrap(),
+            chrono::DateTime::parse_from_rfc3339("2024-01-31T00:00:00+00:00").unwrap(),
+            &pool,
+        )
+        .await?;
+        assert!(before.iter().all(|w| w.worktime_id != 100));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/task.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql",
+        "../../fixtures/worktime.sql"
+    ))]
+    async fn test_stop_forgotten_timers(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        sqlx::query!(
+            "INSERT INTO worktime (worktime_id, employee_id, task_id, start_time, work_type) VALUES (100, 2, 1, '2024-02-01T08:00:00+00:00', 'work')"
+        )
+        .execute(&pool)
+        .await?;
+
+        let stopped =
+            stop_forgotten_timers(chrono::Duration::hours(12), StopPolicy::AfterLimit, &pool)
+                .await?;
+
+        // the timer started just now (worktime_id 2) stays open
+        assert_eq!(stopped.len(), 1);
+        let worktime = &stopped[0];
+        assert_eq!(worktime.worktime_id, 100);
+        assert!(worktime.needs_review);
+        assert_eq!(
+            worktime.end_time.unwrap().to_rfc3339(),
+            "2024-02-01T20:00:00+00:00"
+        );
+
+        let notifications = notification::get_notifications(&2, &pool).await?;
+        assert_eq!(notifications.len(), 1);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/task.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql"
+    ))]
+    async fn test_stop_forgotten_timers_at_time_of_day(pool: sqlx::PgPool) -> sqlx::Result<()> {
+        let forgotten = start_timer(&1, 1, models::WorktimeType::Work, true, &pool)
+            .await?
+            .unwrap();
+        update_timer(
+            forgotten.worktime_id,
+            None,
+            chrono::DateTime::parse_from_rfc3339("2024-02-01T08:00:00+00:00").ok(),
+            None,
+            None,
+            None,
+            None,
+            &pool,
+        )
+        .await?;
+        let late = start_timer(&1, 1, models::WorktimeType::Work, true, &pool)
+            .await?
+            .unwrap();
+        update_timer(
+            late.worktime_id,
+            None,
+            chrono::DateTime::parse_from_rfc3339("2024-02-02T18:00:00+00:00").ok(),
+            None,
+            None,
+            None,
+            None,
+            &pool,
+        )
+        .await?;
+
+        let policy = StopPolicy::AtTimeOfDay(chrono::NaiveTime::from_hms_opt(17, 0, 0).unwrap());
+        let mut stopped = stop_forgotten_timers(chrono::Duration::hours(12), policy, &pool).await?;
+        stopped.sort_by_key(|worktime| worktime.start_time);
+
+        // 17:00 in Berlin is 16:00 UTC in winter
+        assert_eq!(stopped.len(), 2);
+        assert_eq!(
+            stopped[0].end_time.unwrap().to_rfc3339(),
+            "2024-02-01T16:00:00+00:00"
+        );
+        // a timer started after the closing time is closed at its start
+        assert_eq!(
+            stopped[1].end_time.unwrap().to_rfc3339(),
+            "2024-02-02T18:00:00+00:00"
+        );
+
+        // correcting the end time resolves the review
+        let corrected = update_timer(
+            stopped[1].worktime_id,
+            None,
+            None,
+            chrono::DateTime::parse_from_rfc3339("2024-02-02T20:00:00+00:00").ok(),
+            None,
+            None,
+            None,
+            &pool,
+        )
+        .await?;
+        assert!(!corrected.needs_review);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures(
+        "../../fixtures/truncate.sql",
+        "../../fixtures/task.sql",
+        "../../fixtures/address.sql",
+        "../../fixtures/employee.sql"
+    ))]
+    async fn test_stop_forgotten_timers_in_employee_timezone(
+        pool: sqlx::PgPool,
+    ) -> sqlx::Result<()> {
+        // given is an employee in New York with a forgotten timer
+        employee::update_timezone(&1, Some(String::from("America/New_York")), &pool).await?;
+        let forgotten = start_timer(&1, 1, models::WorktimeType::Work, true, &pool)
+            .await?
+            .unwrap();
+        update_timer(
+            forgotten.worktime_id,
+            None,
+            chrono::DateTime::parse_from_rfc3339("2024-02-01T14:00:00+00:00").ok(),
+            None,
+            None,
+            None,
+            None,
+            &pool,
+        )
+        .await?;
+
+        let policy = StopPolicy::AtTimeOfDay(chrono::NaiveTime::from_hms_opt(17, 0, 0).unwrap());
+        let stopped = stop_forgotten_timers(chrono::Duration::hours(12), policy, &pool).await?;
+
+        // then it is closed at 17:00 in New York
+        assert_eq!(stopped.len(), 1);
+        assert_eq!(
+            stopped[0].end_time.unwrap().to_rfc3339(),
+            "2024-02-01T22:00:00+00:00"
+        );
+        // and the employee is told the start in New York time
+        let notifications = notification::get_notifications(&1, &pool).await?;
+        assert!(notifications[0]
+            .message
+            .contains("2024-02-01 09:00 America/New_York"));
+
+        Ok(())
+    }
 }
@@ -1,6 +1,7 @@
 use std::str::FromStr;
 
-use chrono::{prelude::*, Duration};
+use chrono::{prelude::*, Duration, LocalResult, Months};
+use chrono_tz::Tz;
 
 #[allow(dead_code)]
 pub fn create_timestamp() -> String {
@@ -27,10 +28,141 @@ fn format_duration(duration: Duration) -> String {
     format!("P{}DT{}H{}M{}S", days, hours, minutes, seconds)
 }
 
+// timezone of the company, used for every employee without an own timezone
+pub fn company_timezone() -> Tz {
+    let name = dotenvy::var("COMPANY_TIMEZONE").unwrap_or_else(|_| String::from("Europe/Berlin"));
+    name.parse::<Tz>().unwrap_or_else(|_| {
+        tracing::warn!("COMPANY_TIMEZONE '{}' is unknown, using UTC", name);
+        Tz::UTC
+    })
+}
+
+pub fn resolve_timezone(name: Option<&str>) -> Tz {
+    name.and_then(|name| name.parse::<Tz>().ok())
+        .unwrap_or_else(company_timezone)
+}
+
+// first instant of the given day in the timezone, if midnight is skipped by a DST transition
+// the day starts with the first valid local time after it
+pub fn start_of_day(date: NaiveDate, timezone: &Tz) -> DateTime<FixedOffset> {
+    let midnight = date.and_time(NaiveTime::MIN);
+
+    (0..=96)
+        .map(|quarter| midnight + Duration::minutes(15 * quarter))
+        .find_map(|local| match timezone.from_local_datetime(&local) {
+            LocalResult::Single(time) => Some(time),
+            LocalResult::Ambiguous(earliest, _) => Some(earliest),
+            LocalResult::None => None,
+        })
+        .map(|time| time.fixed_offset())
+        .unwrap_or_else(|| midnight.and_utc().fixed_offset())
+}
+
+/// Start (inclusive) and end (exclusive) of the given local day.
+pub fn day_bounds(
+    date: NaiveDate,
+    timezone: &Tz,
+) -> (DateTime<FixedOffset>, DateTime<FixedOffset>) {
+    let next_day = date.succ_opt().unwrap_or(NaiveDate::MAX);
+    (
+        start_of_day(date, timezone),
+        start_of_day(next_day, timezone),
+    )
+}
+
+/// Start (inclusive) and end (exclusive) of the given local month.
+pub fn month_bounds(
+    year: i32,
+    month: u32,
+    timezone: &Tz,
+) -> Option<(DateTime<FixedOffset>, DateTime<FixedOffset>)> {
+    let first_day = NaiveDate::from_ymd_opt(year, month, 1)?;
+    let next_month = first_day.checked_add_months(Months::new(1))?;
+    Some((
+        start_of_day(first_day, timezone),
+        start_of_day(next_month, timezone),
+    ))
+}
+
+// the calendar day a point in time belongs to in the timezone
+pub fn local_date(time: DateTime<Utc>, timezone: &Tz) -> NaiveDate {
+    time.with_timezone(timezone).date_naive()
+}
+
+/// Splits the interval at every local midnight into the minutes of each day. The minutes are
+/// counted from the start of the whole interval, so together they are as many as the interval
+/// has, also if a part of it is shorter than a minute.
+pub fn minutes_per_day(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    timezone: &Tz,
+) -> Vec<(NaiveDate, i64)> {
+    let mut days = Vec::new();
+    let mut date = local_date(start, timezone);
+    let mut part_start = start;
+
+    while part_start < end {
+        let Some(next_day) = date.succ_opt() else {
+            break;
+        };
+        let part_end = end.min(start_of_day(next_day, timezone).to_utc());
+        let minutes = (part_end - start).num_minutes() - (part_start - start).num_minutes();
+        days.push((date, minutes));
+
+        part_start = part_end;
+        date = next_day;
+    }
+
+    days
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_minutes_per_day() {
+        let berlin: Tz = "Europe/Berlin".parse().unwrap();
+        let date = |day| NaiveDate::from_ymd_opt(2024, 1, day).unwrap();
+
+        // a night shift from 22:00 to 06:30 local time
+        let days = minutes_per_day(
+            Utc.with_ymd_and_hms(2024, 1, 31, 21, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 2, 1, 5, 30, 0).unwrap(),
+            &berlin,
+        );
+        assert_eq!(
+            days,
+            vec![
+                (date(31), 120),
+                (NaiveDate::from_ymd_opt(2024, 2, 1).unwrap(), 390)
+            ]
+        );
+
+        // three days, the seconds are only counted once for the whole interval
+        let days = minutes_per_day(
+            Utc.with_ymd_and_hms(2024, 1, 1, 22, 59, 30).unwrap(),
+            Utc.with_ymd_and_hms(2024, 1, 3, 10, 0, 40).unwrap(),
+            &berlin,
+        );
+        assert_eq!(
+            days,
+            vec![(date(1), 0), (date(2), 24 * 60), (date(3), 11 * 60 + 1)]
+        );
+        assert_eq!(
+            days.iter().map(|(_, minutes)| minutes).sum::<i64>(),
+            35 * 60 + 1
+        );
+
+        // within a day
+        let days = minutes_per_day(
+            Utc.with_ymd_and_hms(2024, 1, 1, 8, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 1, 1, 9, 0, 0).unwrap(),
+            &berlin,
+        );
+        assert_eq!(days, vec![(date(1), 60)]);
+    }
+
     #[test]
     fn test_create_timestamp() {
         // Given: The function to create a timestamp
@@ -98,4 +230,71 @@ mod tests {
         // Then: An error is returned
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_day_bounds_in_berlin() {
+        let berlin = chrono_tz::Europe::Berlin;
+
+        // Given: a winter day
+        let (start, end) = day_bounds(NaiveDate::from_ymd_opt(2024, 1, 2).unwrap(), &berlin);
+
+        // Then: the day starts at 23:00 UTC of the day before
+        assert_eq!(start.to_rfc3339(), "2024-01-02T00:00:00+01:00");
+        assert_eq!(
+            start.with_timezone(&Utc).to_rfc3339(),
+            "2024-01-01T23:00:00+00:00"
+        );
+        assert_eq!(end - start, Duration::hours(24));
+    }
+
+    #[test]
+    fn test_day_bounds_on_dst_transitions() {
+        let berlin = chrono_tz::Europe::Berlin;
+
+        // Given: the days the clocks are changed
+        let (start, end) = day_bounds(NaiveDate::from_ymd_opt(2024, 3, 31).unwrap(), &berlin);
+        assert_eq!(end - start, Duration::hours(23));
+
+        let (start, end) = day_bounds(NaiveDate::from_ymd_opt(2024, 10, 27).unwrap(), &berlin);
+        assert_eq!(end - start, Duration::hours(25));
+    }
+
+    #[test]
+    fn test_start_of_day_skipped_midnight() {
+        // Given: Santiago de Chile skips midnight when DST starts
+        let santiago = chrono_tz::America::Santiago;
+
+        // When: the start of that day is calculated
+        let start = start_of_day(NaiveDate::from_ymd_opt(2024, 9, 8).unwrap(), &santiago);
+
+        // Then: the day starts at 01:00 local time
+        assert_eq!(start.to_rfc3339(), "2024-09-08T01:00:00-03:00");
+    }
+
+    #[test]
+    fn test_month_bounds() {
+        let berlin = chrono_tz::Europe::Berlin;
+
+        let (start, end) = month_bounds(2024, 12, &berlin).unwrap();
+
+        assert_eq!(start.to_rfc3339(), "2024-12-01T00:00:00+01:00");
+        assert_eq!(end.to_rfc3339(), "2025-01-01T00:00:00+01:00");
+        assert!(month_bounds(2024, 13, &berlin).is_none());
+    }
+
+    #[test]
+    fn test_local_date() {
+        let berlin = chrono_tz::Europe::Berlin;
+
+        // Given: a booking at 00:30 in Berlin
+        let time = DateTime::parse_from_rfc3339("2024-01-01T23:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        // Then: it belongs to the next day
+        assert_eq!(
+            local_date(time, &berlin),
+            NaiveDate::from_ymd_opt(2024, 1, 2).unwrap()
+        );
+    }
 }